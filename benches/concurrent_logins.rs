@@ -0,0 +1,174 @@
+// Measures login latency under concurrent load, together with the latency of
+// `/health` probes issued while the logins run. The app runs on its own
+// four-worker runtime and the clients time requests from a separate one, so
+// time spent queued behind blocked workers is included. Requires the same
+// environment as the integration tests (TEST_DATABASE_URL, JWT and token hash
+// secrets).
+//
+//     cargo bench --bench concurrent_logins
+//
+// BENCH_CONCURRENCY (default 32) and BENCH_LOGINS (default 256) tune the load.
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_api_template::auth::auth_hashing::hash_password;
+use axum_api_template::db::models::user::NewUser;
+use axum_api_template::user::user_repository;
+use diesel::prelude::*;
+use serde_json::json;
+use std::env;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::{Builder, Handle};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+const PASSWORD: &str = "bench-password-123";
+const PROBE_INTERVAL: Duration = Duration::from_millis(5);
+
+fn main() {
+    let server = Builder::new_multi_thread().worker_threads(4).enable_all().build().unwrap();
+    let client = Builder::new_current_thread().enable_all().build().unwrap();
+
+    client.block_on(run(server.handle().clone()));
+}
+
+async fn run(server: Handle) {
+    dotenv::dotenv().ok();
+    let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+    env::set_var("DATABASE_URL", &database_url);
+
+    let concurrency = env_usize("BENCH_CONCURRENCY", 32);
+    let logins = env_usize("BENCH_LOGINS", 256);
+
+    let mut conn = PgConnection::establish(&database_url).expect("Failed to connect to the database");
+    let email = format!("bench-{}@example.com", Uuid::new_v4());
+    let user = user_repository::create_user(
+        &NewUser {
+            first_name: "Bench".to_string(),
+            last_name: "User".to_string(),
+            email: email.clone(),
+            password_hash: hash_password(PASSWORD).expect("Failed to hash password"),
+            is_active: Some(true),
+            is_verified: Some(true),
+            locale: None,
+        },
+        &mut conn,
+    )
+    .expect("Failed to create bench user");
+
+    let app = server.spawn(async { axum_api_template::app() }).await.unwrap();
+
+    for _ in 0..concurrency.min(8) {
+        login(&server, &app, &email).await;
+    }
+
+    let remaining = Arc::new(AtomicUsize::new(logins));
+    let done = Arc::new(AtomicBool::new(false));
+
+    let probe = {
+        let server = server.clone();
+        let app = app.clone();
+        let done = done.clone();
+        tokio::spawn(async move {
+            let mut samples = Vec::new();
+            while !done.load(Ordering::Relaxed) {
+                // Includes how late the probe wakes up, which is where a
+                // blocked executor shows.
+                let started = Instant::now();
+                tokio::time::sleep(PROBE_INTERVAL).await;
+                let request = Request::builder().uri("/health").body(Body::empty()).unwrap();
+                server.spawn(app.clone().oneshot(request)).await.unwrap().unwrap();
+                samples.push(started.elapsed().saturating_sub(PROBE_INTERVAL));
+            }
+            samples
+        })
+    };
+
+    let started = Instant::now();
+    let workers: Vec<_> = (0..concurrency)
+        .map(|_| {
+            let server = server.clone();
+            let app = app.clone();
+            let email = email.clone();
+            let remaining = remaining.clone();
+            tokio::spawn(async move {
+                let mut samples = Vec::new();
+                while remaining
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                    .is_ok()
+                {
+                    samples.push(login(&server, &app, &email).await);
+                }
+                samples
+            })
+        })
+        .collect();
+
+    let mut login_samples = Vec::new();
+    for worker in workers {
+        login_samples.extend(worker.await.unwrap());
+    }
+    let elapsed = started.elapsed();
+    done.store(true, Ordering::Relaxed);
+    let health_samples = probe.await.unwrap();
+
+    println!(
+        "{} logins, concurrency {}, {:.1} logins/s",
+        logins,
+        concurrency,
+        logins as f64 / elapsed.as_secs_f64()
+    );
+    report("login", login_samples);
+    report("health (during logins)", health_samples);
+
+    diesel::delete(axum_api_template::schema::users::table.find(user.id))
+        .execute(&mut conn)
+        .expect("Failed to remove bench user");
+}
+
+async fn login(server: &Handle, app: &Router, email: &str) -> Duration {
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::USER_AGENT, format!("bench-{}", Uuid::new_v4()))
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "email": email, "password": PASSWORD })).unwrap(),
+        ))
+        .unwrap();
+
+    let started = Instant::now();
+    let response = server.spawn(app.clone().oneshot(request)).await.unwrap().unwrap();
+    let elapsed = started.elapsed();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    elapsed
+}
+
+fn report(label: &str, mut samples: Vec<Duration>) {
+    samples.sort();
+    let percentile = |p: f64| {
+        let index = ((samples.len() as f64 * p).ceil() as usize).clamp(1, samples.len()) - 1;
+        samples[index].as_secs_f64() * 1000.0
+    };
+
+    println!(
+        "{:<24} n={:<5} p50={:>8.1}ms p95={:>8.1}ms p99={:>8.1}ms max={:>8.1}ms",
+        label,
+        samples.len(),
+        percentile(0.50),
+        percentile(0.95),
+        percentile(0.99),
+        percentile(1.0)
+    );
+}
+
+fn env_usize(name: &str, default: usize) -> usize {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
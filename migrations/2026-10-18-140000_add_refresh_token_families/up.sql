@@ -0,0 +1,7 @@
+ALTER TABLE refresh_tokens ADD COLUMN family_id UUID NOT NULL DEFAULT uuid_generate_v4();
+ALTER TABLE refresh_tokens ALTER COLUMN family_id DROP DEFAULT;
+ALTER TABLE refresh_tokens ADD COLUMN parent_id UUID REFERENCES refresh_tokens(id) ON DELETE SET NULL;
+ALTER TABLE refresh_tokens ADD COLUMN rotated_at TIMESTAMP;
+ALTER TABLE refresh_tokens ADD COLUMN revoked_at TIMESTAMP;
+
+CREATE INDEX idx_refresh_tokens_family_id ON refresh_tokens(family_id);
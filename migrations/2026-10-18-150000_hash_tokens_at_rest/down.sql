@@ -0,0 +1,13 @@
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_password_reset_token_hash_format;
+ALTER INDEX idx_users_password_reset_token_hash RENAME TO idx_users_password_reset_token;
+ALTER TABLE users RENAME COLUMN password_reset_token_hash TO password_reset_token;
+
+ALTER TABLE refresh_tokens DROP CONSTRAINT IF EXISTS refresh_tokens_token_hash_format;
+ALTER INDEX idx_refresh_tokens_token_hash RENAME TO idx_refresh_tokens_token;
+ALTER TABLE refresh_tokens RENAME COLUMN token_hash TO token;
+
+-- Hashed values cannot be turned back into usable tokens.
+DELETE FROM refresh_tokens;
+UPDATE users
+SET password_reset_token = NULL, password_reset_expires = NULL
+WHERE password_reset_token IS NOT NULL;
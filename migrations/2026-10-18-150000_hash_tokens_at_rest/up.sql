@@ -0,0 +1,15 @@
+-- Existing rows hold plaintext credentials and cannot be converted, so they are invalidated.
+DELETE FROM refresh_tokens;
+UPDATE users
+SET password_reset_token = NULL, password_reset_expires = NULL
+WHERE password_reset_token IS NOT NULL;
+
+ALTER TABLE refresh_tokens RENAME COLUMN token TO token_hash;
+ALTER INDEX idx_refresh_tokens_token RENAME TO idx_refresh_tokens_token_hash;
+ALTER TABLE refresh_tokens
+    ADD CONSTRAINT refresh_tokens_token_hash_format CHECK (token_hash ~ '^[0-9a-f]{64}$');
+
+ALTER TABLE users RENAME COLUMN password_reset_token TO password_reset_token_hash;
+ALTER INDEX idx_users_password_reset_token RENAME TO idx_users_password_reset_token_hash;
+ALTER TABLE users
+    ADD CONSTRAINT users_password_reset_token_hash_format CHECK (password_reset_token_hash ~ '^[0-9a-f]{64}$');
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_users_magic_link_token_hash;
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_magic_link_token_hash_format;
+ALTER TABLE users DROP COLUMN IF EXISTS magic_link_expires;
+ALTER TABLE users DROP COLUMN IF EXISTS magic_link_token_hash;
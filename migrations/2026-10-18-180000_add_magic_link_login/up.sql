@@ -0,0 +1,7 @@
+ALTER TABLE users ADD COLUMN magic_link_token_hash TEXT;
+ALTER TABLE users ADD COLUMN magic_link_expires TIMESTAMP;
+
+ALTER TABLE users
+    ADD CONSTRAINT users_magic_link_token_hash_format CHECK (magic_link_token_hash ~ '^[0-9a-f]{64}$');
+
+CREATE INDEX idx_users_magic_link_token_hash ON users(magic_link_token_hash);
@@ -0,0 +1,3 @@
+DELETE FROM revoked_tokens WHERE user_id IS NULL;
+
+ALTER TABLE revoked_tokens ALTER COLUMN user_id SET NOT NULL;
@@ -0,0 +1,3 @@
+-- Access tokens issued through the client credentials grant have no user, but
+-- can still be revoked.
+ALTER TABLE revoked_tokens ALTER COLUMN user_id DROP NOT NULL;
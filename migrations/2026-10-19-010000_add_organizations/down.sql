@@ -0,0 +1,5 @@
+ALTER TABLE users DROP COLUMN active_organization_id;
+
+DROP TABLE invitations;
+DROP TABLE memberships;
+DROP TABLE organizations;
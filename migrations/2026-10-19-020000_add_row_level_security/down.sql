@@ -0,0 +1,26 @@
+DROP POLICY invitations_tenant ON invitations;
+ALTER TABLE invitations NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE invitations DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY memberships_tenant ON memberships;
+ALTER TABLE memberships NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE memberships DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY webauthn_credentials_owner ON webauthn_credentials;
+ALTER TABLE webauthn_credentials NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE webauthn_credentials DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY oauth_consents_owner ON oauth_consents;
+ALTER TABLE oauth_consents NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE oauth_consents DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY mfa_recovery_codes_owner ON mfa_recovery_codes;
+ALTER TABLE mfa_recovery_codes NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE mfa_recovery_codes DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY api_keys_owner ON api_keys;
+ALTER TABLE api_keys NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE api_keys DISABLE ROW LEVEL SECURITY;
+
+DROP FUNCTION app_current_organization_id();
+DROP FUNCTION app_current_user_id();
@@ -0,0 +1,53 @@
+-- The request context set by the API with `set_config(..., true)`. Both are
+-- NULL outside a request (migrations, background jobs, login flows), where
+-- the policies below don't restrict anything.
+CREATE FUNCTION app_current_user_id() RETURNS UUID
+    LANGUAGE sql STABLE
+    AS $$ SELECT NULLIF(current_setting('app.current_user_id', true), '')::uuid $$;
+
+CREATE FUNCTION app_current_organization_id() RETURNS UUID
+    LANGUAGE sql STABLE
+    AS $$ SELECT NULLIF(current_setting('app.current_organization_id', true), '')::uuid $$;
+
+-- Rows owned by a single user. `FORCE` applies the policies to the table
+-- owner too, which is usually the role the API connects as.
+ALTER TABLE api_keys ENABLE ROW LEVEL SECURITY;
+ALTER TABLE api_keys FORCE ROW LEVEL SECURITY;
+CREATE POLICY api_keys_owner ON api_keys
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id());
+
+ALTER TABLE mfa_recovery_codes ENABLE ROW LEVEL SECURITY;
+ALTER TABLE mfa_recovery_codes FORCE ROW LEVEL SECURITY;
+CREATE POLICY mfa_recovery_codes_owner ON mfa_recovery_codes
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id());
+
+ALTER TABLE oauth_consents ENABLE ROW LEVEL SECURITY;
+ALTER TABLE oauth_consents FORCE ROW LEVEL SECURITY;
+CREATE POLICY oauth_consents_owner ON oauth_consents
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id());
+
+ALTER TABLE webauthn_credentials ENABLE ROW LEVEL SECURITY;
+ALTER TABLE webauthn_credentials FORCE ROW LEVEL SECURITY;
+CREATE POLICY webauthn_credentials_owner ON webauthn_credentials
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id());
+
+-- Tenant rows: your own memberships, and everything in the active
+-- organization.
+ALTER TABLE memberships ENABLE ROW LEVEL SECURITY;
+ALTER TABLE memberships FORCE ROW LEVEL SECURITY;
+CREATE POLICY memberships_tenant ON memberships
+    USING (
+        app_current_user_id() IS NULL
+        OR user_id = app_current_user_id()
+        OR organization_id = app_current_organization_id()
+    );
+
+-- Invitations are also visible to the invited address.
+ALTER TABLE invitations ENABLE ROW LEVEL SECURITY;
+ALTER TABLE invitations FORCE ROW LEVEL SECURITY;
+CREATE POLICY invitations_tenant ON invitations
+    USING (
+        app_current_user_id() IS NULL
+        OR organization_id = app_current_organization_id()
+        OR email = (SELECT lower(users.email) FROM users WHERE users.id = app_current_user_id())
+    );
@@ -0,0 +1,8 @@
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_verification_token_hash_format;
+ALTER INDEX idx_users_verification_token_hash RENAME TO idx_users_verification_token;
+ALTER TABLE users RENAME COLUMN verification_token_hash TO verification_token;
+
+-- Hashed values cannot be turned back into usable tokens.
+UPDATE users
+SET verification_token = NULL, verification_token_expires = NULL
+WHERE verification_token IS NOT NULL;
@@ -0,0 +1,9 @@
+-- Existing rows hold plaintext tokens and cannot be converted; users can request a new link.
+UPDATE users
+SET verification_token = NULL, verification_token_expires = NULL
+WHERE verification_token IS NOT NULL;
+
+ALTER TABLE users RENAME COLUMN verification_token TO verification_token_hash;
+ALTER INDEX idx_users_verification_token RENAME TO idx_users_verification_token_hash;
+ALTER TABLE users
+    ADD CONSTRAINT users_verification_token_hash_format CHECK (verification_token_hash ~ '^[0-9a-f]{64}$');
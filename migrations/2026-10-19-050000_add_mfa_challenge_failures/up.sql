@@ -0,0 +1,8 @@
+CREATE TABLE mfa_challenge_failures (
+    token_jti TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    failed_attempts INTEGER NOT NULL DEFAULT 1,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_mfa_challenge_failures_expires_at ON mfa_challenge_failures(expires_at);
@@ -0,0 +1,6 @@
+-- Refresh tokens are owned by a single user, like the tables in
+-- 2026-10-19-020000_add_row_level_security.
+ALTER TABLE refresh_tokens ENABLE ROW LEVEL SECURITY;
+ALTER TABLE refresh_tokens FORCE ROW LEVEL SECURITY;
+CREATE POLICY refresh_tokens_owner ON refresh_tokens
+    USING (app_current_user_id() IS NULL OR user_id = app_current_user_id());
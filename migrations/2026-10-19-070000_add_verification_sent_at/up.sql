@@ -0,0 +1,3 @@
+-- The resend cooldown counts from here rather than from the token expiry,
+-- which moves whenever the TTL is reconfigured.
+ALTER TABLE users ADD COLUMN verification_sent_at TIMESTAMP;
@@ -0,0 +1,70 @@
+use crate::auth::auth_dto::SessionResponse;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListUsersQuery {
+    #[validate(length(max = 255, message = "Search must be at most 255 characters"))]
+    pub search: Option<String>,
+
+    pub is_active: Option<bool>,
+
+    #[validate(range(min = 1, message = "Page must be at least 1"))]
+    pub page: Option<i64>,
+
+    #[validate(range(min = 1, max = 100, message = "Page size must be between 1 and 100"))]
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateUserRequest {
+    #[validate(length(min = 1, max = 255, message = "First name must be between 1 and 255 characters"))]
+    pub first_name: String,
+
+    #[validate(length(min = 1, max = 255, message = "Last name must be between 1 and 255 characters"))]
+    pub last_name: String,
+
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+
+    // Without a password the user is sent a password reset link instead.
+    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    pub password: Option<String>,
+
+    #[validate(length(min = 2, max = 35, message = "Locale must be between 2 and 35 characters"))]
+    pub locale: Option<String>,
+
+    pub is_verified: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminUserResponse {
+    pub id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub is_active: bool,
+    pub is_verified: bool,
+    pub two_factor_enabled: bool,
+    pub locale: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserListResponse {
+    pub users: Vec<AdminUserResponse>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserDetailResponse {
+    #[serde(flatten)]
+    pub user: AdminUserResponse,
+    pub roles: Vec<String>,
+    pub sessions: Vec<SessionResponse>,
+}
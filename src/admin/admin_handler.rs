@@ -0,0 +1,89 @@
+use crate::{
+    admin::{
+        admin_dto::{AdminUserResponse, CreateUserRequest, ListUsersQuery, UserDetailResponse, UserListResponse},
+        admin_service,
+    },
+    app::AppState,
+    errors::ApiResult,
+    rbac::rbac_middleware::{RequirePermission, UsersRead, UsersWrite},
+};
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn list_users_handler(
+    State(state): State<AppState>,
+    _: RequirePermission<UsersRead>,
+    Query(query): Query<ListUsersQuery>,
+) -> ApiResult<Json<UserListResponse>> {
+    query.validate()?;
+    let users = admin_service::list_users(query, state.pool).await?;
+    Ok(Json(users))
+}
+
+pub async fn get_user_handler(
+    State(state): State<AppState>,
+    _: RequirePermission<UsersRead>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<UserDetailResponse>> {
+    let user = admin_service::get_user(user_id, state.pool).await?;
+    Ok(Json(user))
+}
+
+pub async fn create_user_handler(
+    State(state): State<AppState>,
+    RequirePermission(admin, _): RequirePermission<UsersWrite>,
+    Json(payload): Json<CreateUserRequest>,
+) -> ApiResult<Json<AdminUserResponse>> {
+    payload.validate()?;
+    let user = admin_service::create_user(admin.id, payload, state.pool, &state.mailer).await?;
+    Ok(Json(user))
+}
+
+pub async fn deactivate_user_handler(
+    State(state): State<AppState>,
+    RequirePermission(admin, _): RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<AdminUserResponse>> {
+    let user = admin_service::set_user_active(admin.id, user_id, false, state.pool).await?;
+    Ok(Json(user))
+}
+
+pub async fn reactivate_user_handler(
+    State(state): State<AppState>,
+    RequirePermission(admin, _): RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<AdminUserResponse>> {
+    let user = admin_service::set_user_active(admin.id, user_id, true, state.pool).await?;
+    Ok(Json(user))
+}
+
+pub async fn force_password_reset_handler(
+    State(state): State<AppState>,
+    RequirePermission(admin, _): RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<serde_json::Value>> {
+    admin_service::force_password_reset(admin.id, user_id, state.pool, &state.mailer).await?;
+    Ok(Json(serde_json::json!({"message": "Password reset email sent"})))
+}
+
+pub async fn revoke_sessions_handler(
+    State(state): State<AppState>,
+    RequirePermission(admin, _): RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<serde_json::Value>> {
+    admin_service::revoke_sessions(admin.id, user_id, state.pool).await?;
+    Ok(Json(serde_json::json!({"message": "Sessions revoked"})))
+}
+
+pub async fn revoke_session_handler(
+    State(state): State<AppState>,
+    RequirePermission(admin, _): RequirePermission<UsersWrite>,
+    Path((user_id, session_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<Json<serde_json::Value>> {
+    admin_service::revoke_session(admin.id, user_id, session_id, state.pool).await?;
+    Ok(Json(serde_json::json!({"message": "Session revoked"})))
+}
@@ -0,0 +1,198 @@
+use crate::{
+    admin::admin_dto::{AdminUserResponse, CreateUserRequest, ListUsersQuery, UserDetailResponse, UserListResponse},
+    auth::{auth_hashing::hash_password_async, auth_service},
+    config::database::DbPool,
+    db::{
+        models::user::{NewUser, User},
+        with_connection, DbContext,
+    },
+    errors::{ApiError, ApiResult},
+    mail::{mail_notifications, mailer::Mailer},
+    rbac::rbac_service,
+    user::user_repository,
+};
+use diesel::{Connection, PgConnection};
+use uuid::Uuid;
+use std::sync::Arc;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+
+pub async fn list_users(query: ListUsersQuery, pool: DbPool) -> ApiResult<UserListResponse> {
+    let page = query.page.unwrap_or(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE);
+    let search = query.search.filter(|search| !search.trim().is_empty());
+
+    with_connection(&pool, move |conn| {
+        let search = search.as_deref().map(str::trim);
+        let total = user_repository::count_users(search, query.is_active, conn)?;
+        let users = user_repository::search_users(search, query.is_active, per_page, (page - 1) * per_page, conn)?;
+
+        Ok(UserListResponse {
+            users: users.into_iter().map(user_to_response).collect(),
+            total,
+            page,
+            per_page,
+        })
+    })
+    .await
+}
+
+pub async fn get_user(user_id: Uuid, pool: DbPool) -> ApiResult<UserDetailResponse> {
+    let (user, roles) = with_connection(&pool, move |conn| {
+        let user = find_user(user_id, conn)?;
+        let roles = rbac_service::user_role_names(user_id, conn)?;
+
+        Ok((user, roles))
+    })
+    .await?;
+
+    Ok(UserDetailResponse {
+        user: user_to_response(user),
+        roles,
+        sessions: auth_service::list_sessions(user_id, None, pool).await?,
+    })
+}
+
+pub async fn create_user(
+    actor_id: Uuid,
+    request: CreateUserRequest,
+    pool: DbPool,
+    mailer: &Arc<dyn Mailer>,
+) -> ApiResult<AdminUserResponse> {
+    let email = request.email.clone();
+    let email_taken = with_connection(&pool, move |conn| Ok(user_repository::email_exists(&email, conn)?)).await?;
+
+    if email_taken {
+        return Err(ApiError::ResourceAlreadyExists("Email already registered".to_string()));
+    }
+
+    let send_reset = request.password.is_none();
+    let password = request.password.unwrap_or_else(auth_service::generate_random_token);
+    let password_hash = hash_password_async(password)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Password hashing failed: {}", e)))?;
+
+    let new_user = NewUser {
+        first_name: request.first_name,
+        last_name: request.last_name,
+        email: request.email,
+        password_hash,
+        is_active: Some(true),
+        is_verified: Some(request.is_verified.unwrap_or(false)),
+        locale: request.locale,
+    };
+
+    let (user, reset_token) = with_connection(&pool, move |conn| {
+        let user = user_repository::create_user(&new_user, conn)?;
+
+        if send_reset {
+            let (user, reset_token) = auth_service::issue_password_reset(user, conn)?;
+            return Ok((user, Some(reset_token)));
+        }
+
+        Ok((user, None))
+    })
+    .await?;
+
+    tracing::info!(target: "security", actor_id = %actor_id, user_id = %user.id, "User created by administrator");
+
+    if let Some(reset_token) = reset_token {
+        mail_notifications::send_password_reset_email(mailer, &user, &reset_token);
+    }
+
+    Ok(user_to_response(user))
+}
+
+// Deactivated users can't log in, and their sessions, access tokens and API
+// keys stop working right away.
+pub async fn set_user_active(actor_id: Uuid, user_id: Uuid, active: bool, pool: DbPool) -> ApiResult<AdminUserResponse> {
+    if !active && actor_id == user_id {
+        return Err(ApiError::BadRequest("You can't deactivate your own account".to_string()));
+    }
+
+    let user = DbContext::unrestricted(with_connection(&pool, move |conn| {
+        find_user(user_id, conn)?;
+
+        conn.transaction(|conn| {
+            let user = user_repository::set_user_active(user_id, active, conn)?;
+            if !active {
+                auth_service::end_all_sessions(user_id, conn)?;
+            }
+
+            Ok(user)
+        })
+    }))
+    .await?;
+
+    tracing::info!(target: "security", actor_id = %actor_id, user_id = %user_id, active, "User activation changed");
+
+    Ok(user_to_response(user))
+}
+
+// Replaces the password with a random one, ends every session and emails a
+// reset link, so the user has to choose a new password to get back in.
+pub async fn force_password_reset(actor_id: Uuid, user_id: Uuid, pool: DbPool, mailer: &Arc<dyn Mailer>) -> ApiResult<()> {
+    let password_hash = hash_password_async(auth_service::generate_random_token())
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Password hashing failed: {}", e)))?;
+
+    let (user, reset_token) = DbContext::unrestricted(with_connection(&pool, move |conn| {
+        let user = find_user(user_id, conn)?;
+        if !user.is_active.unwrap_or(true) {
+            return Err(ApiError::BadRequest("The user is deactivated".to_string()));
+        }
+
+        conn.transaction(|conn| {
+            let user = user_repository::update_password(user_id, &password_hash, conn)?;
+            auth_service::end_all_sessions(user_id, conn)?;
+            auth_service::issue_password_reset(user, conn)
+        })
+    }))
+    .await?;
+
+    tracing::info!(target: "security", actor_id = %actor_id, user_id = %user_id, "Password reset forced");
+
+    mail_notifications::send_password_reset_email(mailer, &user, &reset_token);
+
+    Ok(())
+}
+
+pub async fn revoke_sessions(actor_id: Uuid, user_id: Uuid, pool: DbPool) -> ApiResult<()> {
+    DbContext::unrestricted(with_connection(&pool, move |conn| {
+        find_user(user_id, conn)?;
+        auth_service::end_all_sessions(user_id, conn)
+    }))
+    .await?;
+
+    tracing::info!(target: "security", actor_id = %actor_id, user_id = %user_id, "Sessions revoked by administrator");
+
+    Ok(())
+}
+
+pub async fn revoke_session(actor_id: Uuid, user_id: Uuid, session_id: Uuid, pool: DbPool) -> ApiResult<()> {
+    auth_service::revoke_session(user_id, session_id, pool).await?;
+
+    tracing::info!(target: "security", actor_id = %actor_id, user_id = %user_id, session_id = %session_id, "Session revoked by administrator");
+
+    Ok(())
+}
+
+fn find_user(user_id: Uuid, conn: &mut PgConnection) -> ApiResult<User> {
+    user_repository::find_any_user_by_id(user_id, conn)
+        .map_err(|_| ApiError::NotFound("User not found".to_string()))
+}
+
+fn user_to_response(user: User) -> AdminUserResponse {
+    AdminUserResponse {
+        id: user.id,
+        first_name: user.first_name,
+        last_name: user.last_name,
+        email: user.email,
+        is_active: user.is_active.unwrap_or(true),
+        is_verified: user.is_verified.unwrap_or(false),
+        two_factor_enabled: user.totp_enabled_at.is_some(),
+        locale: user.locale,
+        created_at: user.created_at,
+        updated_at: user.updated_at,
+    }
+}
@@ -0,0 +1,3 @@
+pub mod admin_handler;
+pub mod admin_service;
+pub mod admin_dto;
@@ -0,0 +1,45 @@
+use crate::{
+    api_key::{
+        api_key_dto::{ApiKeyResponse, CreateApiKeyRequest, CreatedApiKeyResponse},
+        api_key_service,
+    },
+    app::AppState,
+    auth::auth_middleware::AuthUser,
+    errors::ApiResult,
+};
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn list_api_keys_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+) -> ApiResult<Json<Vec<ApiKeyResponse>>> {
+    let api_keys = api_key_service::list_api_keys(authenticated_user.id, state.pool).await?;
+    Ok(Json(api_keys))
+}
+
+pub async fn create_api_key_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> ApiResult<Json<CreatedApiKeyResponse>> {
+    payload.validate()?;
+    let api_key = api_key_service::create_api_key(authenticated_user.id, payload, state.pool, &state.oauth).await?;
+    Ok(Json(api_key))
+}
+
+pub async fn revoke_api_key_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    api_key_service::revoke_api_key(authenticated_user.id, id, state.pool).await?;
+    Ok(Json(json!({
+        "message": "API key revoked"
+    })))
+}
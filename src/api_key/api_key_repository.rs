@@ -0,0 +1,61 @@
+use crate::db::models::api_key::{ApiKey, NewApiKey};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub fn create_api_key(new_key: &NewApiKey, conn: &mut PgConnection) -> QueryResult<ApiKey> {
+    use crate::schema::api_keys::dsl::*;
+
+    diesel::insert_into(api_keys)
+        .values(new_key)
+        .returning(ApiKey::as_returning())
+        .get_result(conn)
+}
+
+pub fn list_api_keys(user_id_val: Uuid, conn: &mut PgConnection) -> QueryResult<Vec<ApiKey>> {
+    use crate::schema::api_keys::dsl::*;
+
+    api_keys
+        .filter(user_id.eq(user_id_val))
+        .order(created_at.asc())
+        .select(ApiKey::as_select())
+        .load(conn)
+}
+
+pub fn delete_api_key(user_id_val: Uuid, key_id: Uuid, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::api_keys::dsl::*;
+
+    diesel::delete(api_keys.filter(id.eq(key_id)).filter(user_id.eq(user_id_val))).execute(conn)
+}
+
+// Keys stop working once they expire or their owner is deactivated.
+pub fn find_usable_api_key(key_hash_value: &str, conn: &mut PgConnection) -> QueryResult<Option<ApiKey>> {
+    use crate::schema::{api_keys, users};
+
+    let now = Utc::now().naive_utc();
+
+    api_keys::table
+        .inner_join(users::table)
+        .filter(api_keys::key_hash.eq(key_hash_value))
+        .filter(api_keys::expires_at.is_null().or(api_keys::expires_at.gt(now)))
+        .filter(users::is_active.eq(true))
+        .select(ApiKey::as_select())
+        .first(conn)
+        .optional()
+}
+
+// Usage is recorded at most once a minute per key to keep busy keys from
+// writing on every request.
+pub fn record_api_key_use(key_id: Uuid, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::api_keys::dsl::*;
+
+    let now = Utc::now().naive_utc();
+
+    diesel::update(
+        api_keys
+            .filter(id.eq(key_id))
+            .filter(last_used_at.is_null().or(last_used_at.lt(now - Duration::minutes(1)))),
+    )
+    .set(last_used_at.eq(Some(now)))
+    .execute(conn)
+}
@@ -0,0 +1,125 @@
+use crate::{
+    api_key::{
+        api_key_dto::{ApiKeyResponse, CreateApiKeyRequest, CreatedApiKeyResponse},
+        api_key_repository,
+    },
+    auth::{auth_hashing::hash_token, auth_middleware::AuthUser},
+    config::{database::DbPool, oauth::OAuthConfig},
+    db::{
+        models::api_key::{ApiKey, NewApiKey},
+        with_connection,
+    },
+    errors::{ApiError, ApiResult},
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
+use rand::Rng;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+// Keys look like `pat_<prefix>_<secret>`. The prefix is stored in clear so
+// users can tell their keys apart; only a hash of the whole key is kept.
+pub const API_KEY_PREFIX: &str = "pat_";
+
+pub async fn create_api_key(
+    user_id: Uuid,
+    request: CreateApiKeyRequest,
+    pool: DbPool,
+    config: &OAuthConfig,
+) -> ApiResult<CreatedApiKeyResponse> {
+    if let Some(scope) = request.scopes.iter().find(|scope| !config.is_supported_scope(scope)) {
+        return Err(ApiError::BadRequest(format!("Unsupported scope: {}", scope)));
+    }
+    if request.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(ApiError::BadRequest("Expiry must be in the future".to_string()));
+    }
+
+    let prefix = format!(
+        "{}{}",
+        API_KEY_PREFIX,
+        (0..8).map(|_| format!("{:x}", rand::rng().random_range(0..16u8))).collect::<String>()
+    );
+    let secret: [u8; 32] = rand::rng().random();
+    let key = format!("{}_{}", prefix, URL_SAFE_NO_PAD.encode(secret));
+
+    let mut scopes = request.scopes;
+    scopes.sort_unstable();
+    scopes.dedup();
+
+    let new_key = NewApiKey {
+        user_id,
+        name: request.name,
+        prefix,
+        key_hash: hash_token(&key),
+        scopes,
+        expires_at: request.expires_at.map(|expires_at| expires_at.naive_utc()),
+    };
+
+    with_connection(&pool, move |conn| {
+        let api_key = api_key_repository::create_api_key(&new_key, conn)?;
+
+        tracing::info!("API key {} created for user: {}", api_key.prefix, user_id);
+
+        Ok(CreatedApiKeyResponse {
+            api_key: api_key_to_response(api_key),
+            key,
+        })
+    })
+    .await
+}
+
+pub async fn list_api_keys(user_id: Uuid, pool: DbPool) -> ApiResult<Vec<ApiKeyResponse>> {
+    with_connection(&pool, move |conn| {
+        let api_keys = api_key_repository::list_api_keys(user_id, conn)?;
+        Ok(api_keys.into_iter().map(api_key_to_response).collect())
+    })
+    .await
+}
+
+pub async fn revoke_api_key(user_id: Uuid, key_id: Uuid, pool: DbPool) -> ApiResult<()> {
+    with_connection(&pool, move |conn| {
+        if api_key_repository::delete_api_key(user_id, key_id, conn)? == 0 {
+            return Err(ApiError::NotFound("API key not found".to_string()));
+        }
+
+        tracing::info!("API key {} revoked by user: {}", key_id, user_id);
+
+        Ok(())
+    })
+    .await
+}
+
+// API keys always carry scopes, so they are kept out of account management
+// like tokens issued to OAuth clients.
+pub async fn authenticate(key: String, pool: DbPool) -> ApiResult<Option<AuthUser>> {
+    with_connection(&pool, move |conn| {
+        let Some(api_key) = api_key_repository::find_usable_api_key(&hash_token(&key), conn)? else {
+            return Ok(None);
+        };
+
+        api_key_repository::record_api_key_use(api_key.id, conn)?;
+
+        let mut claims = Map::new();
+        claims.insert("api_key_id".to_string(), Value::String(api_key.id.to_string()));
+
+        Ok(Some(AuthUser {
+            id: api_key.user_id,
+            scopes: Some(api_key.scopes),
+            claims,
+        }))
+    })
+    .await
+}
+
+fn api_key_to_response(api_key: ApiKey) -> ApiKeyResponse {
+    ApiKeyResponse {
+        id: api_key.id,
+        name: api_key.name,
+        prefix: api_key.prefix,
+        scopes: api_key.scopes,
+        expires_at: api_key.expires_at,
+        last_used_at: api_key.last_used_at,
+        created_at: api_key.created_at,
+    }
+}
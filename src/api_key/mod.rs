@@ -0,0 +1,4 @@
+pub mod api_key_handler;
+pub mod api_key_service;
+pub mod api_key_repository;
+pub mod api_key_dto;
@@ -1,24 +1,52 @@
 use crate::auth::auth_repository;
+use crate::config::auth_cookies::AuthCookieConfig;
 use crate::config::database::{establish_connection_pool, DbPool};
+use crate::config::email_verification::EmailVerificationConfig;
+use crate::config::mail::establish_mailer;
+use crate::config::oauth::OAuthConfig;
+use crate::config::oidc::OidcConfig;
+use crate::config::webauthn::WebauthnConfig;
+use crate::db::with_connection;
+use crate::mfa::mfa_repository;
+use crate::oauth::oauth_repository;
+use crate::oidc::oidc_repository;
+use crate::webauthn::webauthn_repository;
+use crate::mail::mailer::Mailer;
 use crate::routes::create_routes;
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
 use hyper_util::rt::TokioIo;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio::net::TcpListener;
 use tower::ServiceExt;
+use axum::extract::ConnectInfo;
 use axum::{Extension, Router};
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: DbPool,
+    pub email_verification: EmailVerificationConfig,
+    pub mailer: Arc<dyn Mailer>,
+    pub webauthn: WebauthnConfig,
+    pub oidc: OidcConfig,
+    pub oauth: OAuthConfig,
+    pub auth_cookies: AuthCookieConfig,
 }
 
 pub fn app() -> Router {
     dotenv::dotenv().ok();
     
     let pool = establish_connection_pool();
-    let app_state = AppState { pool: pool.clone() };
+    let app_state = AppState {
+        pool: pool.clone(),
+        email_verification: EmailVerificationConfig::from_env(),
+        mailer: establish_mailer(),
+        webauthn: WebauthnConfig::from_env(),
+        oidc: OidcConfig::from_env(),
+        oauth: OAuthConfig::from_env(),
+        auth_cookies: AuthCookieConfig::from_env(),
+    };
 
     setup_token_cleanup_tasks(pool.clone());
 
@@ -27,22 +55,20 @@ pub fn app() -> Router {
 }
 
 fn setup_token_cleanup_tasks(pool: DbPool) {
-    let pool_init = pool.clone();
-    tokio::spawn(async move {
-        if let Ok(mut conn) = pool_init.get() {
-            let _ = auth_repository::clean_expired_refresh_tokens(&mut conn);
-            let _ = auth_repository::clean_expired_revoked_tokens(&mut conn);
-        }
-    });
-
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(86400));
         loop {
             interval.tick().await;
-            if let Ok(mut conn) = pool.get() {
-                let _ = auth_repository::clean_expired_refresh_tokens(&mut conn);
-                let _ = auth_repository::clean_expired_revoked_tokens(&mut conn);
-            }
+            let _ = with_connection(&pool, |conn| {
+                let _ = auth_repository::clean_expired_refresh_tokens(conn);
+                let _ = auth_repository::clean_expired_revoked_tokens(conn);
+                let _ = webauthn_repository::clean_expired_challenges(conn);
+                let _ = mfa_repository::clean_expired_challenge_failures(conn);
+                let _ = oidc_repository::clean_expired_login_states(conn);
+                let _ = oauth_repository::clean_expired_authorization_codes(conn);
+                Ok(())
+            })
+            .await;
         }
     });
 }
@@ -57,13 +83,19 @@ pub async fn start_server() -> Result<(), Box<dyn std::error::Error + Send + Syn
     tracing::info!("Servidor iniciado em http://{}", addr);
 
     loop {
-        let (stream, _) = listener.accept().await?;
+        let (stream, peer_addr) = listener.accept().await?;
         let io = TokioIo::new(stream);
         let app = app.clone();
 
         tokio::spawn(async move {
             if let Err(err) = http1::Builder::new()
-                .serve_connection(io, service_fn(move |req| app.clone().oneshot(req)))
+                .serve_connection(
+                    io,
+                    service_fn(move |mut req: hyper::Request<hyper::body::Incoming>| {
+                        req.extensions_mut().insert(ConnectInfo(peer_addr));
+                        app.clone().oneshot(req)
+                    }),
+                )
                 .await
             {
                 tracing::error!("Erro ao atender conexão: {:?}", err);
@@ -0,0 +1,24 @@
+use crate::{
+    app::AppState,
+    audit::{
+        audit_dto::{AuditEventPage, AuditEventQuery},
+        audit_service,
+    },
+    errors::ApiResult,
+    rbac::rbac_middleware::{AuditRead, RequirePermission},
+};
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use validator::Validate;
+
+pub async fn list_audit_events_handler(
+    State(state): State<AppState>,
+    _: RequirePermission<AuditRead>,
+    Query(query): Query<AuditEventQuery>,
+) -> ApiResult<Json<AuditEventPage>> {
+    query.validate()?;
+    let page = audit_service::list_events(query, state.pool).await?;
+    Ok(Json(page))
+}
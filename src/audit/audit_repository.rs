@@ -0,0 +1,55 @@
+use crate::audit::audit_dto::AuditEventQuery;
+use crate::db::models::audit_event::{AuditEvent, NewAuditEvent};
+use crate::schema::audit_events::dsl::*;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub fn insert_event(new_event: &NewAuditEvent, conn: &mut PgConnection) -> QueryResult<usize> {
+    diesel::insert_into(audit_events).values(new_event).execute(conn)
+}
+
+// Newest first; `before` is the position of the last event of the previous page.
+pub fn find_events(
+    filter: &AuditEventQuery,
+    before: Option<(DateTime<Utc>, Uuid)>,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> QueryResult<Vec<AuditEvent>> {
+    let mut query = audit_events.into_boxed();
+
+    if let Some(actor) = filter.actor_id {
+        query = query.filter(actor_id.eq(actor));
+    }
+    if let Some(kind) = &filter.target_type {
+        query = query.filter(target_type.eq(kind));
+    }
+    if let Some(target) = &filter.target_id {
+        query = query.filter(target_id.eq(target));
+    }
+    if let Some(name) = &filter.action {
+        query = query.filter(action.eq(name));
+    }
+    if let Some(result) = filter.outcome {
+        query = query.filter(outcome.eq(result.as_str()));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(occurred_at.ge(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(occurred_at.lt(to));
+    }
+    if let Some((cursor_time, cursor_id)) = before {
+        query = query.filter(
+            occurred_at
+                .lt(cursor_time)
+                .or(occurred_at.eq(cursor_time).and(id.lt(cursor_id))),
+        );
+    }
+
+    query
+        .order((occurred_at.desc(), id.desc()))
+        .limit(limit)
+        .select(AuditEvent::as_select())
+        .load(conn)
+}
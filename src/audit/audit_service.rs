@@ -0,0 +1,184 @@
+use crate::{
+    audit::{
+        audit_dto::{AuditEventPage, AuditEventQuery, AuditEventResponse, AuditOutcome},
+        audit_repository,
+    },
+    auth::auth_middleware::ClientInfo,
+    config::database::DbPool,
+    db::{
+        models::audit_event::{AuditEvent, NewAuditEvent},
+        with_connection,
+    },
+    errors::{ApiError, ApiResult},
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+pub const LOGIN: &str = "auth.login";
+pub const REGISTER: &str = "auth.register";
+pub const PASSWORD_RESET_REQUEST: &str = "auth.password_reset.request";
+pub const PASSWORD_RESET_COMPLETE: &str = "auth.password_reset.complete";
+pub const LOGOUT: &str = "auth.logout";
+pub const LOGOUT_EVERYWHERE: &str = "auth.logout_everywhere";
+pub const TOKEN_REFRESH: &str = "auth.token_refresh";
+pub const PROFILE_UPDATE: &str = "user.profile_update";
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+
+// One event: who (`actor`) did what (`action`) to what (`target`), and how it
+// went. The actor is only set once they are authenticated, so a failed login
+// names the account as its target but has no actor.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    action: &'static str,
+    outcome: AuditOutcome,
+    actor_id: Option<Uuid>,
+    target: Option<(&'static str, String)>,
+    payload: Map<String, Value>,
+}
+
+impl AuditEntry {
+    pub fn success(action: &'static str) -> Self {
+        Self::new(action, AuditOutcome::Success)
+    }
+
+    pub fn failure(action: &'static str) -> Self {
+        Self::new(action, AuditOutcome::Failure)
+    }
+
+    // Failures keep the error as their `reason`.
+    pub fn from_result<T>(action: &'static str, result: &ApiResult<T>) -> Self {
+        match result {
+            Ok(_) => Self::success(action),
+            Err(e) => Self::failure(action).with("reason", e.to_string()),
+        }
+    }
+
+    pub fn actor(mut self, actor_id: Option<Uuid>) -> Self {
+        self.actor_id = actor_id;
+        self
+    }
+
+    pub fn target_user(mut self, user_id: Option<Uuid>) -> Self {
+        self.target = user_id.map(|user_id| ("user", user_id.to_string()));
+        self
+    }
+
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.payload.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.outcome == AuditOutcome::Success
+    }
+
+    fn new(action: &'static str, outcome: AuditOutcome) -> Self {
+        Self {
+            action,
+            outcome,
+            actor_id: None,
+            target: None,
+            payload: Map::new(),
+        }
+    }
+}
+
+// Records events for one request, stamped with its client details.
+#[derive(Clone)]
+pub struct AuditLog {
+    pool: DbPool,
+    client: ClientInfo,
+}
+
+impl AuditLog {
+    pub fn new(pool: &DbPool, client: &ClientInfo) -> Self {
+        Self {
+            pool: pool.clone(),
+            client: client.clone(),
+        }
+    }
+
+    // Uses its own connection so that failures are kept when the request's
+    // work is rolled back. A failed write is logged but never fails the request.
+    pub async fn record(&self, entry: AuditEntry) {
+        let action = entry.action;
+        let (target_type, target_id) = entry.target.unzip();
+        let new_event = NewAuditEvent {
+            actor_id: entry.actor_id,
+            action: entry.action.to_string(),
+            target_type: target_type.map(str::to_string),
+            target_id,
+            ip_address: self.client.ip_address.clone(),
+            user_agent: self.client.user_agent.clone(),
+            outcome: entry.outcome.as_str().to_string(),
+            payload: Value::Object(entry.payload),
+        };
+
+        if let Err(e) = with_connection(&self.pool, move |conn| Ok(audit_repository::insert_event(&new_event, conn)?)).await {
+            tracing::error!(action, "Failed to record audit event: {}", e);
+        }
+    }
+}
+
+pub async fn list_events(query: AuditEventQuery, pool: DbPool) -> ApiResult<AuditEventPage> {
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    let before = query.cursor.as_deref().map(decode_cursor).transpose()?;
+
+    let mut events = with_connection(&pool, move |conn| {
+        Ok(audit_repository::find_events(&query, before, limit + 1, conn)?)
+    })
+    .await?;
+
+    let next_cursor = if events.len() as i64 > limit {
+        events.truncate(limit as usize);
+        events.last().map(encode_cursor)
+    } else {
+        None
+    };
+
+    Ok(AuditEventPage {
+        events: events.into_iter().map(event_to_response).collect(),
+        next_cursor,
+    })
+}
+
+// Cursors are opaque to clients: the position of the last event returned.
+fn encode_cursor(event: &AuditEvent) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", event.occurred_at.timestamp_micros(), event.id))
+}
+
+fn decode_cursor(cursor: &str) -> ApiResult<(DateTime<Utc>, Uuid)> {
+    let invalid = || ApiError::BadRequest("Invalid cursor".to_string());
+
+    let decoded = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+    let (micros, id) = decoded.split_once(':').ok_or_else(invalid)?;
+
+    let occurred_at = micros
+        .parse()
+        .ok()
+        .and_then(DateTime::from_timestamp_micros)
+        .ok_or_else(invalid)?;
+    let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+
+    Ok((occurred_at, id))
+}
+
+fn event_to_response(event: AuditEvent) -> AuditEventResponse {
+    AuditEventResponse {
+        id: event.id,
+        occurred_at: event.occurred_at,
+        actor_id: event.actor_id,
+        action: event.action,
+        target_type: event.target_type,
+        target_id: event.target_id,
+        ip_address: event.ip_address,
+        user_agent: event.user_agent,
+        outcome: event.outcome,
+        payload: event.payload,
+    }
+}
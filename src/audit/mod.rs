@@ -0,0 +1,4 @@
+pub mod audit_handler;
+pub mod audit_service;
+pub mod audit_repository;
+pub mod audit_dto;
@@ -0,0 +1,119 @@
+use crate::{
+    auth::{
+        auth_dto::{AuthResponse, LoginResponse},
+        auth_hashing::random_value,
+        auth_tokens::{ACCESS_TOKEN_TTL_MINUTES, REFRESH_TOKEN_TTL_DAYS},
+    },
+    config::auth_cookies::AuthCookieConfig,
+};
+use tower_cookies::cookie::time::Duration;
+use tower_cookies::{Cookie, Cookies};
+
+pub const ACCESS_TOKEN_COOKIE: &str = "access_token";
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
+pub const CSRF_TOKEN_COOKIE: &str = "csrf_token";
+pub const CSRF_TOKEN_HEADER: &str = "X-CSRF-Token";
+
+// The refresh cookie is only sent to `/auth`, where it is exchanged.
+const REFRESH_TOKEN_COOKIE_PATH: &str = "/auth";
+
+// In cookie mode the tokens move out of the response body into HttpOnly
+// cookies, next to a CSRF token the page can read.
+pub fn store_session(cookies: &Cookies, config: &AuthCookieConfig, mut response: AuthResponse) -> AuthResponse {
+    if !config.enabled {
+        return response;
+    }
+
+    if let Some(refresh_token) = response.refresh_token.take() {
+        cookies.add(build_cookie(
+            config,
+            REFRESH_TOKEN_COOKIE,
+            refresh_token,
+            REFRESH_TOKEN_COOKIE_PATH,
+            Duration::days(REFRESH_TOKEN_TTL_DAYS),
+        ));
+    }
+
+    if config.access_token {
+        if let Some(access_token) = response.access_token.take() {
+            cookies.add(build_cookie(
+                config,
+                ACCESS_TOKEN_COOKIE,
+                access_token,
+                "/",
+                Duration::minutes(ACCESS_TOKEN_TTL_MINUTES),
+            ));
+        }
+    }
+
+    let mut csrf_cookie = build_cookie(
+        config,
+        CSRF_TOKEN_COOKIE,
+        random_value(32),
+        "/",
+        Duration::days(REFRESH_TOKEN_TTL_DAYS),
+    );
+    csrf_cookie.set_http_only(false);
+    cookies.add(csrf_cookie);
+
+    response
+}
+
+pub fn store_login(cookies: &Cookies, config: &AuthCookieConfig, response: LoginResponse) -> LoginResponse {
+    match response {
+        LoginResponse::Authenticated(response) => LoginResponse::Authenticated(store_session(cookies, config, response)),
+        challenge => challenge,
+    }
+}
+
+pub fn clear_session(cookies: &Cookies, config: &AuthCookieConfig) {
+    if !config.enabled {
+        return;
+    }
+
+    for (name, path) in [
+        (ACCESS_TOKEN_COOKIE, "/"),
+        (REFRESH_TOKEN_COOKIE, REFRESH_TOKEN_COOKIE_PATH),
+        (CSRF_TOKEN_COOKIE, "/"),
+    ] {
+        cookies.remove(build_cookie(config, name, String::new(), path, Duration::ZERO));
+    }
+}
+
+pub fn refresh_token(cookies: &Cookies, config: &AuthCookieConfig) -> Option<String> {
+    read_cookie(cookies, config, REFRESH_TOKEN_COOKIE)
+}
+
+pub fn access_token(cookies: &Cookies, config: &AuthCookieConfig) -> Option<String> {
+    read_cookie(cookies, config, ACCESS_TOKEN_COOKIE).filter(|_| config.access_token)
+}
+
+fn read_cookie(cookies: &Cookies, config: &AuthCookieConfig, name: &str) -> Option<String> {
+    if !config.enabled {
+        return None;
+    }
+
+    cookies.get(name).map(|cookie| cookie.value().to_string())
+}
+
+fn build_cookie(
+    config: &AuthCookieConfig,
+    name: &'static str,
+    value: String,
+    path: &'static str,
+    max_age: Duration,
+) -> Cookie<'static> {
+    let mut cookie = Cookie::build((name, value))
+        .path(path)
+        .http_only(true)
+        .secure(config.secure)
+        .same_site(config.same_site)
+        .max_age(max_age)
+        .build();
+
+    if let Some(domain) = &config.domain {
+        cookie.set_domain(domain.clone());
+    }
+
+    cookie
+}
@@ -14,6 +14,9 @@ pub struct RegisterRequest {
 
     #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
     pub password: String,
+
+    #[validate(length(min = 2, max = 35, message = "Locale must be between 2 and 35 characters"))]
+    pub locale: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -36,6 +39,18 @@ pub struct ForgotPasswordRequest {
     pub email: String,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct MagicLinkRequest {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConsumeMagicLinkRequest {
+    #[validate(length(min = 1, message = "Login token is required"))]
+    pub token: String,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct ResetPasswordRequest {
     #[validate(length(min = 1, message = "Reset token is required"))]
@@ -45,13 +60,41 @@ pub struct ResetPasswordRequest {
     pub new_password: String,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1, message = "Verification token is required"))]
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResendVerificationRequest {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
-    pub access_token: String,
-    pub refresh_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
     pub user: UserInfo,
 }
 
+#[derive(Debug, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+    MfaRequired(MfaChallengeResponse),
+}
+
 #[derive(Debug, Serialize)]
 pub struct UserInfo {
     pub id: uuid::Uuid,
@@ -59,3 +102,15 @@ pub struct UserInfo {
     pub last_name: String,
     pub email: String,
 }
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    pub id: uuid::Uuid,
+    pub device_label: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub expires_at: chrono::NaiveDateTime,
+    pub current: bool,
+}
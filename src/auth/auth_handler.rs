@@ -1,67 +1,170 @@
 use crate::{
     app::AppState,
     auth::{
-        auth_dto::{LoginRequest, RegisterRequest, RefreshTokenRequest, ForgotPasswordRequest, ResetPasswordRequest, AuthResponse},
+        auth_dto::{LoginRequest, RegisterRequest, RefreshTokenRequest, ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest, ResendVerificationRequest, AuthResponse, LoginResponse, MagicLinkRequest, ConsumeMagicLinkRequest, SessionResponse},
+        auth_cookies,
         auth_service,
-        auth_middleware::AuthUser,
+        auth_tokens,
+        auth_middleware::{AccessTokenInfo, AuthUser, ClientInfo},
     },
-    errors::ApiResult,
+    errors::{ApiError, ApiResult},
 };
 use axum::{
-    extract::State,
+    extract::{Path, State},
     response::Json,
     Extension,
 };
+use jsonwebtoken::jwk::JwkSet;
+use tower_cookies::Cookies;
+use uuid::Uuid;
 use validator::Validate;
 
 pub async fn login_handler(
     State(state): State<AppState>,
+    client: ClientInfo,
+    cookies: Cookies,
     Json(payload): Json<LoginRequest>,
-) -> ApiResult<Json<AuthResponse>> {
+) -> ApiResult<Json<LoginResponse>> {
     payload.validate()?;
-    let auth_response = auth_service::login(payload, state.pool).await?;
-    Ok(Json(auth_response))
+    let auth_response = auth_service::login(payload, client, state.pool, &state.email_verification).await?;
+    Ok(Json(auth_cookies::store_login(&cookies, &state.auth_cookies, auth_response)))
 }
 
 pub async fn register_handler(
     State(state): State<AppState>,
+    client: ClientInfo,
+    cookies: Cookies,
     Json(payload): Json<RegisterRequest>,
 ) -> ApiResult<Json<AuthResponse>> {
     payload.validate()?;
-    let auth_response = auth_service::register(payload, state.pool).await?;
-    Ok(Json(auth_response))
+    let auth_response = auth_service::register(payload, client, state.pool, &state.email_verification, &state.mailer).await?;
+    Ok(Json(auth_cookies::store_session(&cookies, &state.auth_cookies, auth_response)))
 }
 
 pub async fn refresh_token_handler(
     State(state): State<AppState>,
-    Json(payload): Json<RefreshTokenRequest>,
+    client: ClientInfo,
+    cookies: Cookies,
+    payload: Option<Json<RefreshTokenRequest>>,
 ) -> ApiResult<Json<AuthResponse>> {
-    let auth_response = auth_service::refresh_token(payload, state.pool).await?;
-    Ok(Json(auth_response))
+    let payload = match payload {
+        Some(Json(payload)) => payload,
+        None => RefreshTokenRequest {
+            refresh_token: auth_cookies::refresh_token(&cookies, &state.auth_cookies)
+                .ok_or_else(|| ApiError::Unauthorized("Missing refresh token".to_string()))?,
+        },
+    };
+    let auth_response = auth_service::refresh_token(payload, client, state.pool, &state.mailer).await?;
+    Ok(Json(auth_cookies::store_session(&cookies, &state.auth_cookies, auth_response)))
 }
 
 pub async fn forgot_password_handler(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(payload): Json<ForgotPasswordRequest>,
 ) -> ApiResult<Json<serde_json::Value>> {
     payload.validate()?;
-    auth_service::forgot_password(payload, state.pool).await?;
+    auth_service::forgot_password(payload, client, state.pool, &state.mailer).await?;
     Ok(Json(serde_json::json!({"message": "If the email exists, a password reset link has been sent"})))
 }
 
+pub async fn magic_link_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<MagicLinkRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    payload.validate()?;
+    auth_service::request_magic_link(payload, state.pool, &state.mailer).await?;
+    Ok(Json(serde_json::json!({"message": "If the email exists, a login link has been sent"})))
+}
+
+pub async fn consume_magic_link_handler(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    cookies: Cookies,
+    Json(payload): Json<ConsumeMagicLinkRequest>,
+) -> ApiResult<Json<LoginResponse>> {
+    payload.validate()?;
+    let login_response = auth_service::consume_magic_link(payload, client, state.pool, &state.email_verification).await?;
+    Ok(Json(auth_cookies::store_login(&cookies, &state.auth_cookies, login_response)))
+}
+
 pub async fn reset_password_handler(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(payload): Json<ResetPasswordRequest>,
 ) -> ApiResult<Json<serde_json::Value>> {
     payload.validate()?;
-    auth_service::reset_password(payload, state.pool).await?;
+    auth_service::reset_password(payload, client, state.pool, &state.mailer).await?;
     Ok(Json(serde_json::json!({"message": "Password reset successfully"})))
 }
 
+pub async fn verify_email_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    payload.validate()?;
+    auth_service::verify_email(payload, state.pool).await?;
+    Ok(Json(serde_json::json!({"message": "Email verified successfully"})))
+}
+
+pub async fn resend_verification_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<ResendVerificationRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    payload.validate()?;
+    auth_service::resend_verification(payload, state.pool, &state.email_verification, &state.mailer).await?;
+    Ok(Json(serde_json::json!({"message": "If the account exists and is not yet verified, a verification email has been sent"})))
+}
+
 pub async fn logout_handler(
     State(state): State<AppState>,
-    Extension(authenticated_user): Extension<AuthUser>,
+    authenticated_user: AuthUser,
+    Extension(access_token): Extension<AccessTokenInfo>,
+    client: ClientInfo,
+    cookies: Cookies,
 ) -> ApiResult<Json<serde_json::Value>> {
-    auth_service::logout(authenticated_user.0, state.pool).await?;
+    auth_service::logout(authenticated_user.id, authenticated_user.claim("sid"), access_token, client, state.pool).await?;
+    auth_cookies::clear_session(&cookies, &state.auth_cookies);
     Ok(Json(serde_json::json!({"message": "Logged out successfully"})))
 }
+
+pub async fn logout_everywhere_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Extension(access_token): Extension<AccessTokenInfo>,
+    client: ClientInfo,
+    cookies: Cookies,
+) -> ApiResult<Json<serde_json::Value>> {
+    auth_service::logout_everywhere(authenticated_user.id, access_token, client, state.pool).await?;
+    auth_cookies::clear_session(&cookies, &state.auth_cookies);
+    Ok(Json(serde_json::json!({"message": "Logged out from all devices successfully"})))
+}
+
+pub async fn list_sessions_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+) -> ApiResult<Json<Vec<SessionResponse>>> {
+    let sessions = auth_service::list_sessions(authenticated_user.id, authenticated_user.claim("sid"), state.pool).await?;
+    Ok(Json(sessions))
+}
+
+pub async fn revoke_session_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<serde_json::Value>> {
+    auth_service::revoke_session(authenticated_user.id, id, state.pool).await?;
+    Ok(Json(serde_json::json!({"message": "Session revoked"})))
+}
+
+pub async fn revoke_other_sessions_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+) -> ApiResult<Json<serde_json::Value>> {
+    let revoked = auth_service::revoke_other_sessions(authenticated_user.id, authenticated_user.claim("sid"), state.pool).await?;
+    Ok(Json(serde_json::json!({"message": "Other sessions revoked", "revoked": revoked})))
+}
+
+pub async fn jwks_handler() -> Json<JwkSet> {
+    Json(auth_tokens::access_token_jwks())
+}
@@ -1,9 +1,147 @@
-use bcrypt::{hash, verify, DEFAULT_COST};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fmt;
+use tokio::sync::Semaphore;
 
-pub fn hash_password(password: &str) -> Result<String, bcrypt::BcryptError> {
-    hash(password, DEFAULT_COST)
+static HASHING_SLOTS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(hashing_concurrency()));
+
+#[derive(Debug)]
+pub struct PasswordHashError(String);
+
+impl fmt::Display for PasswordHashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for PasswordHashError {}
+
+pub fn hash_password(password: &str) -> Result<String, PasswordHashError> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    argon2_hasher()?
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| PasswordHashError(e.to_string()))
+}
+
+pub fn verify_password(password: &str, hash: &str) -> Result<bool, PasswordHashError> {
+    if is_bcrypt_hash(hash) {
+        return bcrypt::verify(password, hash).map_err(|e| PasswordHashError(e.to_string()));
+    }
+
+    let parsed = PasswordHash::new(hash).map_err(|e| PasswordHashError(e.to_string()))?;
+
+    match Argon2::default().verify_password(password.as_bytes(), &parsed) {
+        Ok(()) => Ok(true),
+        Err(argon2::password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(PasswordHashError(e.to_string())),
+    }
+}
+
+pub async fn hash_password_async(password: String) -> Result<String, PasswordHashError> {
+    run_hashing(move || hash_password(&password)).await
+}
+
+pub async fn verify_password_async(password: String, hash: String) -> Result<bool, PasswordHashError> {
+    run_hashing(move || verify_password(&password, &hash)).await
+}
+
+pub fn password_needs_rehash(hash: &str) -> bool {
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return true;
+    };
+    let Ok(params) = Params::try_from(&parsed) else {
+        return true;
+    };
+    let Ok(configured) = argon2_params() else {
+        return false;
+    };
+
+    parsed.algorithm != Algorithm::Argon2id.ident()
+        || parsed.version != Some(Version::V0x13.into())
+        || params.m_cost() != configured.m_cost()
+        || params.t_cost() != configured.t_cost()
+        || params.p_cost() != configured.p_cost()
+}
+
+pub fn hash_token(token: &str) -> String {
+    let secret = env::var("TOKEN_HASH_SECRET").expect("TOKEN_HASH_SECRET must be set");
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(token.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// `len` random bytes, base64url-encoded without padding: state, nonces,
+// challenges and client credentials.
+pub fn random_value(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::rng().random()).collect();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// The PKCE `S256` code challenge (RFC 7636).
+pub fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+async fn run_hashing<T, F>(f: F) -> Result<T, PasswordHashError>
+where
+    F: FnOnce() -> Result<T, PasswordHashError> + Send + 'static,
+    T: Send + 'static,
+{
+    let _slot = HASHING_SLOTS
+        .acquire()
+        .await
+        .map_err(|e| PasswordHashError(e.to_string()))?;
+
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| PasswordHashError(format!("Hashing task failed: {}", e)))?
+}
+
+fn hashing_concurrency() -> usize {
+    env::var("PASSWORD_HASHING_CONCURRENCY")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|&slots| slots > 0)
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+}
+
+fn is_bcrypt_hash(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+}
+
+fn argon2_hasher() -> Result<Argon2<'static>, PasswordHashError> {
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params()?))
+}
+
+fn argon2_params() -> Result<Params, PasswordHashError> {
+    let memory_kib = env_u32("ARGON2_MEMORY_KIB", Params::DEFAULT_M_COST);
+    let iterations = env_u32("ARGON2_ITERATIONS", Params::DEFAULT_T_COST);
+    let parallelism = env_u32("ARGON2_PARALLELISM", Params::DEFAULT_P_COST);
+
+    Params::new(memory_kib, iterations, parallelism, None)
+        .map_err(|e| PasswordHashError(format!("Invalid Argon2 parameters: {}", e)))
+}
+
+fn env_u32(name: &str, default: u32) -> u32 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
 }
 
-pub fn verify_password(password: &str, hash: &str) -> Result<bool, bcrypt::BcryptError> {
-    verify(password, hash)
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
 }
@@ -0,0 +1,471 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters, EllipticCurveKeyType,
+    Jwk, JwkSet, KeyAlgorithm, OctetKeyPairParameters, OctetKeyPairType, OctetKeyParameters, OctetKeyType,
+    PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum KeyError {
+    UnsupportedAlgorithm(String),
+    Io(String),
+    InvalidKey(String),
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::UnsupportedAlgorithm(alg) => write!(f, "Unsupported JWT algorithm: {}", alg),
+            KeyError::Io(msg) => write!(f, "Key IO error: {}", msg),
+            KeyError::InvalidKey(msg) => write!(f, "Invalid key: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+#[derive(Clone)]
+pub struct SigningKey {
+    pub algorithm: Algorithm,
+    pub kid: String,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    public_jwk: Option<Jwk>,
+}
+
+impl SigningKey {
+    pub fn from_secret(secret: &[u8]) -> Self {
+        let kid = jwk_thumbprint(&Jwk {
+            common: CommonParameters::default(),
+            algorithm: AlgorithmParameters::OctetKey(OctetKeyParameters {
+                key_type: OctetKeyType::Octet,
+                value: URL_SAFE_NO_PAD.encode(secret),
+            }),
+        });
+
+        Self {
+            algorithm: Algorithm::HS256,
+            kid,
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+            public_jwk: None,
+        }
+    }
+
+    pub fn from_pem(algorithm: Algorithm, private_pem: &[u8], public_pem: &[u8]) -> Result<Self, KeyError> {
+        let encoding_key = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(private_pem),
+            Algorithm::ES256 => EncodingKey::from_ec_pem(private_pem),
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(private_pem),
+            other => return Err(KeyError::UnsupportedAlgorithm(format!("{:?}", other))),
+        }
+        .map_err(|e| KeyError::InvalidKey(format!("private key: {}", e)))?;
+
+        let mut public_jwk = public_jwk_from_pem(algorithm, public_pem)?;
+        let kid = jwk_thumbprint(&public_jwk);
+        public_jwk.common.key_id = Some(kid.clone());
+
+        let decoding_key = DecodingKey::from_jwk(&public_jwk)
+            .map_err(|e| KeyError::InvalidKey(format!("public key: {}", e)))?;
+
+        let key = Self {
+            algorithm,
+            kid,
+            encoding_key,
+            decoding_key,
+            public_jwk: Some(public_jwk),
+        };
+        key.check_pair()?;
+
+        Ok(key)
+    }
+
+    pub fn from_env() -> Result<Self, KeyError> {
+        let algorithm = env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string());
+
+        match algorithm.as_str() {
+            "HS256" => {
+                let secret = env::var("JWT_ACCESS_SECRET").expect("JWT_ACCESS_SECRET must be set");
+                Ok(Self::from_secret(secret.as_bytes()))
+            }
+            "RS256" | "ES256" | "EdDSA" => {
+                let algorithm = algorithm.parse().map_err(|_| KeyError::UnsupportedAlgorithm(algorithm))?;
+                let private_pem = read_key_file(&key_path_from_env("JWT_PRIVATE_KEY_PATH")?)?;
+                let public_pem = read_key_file(&key_path_from_env("JWT_PUBLIC_KEY_PATH")?)?;
+                Self::from_pem(algorithm, &private_pem, &public_pem)
+            }
+            _ => Err(KeyError::UnsupportedAlgorithm(algorithm)),
+        }
+    }
+
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = kid.into();
+        if let Some(jwk) = self.public_jwk.as_mut() {
+            jwk.common.key_id = Some(self.kid.clone());
+        }
+        self
+    }
+
+    pub fn header(&self) -> Header {
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.kid.clone());
+        header
+    }
+
+    pub fn encoding_key(&self) -> &EncodingKey {
+        &self.encoding_key
+    }
+
+    pub fn decoding_key(&self) -> &DecodingKey {
+        &self.decoding_key
+    }
+
+    pub fn validation(&self) -> Validation {
+        Validation::new(self.algorithm)
+    }
+
+    pub fn public_jwk(&self) -> Option<&Jwk> {
+        self.public_jwk.as_ref()
+    }
+
+    fn check_pair(&self) -> Result<(), KeyError> {
+        let token = encode(&self.header(), &json!({}), &self.encoding_key)
+            .map_err(|e| KeyError::InvalidKey(format!("signing failed: {}", e)))?;
+
+        let mut validation = self.validation();
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+
+        decode::<serde_json::Value>(&token, &self.decoding_key, &validation)
+            .map(|_| ())
+            .map_err(|_| KeyError::InvalidKey("public key does not match private key".to_string()))
+    }
+}
+
+#[derive(Clone)]
+pub struct KeyringEntry {
+    pub key: SigningKey,
+    pub active_from: Option<DateTime<Utc>>,
+    pub retire_after: Option<DateTime<Utc>>,
+}
+
+impl KeyringEntry {
+    pub fn new(key: SigningKey) -> Self {
+        Self {
+            key,
+            active_from: None,
+            retire_after: None,
+        }
+    }
+
+    fn is_retired(&self, now: DateTime<Utc>) -> bool {
+        self.retire_after.is_some_and(|retire_after| now >= retire_after)
+    }
+
+    fn is_active(&self, now: DateTime<Utc>) -> bool {
+        !self.is_retired(now) && self.active_from.is_none_or(|active_from| now >= active_from)
+    }
+}
+
+#[derive(Clone)]
+pub struct Keyring {
+    entries: Vec<KeyringEntry>,
+}
+
+#[derive(Deserialize)]
+struct KeyringFileEntry {
+    kid: String,
+    algorithm: String,
+    secret: Option<String>,
+    private_key_path: Option<PathBuf>,
+    public_key_path: Option<PathBuf>,
+    active_from: Option<DateTime<Utc>>,
+    retire_after: Option<DateTime<Utc>>,
+}
+
+impl Keyring {
+    pub fn new(entries: Vec<KeyringEntry>) -> Result<Self, KeyError> {
+        if entries.is_empty() {
+            return Err(KeyError::InvalidKey("keyring has no keys".to_string()));
+        }
+
+        for (index, entry) in entries.iter().enumerate() {
+            if entries[..index].iter().any(|other| other.key.kid == entry.key.kid) {
+                return Err(KeyError::InvalidKey(format!("duplicate kid {}", entry.key.kid)));
+            }
+        }
+
+        Ok(Self { entries })
+    }
+
+    pub fn from_env() -> Result<Self, KeyError> {
+        match non_empty_env("JWT_KEYRING_PATH") {
+            Some(path) => Self::from_file(Path::new(&path)),
+            None => Self::new(vec![KeyringEntry::new(SigningKey::from_env()?)]),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, KeyError> {
+        let content = read_key_file(path)?;
+        let file_entries: Vec<KeyringFileEntry> = serde_json::from_slice(&content)
+            .map_err(|e| KeyError::InvalidKey(format!("{}: {}", path.display(), e)))?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let entries = file_entries
+            .into_iter()
+            .map(|entry| {
+                let key = match entry.algorithm.as_str() {
+                    "HS256" => {
+                        let secret = entry
+                            .secret
+                            .ok_or_else(|| KeyError::InvalidKey(format!("{}: missing secret", entry.kid)))?;
+                        SigningKey::from_secret(secret.as_bytes())
+                    }
+                    "RS256" | "ES256" | "EdDSA" => {
+                        let algorithm = entry
+                            .algorithm
+                            .parse()
+                            .map_err(|_| KeyError::UnsupportedAlgorithm(entry.algorithm.clone()))?;
+                        let (Some(private_key_path), Some(public_key_path)) =
+                            (entry.private_key_path, entry.public_key_path)
+                        else {
+                            return Err(KeyError::InvalidKey(format!("{}: missing key paths", entry.kid)));
+                        };
+                        let private_pem = read_key_file(&base_dir.join(private_key_path))?;
+                        let public_pem = read_key_file(&base_dir.join(public_key_path))?;
+                        SigningKey::from_pem(algorithm, &private_pem, &public_pem)?
+                    }
+                    _ => return Err(KeyError::UnsupportedAlgorithm(entry.algorithm)),
+                };
+
+                Ok(KeyringEntry {
+                    key: key.with_kid(entry.kid),
+                    active_from: entry.active_from,
+                    retire_after: entry.retire_after,
+                })
+            })
+            .collect::<Result<Vec<_>, KeyError>>()?;
+
+        Self::new(entries)
+    }
+
+    pub fn current_key(&self, now: DateTime<Utc>) -> Option<&SigningKey> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_active(now))
+            .max_by_key(|entry| entry.active_from)
+            .map(|entry| &entry.key)
+    }
+
+    pub fn verification_keys(&self, kid: Option<&str>, now: DateTime<Utc>) -> Vec<&SigningKey> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.is_retired(now))
+            .filter(|entry| kid.is_none_or(|kid| entry.key.kid == kid))
+            .map(|entry| &entry.key)
+            .collect()
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T, now: DateTime<Utc>) -> Result<String, KeyError> {
+        let key = self
+            .current_key(now)
+            .ok_or_else(|| KeyError::InvalidKey("no active signing key".to_string()))?;
+
+        encode(&key.header(), claims, key.encoding_key())
+            .map_err(|e| KeyError::InvalidKey(format!("signing failed: {}", e)))
+    }
+
+    pub fn decode<T: DeserializeOwned>(&self, token: &str, now: DateTime<Utc>, validation: &Validation) -> Option<T> {
+        let header = decode_header(token).ok()?;
+
+        self.verification_keys(header.kid.as_deref(), now)
+            .into_iter()
+            .filter(|key| key.algorithm == header.alg)
+            .find_map(|key| {
+                let mut validation = validation.clone();
+                validation.algorithms = vec![key.algorithm];
+                decode::<T>(token, key.decoding_key(), &validation).ok()
+            })
+            .map(|token_data| token_data.claims)
+    }
+
+    pub fn jwks(&self, now: DateTime<Utc>) -> JwkSet {
+        JwkSet {
+            keys: self
+                .verification_keys(None, now)
+                .into_iter()
+                .filter_map(|key| key.public_jwk().cloned())
+                .collect(),
+        }
+    }
+}
+
+fn key_path_from_env(variable: &str) -> Result<PathBuf, KeyError> {
+    non_empty_env(variable)
+        .map(PathBuf::from)
+        .ok_or_else(|| KeyError::Io(format!("{} must be set", variable)))
+}
+
+// An empty value, as in .env.example, counts as unset.
+fn non_empty_env(variable: &str) -> Option<String> {
+    env::var(variable).ok().filter(|value| !value.is_empty())
+}
+
+fn read_key_file(path: &Path) -> Result<Vec<u8>, KeyError> {
+    std::fs::read(path).map_err(|e| KeyError::Io(format!("{}: {}", path.display(), e)))
+}
+
+fn public_jwk_from_pem(algorithm: Algorithm, public_pem: &[u8]) -> Result<Jwk, KeyError> {
+    let der = pem_to_der(public_pem, "PUBLIC KEY")?;
+    let key_bytes = spki_public_key(&der).ok_or_else(|| KeyError::InvalidKey("malformed public key".to_string()))?;
+
+    let (key_algorithm, parameters) = match algorithm {
+        Algorithm::RS256 => {
+            let (n, e) = rsa_components(key_bytes)
+                .ok_or_else(|| KeyError::InvalidKey("malformed RSA public key".to_string()))?;
+            (
+                KeyAlgorithm::RS256,
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(n),
+                    e: URL_SAFE_NO_PAD.encode(e),
+                }),
+            )
+        }
+        Algorithm::ES256 => {
+            if key_bytes.len() != 65 || key_bytes[0] != 0x04 {
+                return Err(KeyError::InvalidKey("expected an uncompressed P-256 public key".to_string()));
+            }
+            (
+                KeyAlgorithm::ES256,
+                AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+                    key_type: EllipticCurveKeyType::EC,
+                    curve: EllipticCurve::P256,
+                    x: URL_SAFE_NO_PAD.encode(&key_bytes[1..33]),
+                    y: URL_SAFE_NO_PAD.encode(&key_bytes[33..]),
+                }),
+            )
+        }
+        Algorithm::EdDSA => {
+            if key_bytes.len() != 32 {
+                return Err(KeyError::InvalidKey("expected an Ed25519 public key".to_string()));
+            }
+            (
+                KeyAlgorithm::EdDSA,
+                AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: URL_SAFE_NO_PAD.encode(key_bytes),
+                }),
+            )
+        }
+        other => return Err(KeyError::UnsupportedAlgorithm(format!("{:?}", other))),
+    };
+
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            ..Default::default()
+        },
+        algorithm: parameters,
+    })
+}
+
+// RFC 7638: SHA-256 over the required members in lexicographic order.
+fn jwk_thumbprint(jwk: &Jwk) -> String {
+    let canonical = match &jwk.algorithm {
+        AlgorithmParameters::RSA(rsa) => format!(r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#, rsa.e, rsa.n),
+        AlgorithmParameters::EllipticCurve(ec) => {
+            format!(r#"{{"crv":"P-256","kty":"EC","x":"{}","y":"{}"}}"#, ec.x, ec.y)
+        }
+        AlgorithmParameters::OctetKeyPair(okp) => format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{}"}}"#, okp.x),
+        AlgorithmParameters::OctetKey(oct) => format!(r#"{{"k":"{}","kty":"oct"}}"#, oct.value),
+    };
+
+    URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes()))
+}
+
+fn pem_to_der(pem: &[u8], label: &str) -> Result<Vec<u8>, KeyError> {
+    let pem = std::str::from_utf8(pem).map_err(|_| KeyError::InvalidKey("PEM is not valid UTF-8".to_string()))?;
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+
+    let start = pem
+        .find(&begin)
+        .ok_or_else(|| KeyError::InvalidKey(format!("expected a \"{}\" PEM block", label)))?
+        + begin.len();
+    let stop = pem[start..]
+        .find(&end)
+        .ok_or_else(|| KeyError::InvalidKey(format!("unterminated \"{}\" PEM block", label)))?
+        + start;
+
+    let body: String = pem[start..stop].chars().filter(|c| !c.is_whitespace()).collect();
+    STANDARD
+        .decode(body)
+        .map_err(|e| KeyError::InvalidKey(format!("PEM body: {}", e)))
+}
+
+// SubjectPublicKeyInfo ::= SEQUENCE { algorithm AlgorithmIdentifier, subjectPublicKey BIT STRING }
+fn spki_public_key(der: &[u8]) -> Option<&[u8]> {
+    let (spki, _) = der_element(der, 0x30)?;
+    let (_, rest) = der_element(spki, 0x30)?;
+    let (bits, _) = der_element(rest, 0x03)?;
+
+    match bits.split_first() {
+        Some((0, key)) => Some(key),
+        _ => None,
+    }
+}
+
+// RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+fn rsa_components(der: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (sequence, _) = der_element(der, 0x30)?;
+    let (n, rest) = der_element(sequence, 0x02)?;
+    let (e, _) = der_element(rest, 0x02)?;
+
+    Some((strip_leading_zeros(n), strip_leading_zeros(e)))
+}
+
+fn strip_leading_zeros(mut value: &[u8]) -> &[u8] {
+    while value.len() > 1 && value[0] == 0 {
+        value = &value[1..];
+    }
+    value
+}
+
+fn der_element(input: &[u8], expected_tag: u8) -> Option<(&[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    if tag != expected_tag {
+        return None;
+    }
+
+    let (&first, rest) = rest.split_first()?;
+    let (length, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let length = rest[..count].iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        (length, &rest[count..])
+    };
+
+    if rest.len() < length {
+        return None;
+    }
+
+    Some(rest.split_at(length))
+}
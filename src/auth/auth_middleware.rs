@@ -1,18 +1,123 @@
-use crate::auth::{auth_tokens::validate_access_token, auth_repository};
-use crate::config::database::DbPool;
+use crate::api_key::api_key_service::{self, API_KEY_PREFIX};
+use crate::app::AppState;
+use crate::auth::{auth_cookies, auth_service, auth_tokens::validate_access_token};
+use crate::db::with_connection;
+use crate::errors::{ApiError, ApiResult};
+use crate::user::user_repository;
 use axum::{
-    extract::{Request, State},
-    http::StatusCode,
+    extract::{ConnectInfo, FromRequestParts, OriginalUri, Request, State},
+    http::{header, request::Parts, StatusCode},
     middleware::Next,
     response::Response,
 };
+use chrono::{DateTime, NaiveDateTime};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use tower_cookies::Cookies;
 use uuid::Uuid;
 
 #[derive(Clone, Debug)]
-pub struct AuthUser(pub Uuid);
+pub struct AuthUser {
+    pub id: Uuid,
+    pub scopes: Option<Vec<String>>,
+    pub claims: Map<String, Value>,
+}
+
+impl AuthUser {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.iter().any(|granted| granted == scope))
+    }
+
+    pub fn require_scope(&self, scope: &str) -> ApiResult<()> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(format!("The access token is missing the {} scope", scope)))
+        }
+    }
+
+    // Roles come from the token; permission checks go through `rbac_service`.
+    pub fn has_role(&self, role: &str) -> bool {
+        self.claim::<Vec<String>>("roles")
+            .is_some_and(|roles| roles.iter().any(|granted| granted == role))
+    }
+
+    pub fn claim<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        self.claims
+            .get(name)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthUser>()
+            .cloned()
+            .ok_or(StatusCode::UNAUTHORIZED)
+    }
+}
+
+// Where a request came from; recorded on the sessions it starts.
+#[derive(Clone, Debug, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|header| header.to_str().ok())
+            .map(|user_agent| user_agent.chars().take(512).collect());
+
+        // X-Forwarded-For is only honoured behind a proxy that sets it.
+        let forwarded_for = parts
+            .headers
+            .get("X-Forwarded-For")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.split(',').next())
+            .and_then(|ip| ip.trim().parse::<IpAddr>().ok())
+            .filter(|_| trust_proxy_headers());
+
+        let ip_address = forwarded_for
+            .or_else(|| {
+                parts
+                    .extensions
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|ConnectInfo(addr)| addr.ip())
+            })
+            .map(|ip| ip.to_string());
+
+        Ok(ClientInfo { user_agent, ip_address })
+    }
+}
+
+fn trust_proxy_headers() -> bool {
+    std::env::var("TRUST_PROXY_HEADERS")
+        .map(|value| value == "true")
+        .unwrap_or(false)
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessTokenInfo {
+    pub jti: String,
+    pub expires_at: NaiveDateTime,
+}
 
 pub async fn auth_middleware(
-    State(pool): State<DbPool>,
+    State(state): State<AppState>,
     mut req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
@@ -21,22 +126,120 @@ pub async fn auth_middleware(
         .get("Authorization")
         .and_then(|header| header.to_str().ok());
 
+    // API keys arrive in their own header or as a bearer token carrying the
+    // key prefix; anything else is treated as a JWT.
+    let api_key = req
+        .headers()
+        .get("X-API-Key")
+        .and_then(|header| header.to_str().ok())
+        .or_else(|| {
+            auth_header
+                .and_then(|header| header.strip_prefix("Bearer "))
+                .filter(|token| token.starts_with(API_KEY_PREFIX))
+        });
+
+    if let Some(api_key) = api_key {
+        let user = api_key_service::authenticate(api_key.to_string(), state.pool)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+
+        req.extensions_mut().insert(user);
+        return Ok(next.run(req).await);
+    }
+
+    // Browsers in cookie mode send the access token as a cookie instead. Any
+    // other Authorization scheme is rejected rather than falling back to it.
     let token = match auth_header {
-        Some(header) if header.starts_with("Bearer ") => &header[7..],
-        _ => return Err(StatusCode::UNAUTHORIZED),
+        Some(header) => header
+            .strip_prefix("Bearer ")
+            .ok_or(StatusCode::UNAUTHORIZED)?
+            .to_string(),
+        None => req
+            .extensions()
+            .get::<Cookies>()
+            .and_then(|cookies| auth_cookies::access_token(cookies, &state.auth_cookies))
+            .ok_or(StatusCode::UNAUTHORIZED)?,
     };
 
-    match validate_access_token(token) {
+    match validate_access_token(&token) {
         Ok(claims) => {
-            if let Ok(mut conn) = pool.get() {
-                if auth_repository::is_token_revoked(&claims.jti, &mut conn).unwrap_or(false) {
-                    return Err(StatusCode::UNAUTHORIZED);
-                }
+            let jti = claims.jti.clone();
+            let user_id = claims.sub;
+            let issued_at = claims.iat;
+            let session_id = claims.session_id();
+            let rejected = with_connection(&state.pool, move |conn| {
+                auth_service::is_access_token_revoked(&jti, user_id, issued_at, session_id, conn)
+            })
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to check access token revocation: {}", e);
+                StatusCode::SERVICE_UNAVAILABLE
+            })?;
+
+            if rejected {
+                return Err(StatusCode::UNAUTHORIZED);
             }
 
-            req.extensions_mut().insert(AuthUser(claims.sub));
+            let expires_at = DateTime::from_timestamp(claims.exp, 0)
+                .map(|dt| dt.naive_utc())
+                .ok_or(StatusCode::UNAUTHORIZED)?;
+
+            req.extensions_mut().insert(AuthUser {
+                id: claims.sub,
+                scopes: claims
+                    .scope
+                    .map(|scope| scope.split_whitespace().map(str::to_string).collect()),
+                claims: claims.custom,
+            });
+            req.extensions_mut().insert(AccessTokenInfo {
+                jti: claims.jti,
+                expires_at,
+            });
             Ok(next.run(req).await)
         }
         Err(_) => Err(StatusCode::UNAUTHORIZED),
     }
 }
+
+// Account management is reserved for first-party sessions; tokens issued to
+// OAuth clients carry a scope and are turned away.
+pub async fn reject_scoped_tokens(req: Request, next: Next) -> Result<Response, StatusCode> {
+    match req.extensions().get::<AuthUser>() {
+        Some(user) if user.scopes.is_none() => Ok(next.run(req).await),
+        Some(_) => Err(StatusCode::FORBIDDEN),
+        None => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+pub async fn require_verified_email(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let path = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+
+    if !state.email_verification.is_required_for(&path) {
+        return Ok(next.run(req).await);
+    }
+
+    let user_id = match req.extensions().get::<AuthUser>() {
+        Some(user) => user.id,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let user = with_connection(&state.pool, move |conn| Ok(user_repository::find_user_by_id(user_id, conn)))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    if !user.is_verified.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(next.run(req).await)
+}
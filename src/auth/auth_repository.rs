@@ -1,5 +1,6 @@
 use crate::db::models::{
     refresh_token::{RefreshToken, NewRefreshToken},
+    revoked_token::NewRevokedToken,
 };
 use diesel::prelude::*;
 use uuid::Uuid;
@@ -17,39 +18,89 @@ pub fn create_refresh_token(
 }
 
 pub fn find_refresh_token(
-    token_value: &str,
+    token_hash_value: &str,
     conn: &mut PgConnection,
 ) -> QueryResult<RefreshToken> {
     use crate::schema::refresh_tokens::dsl::*;
 
     refresh_tokens
-        .filter(token.eq(token_value))
+        .filter(token_hash.eq(token_hash_value))
         .filter(expires_at.gt(chrono::Utc::now().naive_utc()))
         .select(RefreshToken::as_select())
         .first(conn)
 }
 
-pub fn delete_refresh_token(
-    token_value: &str,
+pub fn mark_refresh_token_rotated(
+    token_id: Uuid,
+    conn: &mut PgConnection,
+) -> QueryResult<bool> {
+    use crate::schema::refresh_tokens::dsl::*;
+
+    let updated = diesel::update(
+        refresh_tokens
+            .filter(id.eq(token_id))
+            .filter(rotated_at.is_null())
+            .filter(revoked_at.is_null()),
+    )
+    .set(rotated_at.eq(Some(chrono::Utc::now().naive_utc())))
+    .execute(conn)?;
+
+    Ok(updated > 0)
+}
+
+pub fn count_child_refresh_tokens(parent: Uuid, conn: &mut PgConnection) -> QueryResult<i64> {
+    use crate::schema::refresh_tokens::dsl::*;
+
+    refresh_tokens
+        .filter(parent_id.eq(parent))
+        .count()
+        .get_result(conn)
+}
+
+pub fn revoke_refresh_token_family(
+    family_id_val: Uuid,
     conn: &mut PgConnection,
 ) -> QueryResult<usize> {
     use crate::schema::refresh_tokens::dsl::*;
 
-    diesel::delete(refresh_tokens.filter(token.eq(token_value)))
-        .execute(conn)
+    diesel::update(
+        refresh_tokens
+            .filter(family_id.eq(family_id_val))
+            .filter(revoked_at.is_null()),
+    )
+    .set(revoked_at.eq(Some(chrono::Utc::now().naive_utc())))
+    .execute(conn)
 }
 
-pub fn delete_user_refresh_tokens(
+// Tokens are marked revoked rather than deleted so access tokens carrying
+// their session id are rejected too.
+pub fn revoke_user_refresh_tokens(
     user_id_val: Uuid,
     conn: &mut PgConnection,
 ) -> QueryResult<usize> {
     use crate::schema::refresh_tokens::dsl::*;
 
-    diesel::delete(refresh_tokens.filter(user_id.eq(user_id_val)))
-        .execute(conn)
+    diesel::update(
+        refresh_tokens
+            .filter(user_id.eq(user_id_val))
+            .filter(revoked_at.is_null()),
+    )
+    .set(revoked_at.eq(Some(chrono::Utc::now().naive_utc())))
+    .execute(conn)
 }
 
+pub fn revoke_token(
+    new_revoked_token: &NewRevokedToken,
+    conn: &mut PgConnection,
+) -> QueryResult<usize> {
+    use crate::schema::revoked_tokens::dsl::*;
 
+    diesel::insert_into(revoked_tokens)
+        .values(new_revoked_token)
+        .on_conflict(token_jti)
+        .do_nothing()
+        .execute(conn)
+}
 
 pub fn is_token_revoked(
     token_jti_value: &str,
@@ -79,3 +130,97 @@ pub fn clean_expired_revoked_tokens(conn: &mut PgConnection) -> QueryResult<usiz
     diesel::delete(revoked_tokens.filter(expiry.lt(chrono::Utc::now().naive_utc())))
         .execute(conn)
 }
+
+pub fn revoke_client_refresh_tokens(
+    user_id_val: Uuid,
+    client_id_val: Uuid,
+    conn: &mut PgConnection,
+) -> QueryResult<usize> {
+    use crate::schema::refresh_tokens::dsl::*;
+
+    diesel::update(
+        refresh_tokens
+            .filter(user_id.eq(user_id_val))
+            .filter(client_id.eq(client_id_val))
+            .filter(revoked_at.is_null()),
+    )
+    .set(revoked_at.eq(Some(chrono::Utc::now().naive_utc())))
+    .execute(conn)
+}
+
+// The latest unrotated token of each first-party family stands for an active
+// session.
+pub fn find_active_sessions(
+    user_id_val: Uuid,
+    conn: &mut PgConnection,
+) -> QueryResult<Vec<RefreshToken>> {
+    use crate::schema::refresh_tokens::dsl::*;
+
+    refresh_tokens
+        .filter(user_id.eq(user_id_val))
+        .filter(client_id.is_null())
+        .filter(rotated_at.is_null())
+        .filter(revoked_at.is_null())
+        .filter(expires_at.gt(chrono::Utc::now().naive_utc()))
+        .distinct_on(family_id)
+        .order((family_id, created_at.desc()))
+        .select(RefreshToken::as_select())
+        .load(conn)
+}
+
+pub fn is_session_revoked(
+    user_id_val: Uuid,
+    family_id_val: Uuid,
+    conn: &mut PgConnection,
+) -> QueryResult<bool> {
+    use crate::schema::refresh_tokens::dsl::*;
+
+    diesel::select(diesel::dsl::exists(
+        refresh_tokens
+            .filter(family_id.eq(family_id_val))
+            .filter(user_id.eq(user_id_val))
+            .filter(revoked_at.is_not_null()),
+    ))
+    .get_result(conn)
+}
+
+pub fn revoke_user_session(
+    user_id_val: Uuid,
+    family_id_val: Uuid,
+    conn: &mut PgConnection,
+) -> QueryResult<usize> {
+    use crate::schema::refresh_tokens::dsl::*;
+
+    diesel::update(
+        refresh_tokens
+            .filter(user_id.eq(user_id_val))
+            .filter(family_id.eq(family_id_val))
+            .filter(client_id.is_null())
+            .filter(revoked_at.is_null())
+            .filter(expires_at.gt(chrono::Utc::now().naive_utc())),
+    )
+    .set(revoked_at.eq(Some(chrono::Utc::now().naive_utc())))
+    .execute(conn)
+}
+
+pub fn revoke_other_sessions(
+    user_id_val: Uuid,
+    current_family_id: Uuid,
+    conn: &mut PgConnection,
+) -> QueryResult<usize> {
+    use crate::schema::refresh_tokens::dsl::*;
+
+    let revoked: Vec<Uuid> = diesel::update(
+        refresh_tokens
+            .filter(user_id.eq(user_id_val))
+            .filter(family_id.ne(current_family_id))
+            .filter(client_id.is_null())
+            .filter(revoked_at.is_null())
+            .filter(expires_at.gt(chrono::Utc::now().naive_utc())),
+    )
+    .set(revoked_at.eq(Some(chrono::Utc::now().naive_utc())))
+    .returning(family_id)
+    .get_results(conn)?;
+
+    Ok(revoked.into_iter().collect::<std::collections::HashSet<_>>().len())
+}
@@ -1,29 +1,76 @@
 use crate::{
+    audit::audit_service::{self, AuditEntry, AuditLog},
     auth::{
-        auth_dto::{LoginRequest, RegisterRequest, RefreshTokenRequest, ForgotPasswordRequest, ResetPasswordRequest, AuthResponse, UserInfo},
-        auth_hashing::{hash_password, verify_password},
-        auth_tokens::{generate_access_token, generate_refresh_token, validate_refresh_token},
+        auth_dto::{LoginRequest, RegisterRequest, RefreshTokenRequest, ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest, ResendVerificationRequest, AuthResponse, UserInfo, LoginResponse, MfaChallengeResponse, MagicLinkRequest, ConsumeMagicLinkRequest, SessionResponse},
+        auth_hashing::{hash_password_async, hash_token, password_needs_rehash, verify_password_async},
+        auth_tokens::{generate_access_token_with, generate_mfa_challenge_token, generate_refresh_token, validate_mfa_challenge_token, validate_refresh_token, AccessTokenOptions, REFRESH_TOKEN_TTL_DAYS},
         auth_repository,
+        auth_middleware::{AccessTokenInfo, ClientInfo},
     },
-    config::database::DbPool,
-    db::models::{
-        user::{User, NewUser},
-        refresh_token::NewRefreshToken,
+    config::{database::DbPool, email_verification::EmailVerificationConfig},
+    db::{
+        models::{
+            user::{User, NewUser},
+            refresh_token::{NewRefreshToken, RefreshToken},
+            revoked_token::NewRevokedToken,
+        },
+        with_connection,
     },
     errors::{ApiError, ApiResult},
+    mail::{mailer::Mailer, mail_notifications},
+    organization::organization_service,
+    rbac::rbac_service,
     user::user_repository,
 };
 use chrono::{Duration, Utc};
+use diesel::{OptionalExtension, PgConnection};
 use rand::{Rng};
+use serde_json::Value;
+use std::sync::Arc;
+
+pub async fn register(
+    request: RegisterRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    verification: &EmailVerificationConfig,
+    mailer: &Arc<dyn Mailer>,
+) -> ApiResult<AuthResponse> {
+    let audit = AuditLog::new(&pool, &client);
+    let email = request.email.clone();
+    let result = create_account(request, client, pool, verification, mailer).await;
+
+    let user_id = result.as_ref().ok().map(|response| response.user.id);
+    audit
+        .record(
+            AuditEntry::from_result(audit_service::REGISTER, &result)
+                .actor(user_id)
+                .target_user(user_id)
+                .with("email", email),
+        )
+        .await;
+
+    result
+}
 
-pub async fn register(request: RegisterRequest, pool: DbPool) -> ApiResult<AuthResponse> {
-    let mut conn = pool.get().map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+async fn create_account(
+    request: RegisterRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    verification: &EmailVerificationConfig,
+    mailer: &Arc<dyn Mailer>,
+) -> ApiResult<AuthResponse> {
+    let email = request.email.clone();
+    let email_taken = with_connection(&pool, move |conn| {
+        Ok(user_repository::find_user_by_email(&email, conn).is_ok())
+    })
+    .await?;
 
-    if user_repository::find_user_by_email(&request.email, &mut conn).is_ok() {
+    if email_taken {
         return Err(ApiError::ResourceAlreadyExists("Email already registered".to_string()));
     }
 
-    let password_hash = hash_password(&request.password)
+    let password_hash = hash_password_async(request.password)
+        .await
         .map_err(|e| ApiError::InternalServerError(format!("Password hashing failed: {}", e)))?;
 
     let new_user = NewUser {
@@ -33,131 +80,552 @@ pub async fn register(request: RegisterRequest, pool: DbPool) -> ApiResult<AuthR
         password_hash,
         is_active: Some(true),
         is_verified: Some(false),
+        locale: request.locale,
     };
 
-    let user = user_repository::create_user(&new_user, &mut conn)?;
-
-    let access_token = generate_access_token(user.id);
-    let refresh_token = generate_refresh_token(user.id);
+    let verification = verification.clone();
+    let (user, verification_token, auth_response) = with_connection(&pool, move |conn| {
+        let user = user_repository::create_user(&new_user, conn)?;
+        let (user, verification_token) = issue_verification_token(user, &verification, conn)?;
+        let auth_response = start_session(user.clone(), &client, conn)?;
 
-    let new_refresh_token = NewRefreshToken {
-        user_id: user.id,
-        token: refresh_token.clone(),
-        expires_at: (Utc::now() + Duration::days(7)).naive_utc(),
-    };
+        Ok((user, verification_token, auth_response))
+    })
+    .await?;
 
-    auth_repository::create_refresh_token(&new_refresh_token, &mut conn)?;
+    mail_notifications::send_verification_email(mailer, &user, &verification_token);
 
-    Ok(AuthResponse {
-        access_token,
-        refresh_token,
-        user: user_to_info(user),
-    })
+    Ok(auth_response)
 }
 
-pub async fn login(request: LoginRequest, pool: DbPool) -> ApiResult<AuthResponse> {
-    let mut conn = pool.get().map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+pub async fn login(
+    request: LoginRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    verification: &EmailVerificationConfig,
+) -> ApiResult<LoginResponse> {
+    let audit = AuditLog::new(&pool, &client);
+    let email = request.email.clone();
+    let result = password_login(request, client, pool.clone(), verification).await;
+
+    let mut entry = login_entry(&result)
+        .with("method", "password")
+        .with("email", email.clone());
+    if !entry.is_success() {
+        let user_id = with_connection(&pool, move |conn| {
+            Ok(user_repository::find_user_by_email(&email, conn).ok().map(|user| user.id))
+        })
+        .await
+        .ok()
+        .flatten();
+        entry = entry.target_user(user_id);
+    }
+    audit.record(entry).await;
+
+    result
+}
 
-    let user = user_repository::find_user_by_email(&request.email, &mut conn)
-        .map_err(|_| ApiError::Unauthorized("Invalid credentials".to_string()))?;
+async fn password_login(
+    request: LoginRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    verification: &EmailVerificationConfig,
+) -> ApiResult<LoginResponse> {
+    let email = request.email.clone();
+    let user = with_connection(&pool, move |conn| {
+        user_repository::find_user_by_email(&email, conn)
+            .map_err(|_| ApiError::Unauthorized("Invalid credentials".to_string()))
+    })
+    .await?;
 
-    if !verify_password(&request.password, &user.password_hash)
+    if !verify_password_async(request.password.clone(), user.password_hash.clone())
+        .await
         .map_err(|e| ApiError::InternalServerError(format!("Password verification failed: {}", e)))?
     {
         return Err(ApiError::Unauthorized("Invalid credentials".to_string()));
     }
 
-    if !user.is_active.unwrap_or(false) {
-        return Err(ApiError::Forbidden("Account is deactivated".to_string()));
-    }
+    check_login_allowed(&user, verification)?;
 
-    let access_token = generate_access_token(user.id);
-    let refresh_token = generate_refresh_token(user.id);
-
-    let new_refresh_token = NewRefreshToken {
-        user_id: user.id,
-        token: refresh_token.clone(),
-        expires_at: (Utc::now() + Duration::days(7)).naive_utc(),
+    let rehashed_password = if password_needs_rehash(&user.password_hash) {
+        hash_password_async(request.password)
+            .await
+            .inspect_err(|e| tracing::warn!(user_id = %user.id, "Failed to rehash password: {}", e))
+            .ok()
+    } else {
+        None
     };
 
-    auth_repository::create_refresh_token(&new_refresh_token, &mut conn)?;
+    with_connection(&pool, move |conn| {
+        if let Some(new_hash) = rehashed_password {
+            if let Err(e) = user_repository::replace_password_hash(user.id, &user.password_hash, &new_hash, conn) {
+                tracing::warn!(user_id = %user.id, "Failed to store rehashed password: {}", e);
+            }
+        }
 
-    Ok(AuthResponse {
-        access_token,
-        refresh_token,
-        user: user_to_info(user),
+        complete_login(user, &client, conn)
     })
+    .await
 }
 
-pub async fn refresh_token(request: RefreshTokenRequest, pool: DbPool) -> ApiResult<AuthResponse> {
-    let mut conn = pool.get().map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+pub async fn request_magic_link(
+    request: MagicLinkRequest,
+    pool: DbPool,
+    mailer: &Arc<dyn Mailer>,
+) -> ApiResult<()> {
+    let issued = with_connection(&pool, move |conn| {
+        let Ok(user) = user_repository::find_user_by_email(&request.email, conn) else {
+            return Ok(None);
+        };
+
+        let login_token = generate_random_token();
+        let expires = (Utc::now() + magic_link_ttl()).naive_utc();
+
+        user_repository::set_magic_link_token(user.id, &hash_token(&login_token), expires, conn)?;
+
+        tracing::info!("Magic link generated for user: {}", user.email);
+
+        Ok(Some((user, login_token)))
+    })
+    .await?;
+
+    if let Some((user, login_token)) = issued {
+        mail_notifications::send_magic_link_email(mailer, &user, &login_token);
+    }
+
+    Ok(())
+}
+
+pub async fn consume_magic_link(
+    request: ConsumeMagicLinkRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    verification: &EmailVerificationConfig,
+) -> ApiResult<LoginResponse> {
+    let audit = AuditLog::new(&pool, &client);
+    let token_hash = hash_token(&request.token);
+    let verification = verification.clone();
+
+    let result = with_connection(&pool, move |conn| {
+        let user = user_repository::consume_magic_link_token(&token_hash, conn)?
+            .ok_or_else(|| ApiError::Unauthorized("Invalid or expired login link".to_string()))?;
+
+        check_login_allowed(&user, &verification)?;
+        complete_login(user, &client, conn)
+    })
+    .await;
+
+    audit.record(login_entry(&result).with("method", "magic_link")).await;
+
+    result
+}
+
+// A login waiting on its second factor names the account but has no actor yet.
+pub fn login_entry(result: &ApiResult<LoginResponse>) -> AuditEntry {
+    let entry = AuditEntry::from_result(audit_service::LOGIN, result);
+
+    match result {
+        Ok(LoginResponse::Authenticated(response)) => entry
+            .actor(Some(response.user.id))
+            .target_user(Some(response.user.id)),
+        Ok(LoginResponse::MfaRequired(challenge)) => entry
+            .target_user(validate_mfa_challenge_token(&challenge.mfa_token).ok().map(|claims| claims.sub))
+            .with("mfa_required", true),
+        Err(_) => entry,
+    }
+}
+
+pub enum RefreshOutcome<T> {
+    Rotated(T),
+    Reused(Option<Box<User>>),
+}
+
+pub async fn refresh_token(
+    request: RefreshTokenRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    mailer: &Arc<dyn Mailer>,
+) -> ApiResult<AuthResponse> {
+    let audit = AuditLog::new(&pool, &client);
+    let outcome = with_connection(&pool, move |conn| {
+        rotate_refresh_token(&request.refresh_token, None, &client, conn, |user, stored_token, conn| {
+            let new_access_token =
+                generate_access_token_with(user.id, session_access_token_options(&user, stored_token.family_id, conn)?);
+            let new_refresh_token = issue_refresh_token(
+                user.id,
+                stored_token.family_id,
+                Some(&stored_token),
+                None,
+                None,
+                &client,
+                conn,
+            )?;
+
+            Ok(AuthResponse {
+                access_token: Some(new_access_token),
+                refresh_token: Some(new_refresh_token),
+                user: user_to_info(user),
+            })
+        })
+    })
+    .await;
+
+    let entry = match &outcome {
+        Ok(RefreshOutcome::Rotated(response)) => AuditEntry::success(audit_service::TOKEN_REFRESH)
+            .actor(Some(response.user.id))
+            .target_user(Some(response.user.id)),
+        Ok(RefreshOutcome::Reused(user)) => AuditEntry::failure(audit_service::TOKEN_REFRESH)
+            .target_user(user.as_ref().map(|user| user.id))
+            .with("reason", "Refresh token reuse detected"),
+        Err(e) => AuditEntry::failure(audit_service::TOKEN_REFRESH).with("reason", e.to_string()),
+    };
+    audit.record(entry).await;
+
+    finish_refresh(outcome?, mailer)
+}
 
-    let claims = validate_refresh_token(&request.refresh_token)
+// Shared by `/auth/refresh` and the OAuth token endpoint. A refresh token only
+// rotates through the client it was issued to (`None` for first-party
+// sessions); `issue` mints the replacement tokens for the rotated one.
+pub fn rotate_refresh_token<T>(
+    refresh_token: &str,
+    client_id: Option<uuid::Uuid>,
+    client: &ClientInfo,
+    conn: &mut PgConnection,
+    issue: impl FnOnce(User, RefreshToken, &mut PgConnection) -> ApiResult<T>,
+) -> ApiResult<RefreshOutcome<T>> {
+    let claims = validate_refresh_token(refresh_token)
         .map_err(|_| ApiError::Unauthorized("Invalid refresh token".to_string()))?;
 
-    let stored_token = auth_repository::find_refresh_token(&request.refresh_token, &mut conn)
+    let refresh_token_hash = hash_token(refresh_token);
+
+    let stored_token = auth_repository::find_refresh_token(&refresh_token_hash, conn)
         .map_err(|_| ApiError::Unauthorized("Refresh token not found".to_string()))?;
 
     if stored_token.user_id != claims.sub {
         return Err(ApiError::Unauthorized("Token user mismatch".to_string()));
     }
 
-    let user = user_repository::find_user_by_id(claims.sub, &mut conn)?;
+    if stored_token.client_id != client_id {
+        return Err(ApiError::Unauthorized("Refresh token not found".to_string()));
+    }
+
+    if stored_token.revoked_at.is_some() {
+        return Err(ApiError::Unauthorized("Refresh token has been revoked".to_string()));
+    }
+
+    if !auth_repository::mark_refresh_token_rotated(stored_token.id, conn)? {
+        let stored_token = auth_repository::find_refresh_token(&refresh_token_hash, conn)?;
+        // The grace period only covers one concurrent refresh from the device
+        // the token was issued to.
+        let within_grace_period = stored_token.revoked_at.is_none()
+            && stored_token
+                .rotated_at
+                .is_some_and(|rotated_at| Utc::now().naive_utc() - rotated_at <= refresh_reuse_grace_period())
+            && stored_token.user_agent == client.user_agent
+            && stored_token.ip_address == client.ip_address
+            && auth_repository::count_child_refresh_tokens(stored_token.id, conn)? < 2;
+
+        if !within_grace_period {
+            auth_repository::revoke_refresh_token_family(stored_token.family_id, conn)?;
+
+            tracing::warn!(
+                target: "security",
+                user_id = %stored_token.user_id,
+                family_id = %stored_token.family_id,
+                "Refresh token reuse detected, token family revoked"
+            );
+
+            let user = user_repository::find_user_by_id(stored_token.user_id, conn).ok();
+            return Ok(RefreshOutcome::Reused(user.map(Box::new)));
+        }
+    }
+
+    let user = user_repository::find_user_by_id(claims.sub, conn)?;
+
+    issue(user, stored_token, conn).map(RefreshOutcome::Rotated)
+}
+
+pub fn finish_refresh<T>(outcome: RefreshOutcome<T>, mailer: &Arc<dyn Mailer>) -> ApiResult<T> {
+    match outcome {
+        RefreshOutcome::Rotated(response) => Ok(response),
+        RefreshOutcome::Reused(user) => {
+            if let Some(user) = user {
+                mail_notifications::send_refresh_token_reuse_email(mailer, &user);
+            }
 
-    auth_repository::delete_refresh_token(&request.refresh_token, &mut conn)?;
+            Err(ApiError::Unauthorized("Refresh token reuse detected".to_string()))
+        }
+    }
+}
 
-    let new_access_token = generate_access_token(user.id);
-    let new_refresh_token = generate_refresh_token(user.id);
+// A rotated token carries its session's start over to the new token, along
+// with the latest client details.
+pub fn issue_refresh_token(
+    user_id: uuid::Uuid,
+    family_id: uuid::Uuid,
+    parent: Option<&RefreshToken>,
+    client_id: Option<uuid::Uuid>,
+    scope: Option<String>,
+    client: &ClientInfo,
+    conn: &mut PgConnection,
+) -> ApiResult<String> {
+    let refresh_token = generate_refresh_token(user_id);
+
+    let user_agent = client
+        .user_agent
+        .clone()
+        .or_else(|| parent.and_then(|parent| parent.user_agent.clone()));
 
     let refresh_token_record = NewRefreshToken {
-        user_id: user.id,
-        token: new_refresh_token.clone(),
-        expires_at: (Utc::now() + Duration::days(7)).naive_utc(),
+        user_id,
+        token_hash: hash_token(&refresh_token),
+        expires_at: (Utc::now() + Duration::days(REFRESH_TOKEN_TTL_DAYS)).naive_utc(),
+        family_id,
+        parent_id: parent.map(|parent| parent.id),
+        client_id,
+        scope,
+        device_label: user_agent.as_deref().and_then(describe_device),
+        user_agent,
+        ip_address: client
+            .ip_address
+            .clone()
+            .or_else(|| parent.and_then(|parent| parent.ip_address.clone())),
+        session_started_at: parent.map(|parent| parent.session_started_at),
     };
 
-    auth_repository::create_refresh_token(&refresh_token_record, &mut conn)?;
+    auth_repository::create_refresh_token(&refresh_token_record, conn)?;
 
-    Ok(AuthResponse {
-        access_token: new_access_token,
-        refresh_token: new_refresh_token,
-        user: user_to_info(user),
+    Ok(refresh_token)
+}
+
+// Ends only the session the access token belongs to; other devices stay
+// logged in.
+pub async fn logout(
+    user_id: uuid::Uuid,
+    session_id: Option<uuid::Uuid>,
+    access_token: AccessTokenInfo,
+    client: ClientInfo,
+    pool: DbPool,
+) -> ApiResult<()> {
+    let audit = AuditLog::new(&pool, &client);
+    let result = with_connection(&pool, move |conn| {
+        if let Some(session_id) = session_id {
+            auth_repository::revoke_user_session(user_id, session_id, conn)?;
+        }
+        revoke_access_token(user_id, access_token, conn)
     })
+    .await;
+
+    audit
+        .record(
+            AuditEntry::from_result(audit_service::LOGOUT, &result)
+                .actor(Some(user_id))
+                .target_user(Some(user_id)),
+        )
+        .await;
+
+    result
 }
 
-pub async fn logout(user_id: uuid::Uuid, pool: DbPool) -> ApiResult<()> {
-    let mut conn = pool.get().map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+pub async fn logout_everywhere(
+    user_id: uuid::Uuid,
+    access_token: AccessTokenInfo,
+    client: ClientInfo,
+    pool: DbPool,
+) -> ApiResult<()> {
+    let audit = AuditLog::new(&pool, &client);
+    let result = with_connection(&pool, move |conn| {
+        revoke_access_token(user_id, access_token, conn)?;
+        end_all_sessions(user_id, conn)
+    })
+    .await;
+
+    audit
+        .record(
+            AuditEntry::from_result(audit_service::LOGOUT_EVERYWHERE, &result)
+                .actor(Some(user_id))
+                .target_user(Some(user_id)),
+        )
+        .await;
+
+    result
+}
 
-    auth_repository::delete_user_refresh_tokens(user_id, &mut conn)?;
+// Revokes every refresh token and rejects every access token issued before now.
+pub fn end_all_sessions(user_id: uuid::Uuid, conn: &mut PgConnection) -> ApiResult<()> {
+    auth_repository::revoke_user_refresh_tokens(user_id, conn)?;
+    user_repository::set_tokens_valid_after(user_id, Utc::now().naive_utc(), conn)?;
 
     Ok(())
 }
 
-pub async fn forgot_password(request: ForgotPasswordRequest, pool: DbPool) -> ApiResult<()> {
-    let mut conn = pool.get().map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+pub async fn list_sessions(
+    user_id: uuid::Uuid,
+    current_session: Option<uuid::Uuid>,
+    pool: DbPool,
+) -> ApiResult<Vec<SessionResponse>> {
+    with_connection(&pool, move |conn| {
+        let mut sessions = auth_repository::find_active_sessions(user_id, conn)?;
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.session_started_at));
+
+        Ok(sessions
+            .into_iter()
+            .map(|token| session_to_response(token, current_session))
+            .collect())
+    })
+    .await
+}
+
+pub async fn revoke_session(user_id: uuid::Uuid, session_id: uuid::Uuid, pool: DbPool) -> ApiResult<()> {
+    with_connection(&pool, move |conn| {
+        if auth_repository::revoke_user_session(user_id, session_id, conn)? == 0 {
+            return Err(ApiError::NotFound("Session not found".to_string()));
+        }
+
+        tracing::info!("Session {} revoked by user: {}", session_id, user_id);
+
+        Ok(())
+    })
+    .await
+}
+
+pub async fn revoke_other_sessions(
+    user_id: uuid::Uuid,
+    current_session: Option<uuid::Uuid>,
+    pool: DbPool,
+) -> ApiResult<usize> {
+    let current_session = current_session
+        .ok_or_else(|| ApiError::BadRequest("The current session can't be identified".to_string()))?;
+
+    with_connection(&pool, move |conn| {
+        let revoked = auth_repository::revoke_other_sessions(user_id, current_session, conn)?;
+
+        tracing::info!("{} other sessions revoked by user: {}", revoked, user_id);
+
+        Ok(revoked)
+    })
+    .await
+}
+
+// An access token stops working once its jti or session is revoked, or the
+// user logged out everywhere after it was issued. Lookup failures are returned
+// so callers reject the token rather than trust it.
+pub fn is_access_token_revoked(
+    jti: &str,
+    user_id: uuid::Uuid,
+    issued_at: i64,
+    session_id: Option<uuid::Uuid>,
+    conn: &mut PgConnection,
+) -> ApiResult<bool> {
+    if auth_repository::is_token_revoked(jti, conn)? {
+        return Ok(true);
+    }
+
+    if let Some(session_id) = session_id {
+        if auth_repository::is_session_revoked(user_id, session_id, conn)? {
+            return Ok(true);
+        }
+    }
+
+    // `iat` only has whole seconds, so a token from the same second as the
+    // cutoff is rejected too. Client tokens have no user row and no cutoff.
+    let valid_after = user_repository::find_tokens_valid_after(user_id, conn).optional()?.flatten();
+    Ok(valid_after.is_some_and(|valid_after| issued_at <= valid_after.and_utc().timestamp()))
+}
 
-    if let Ok(mut user) = user_repository::find_user_by_email(&request.email, &mut conn) {
-        let reset_token: String = (0..32)
-            .map(|_| rand::rng().random::<u8>() % 26 + b'a')
-            .map(|b| b as char)
-            .collect();
+fn revoke_access_token(
+    user_id: uuid::Uuid,
+    access_token: AccessTokenInfo,
+    conn: &mut PgConnection,
+) -> ApiResult<()> {
+    let revoked_token = NewRevokedToken {
+        token_jti: access_token.jti,
+        user_id: Some(user_id),
+        expiry: access_token.expires_at,
+    };
 
-        user.password_reset_token = Some(reset_token.clone());
-        user.password_reset_expires = Some((Utc::now() + Duration::hours(1)).naive_utc());
+    auth_repository::revoke_token(&revoked_token, conn)?;
 
-        user_repository::update_user(user.id, &user, &mut conn)?;
+    Ok(())
+}
 
-        tracing::info!("Password reset token generated for user: {}", user.email);
-        tracing::info!("Reset token: {} (expires in 1 hour)", reset_token);
+pub async fn forgot_password(
+    request: ForgotPasswordRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    mailer: &Arc<dyn Mailer>,
+) -> ApiResult<()> {
+    let audit = AuditLog::new(&pool, &client);
+    let email = request.email.clone();
+    let issued = with_connection(&pool, move |conn| {
+        let Ok(user) = user_repository::find_user_by_email(&request.email, conn) else {
+            return Ok(None);
+        };
+
+        issue_password_reset(user, conn).map(Some)
+    })
+    .await;
+
+    let user_id = issued.as_ref().ok().and_then(|issued| issued.as_ref()).map(|(user, _)| user.id);
+    audit
+        .record(
+            AuditEntry::from_result(audit_service::PASSWORD_RESET_REQUEST, &issued)
+                .target_user(user_id)
+                .with("email", email),
+        )
+        .await;
+
+    if let Some((user, reset_token)) = issued? {
+        mail_notifications::send_password_reset_email(mailer, &user, &reset_token);
     }
 
     Ok(())
 }
 
-pub async fn reset_password(request: ResetPasswordRequest, pool: DbPool) -> ApiResult<()> {
-    let mut conn = pool.get().map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+pub fn issue_password_reset(mut user: User, conn: &mut PgConnection) -> ApiResult<(User, String)> {
+    let reset_token = generate_random_token();
 
-    let mut user = user_repository::find_user_by_reset_token(&request.token, &mut conn)
-        .map_err(|_| ApiError::BadRequest("Invalid or expired reset token".to_string()))?;
+    user.password_reset_token_hash = Some(hash_token(&reset_token));
+    user.password_reset_expires = Some((Utc::now() + Duration::hours(1)).naive_utc());
+
+    let user = user_repository::update_user(user.id, &user, conn)?;
+
+    tracing::info!("Password reset token generated for user: {}", user.email);
+
+    Ok((user, reset_token))
+}
+
+pub async fn reset_password(
+    request: ResetPasswordRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    mailer: &Arc<dyn Mailer>,
+) -> ApiResult<()> {
+    let audit = AuditLog::new(&pool, &client);
+    let result = replace_password(request, pool).await;
+
+    let user_id = result.as_ref().ok().map(|user| user.id);
+    audit
+        .record(
+            AuditEntry::from_result(audit_service::PASSWORD_RESET_COMPLETE, &result)
+                .actor(user_id)
+                .target_user(user_id),
+        )
+        .await;
+
+    mail_notifications::send_password_changed_email(mailer, &result?);
+
+    Ok(())
+}
+
+async fn replace_password(request: ResetPasswordRequest, pool: DbPool) -> ApiResult<User> {
+    let reset_token_hash = hash_token(&request.token);
+    let user = with_connection(&pool, move |conn| {
+        user_repository::find_user_by_reset_token(&reset_token_hash, conn)
+            .map_err(|_| ApiError::BadRequest("Invalid or expired reset token".to_string()))
+    })
+    .await?;
 
     if let Some(expires) = user.password_reset_expires {
         if expires < Utc::now().naive_utc() {
@@ -167,20 +635,271 @@ pub async fn reset_password(request: ResetPasswordRequest, pool: DbPool) -> ApiR
         return Err(ApiError::BadRequest("Invalid reset token".to_string()));
     }
 
-    let new_password_hash = hash_password(&request.new_password)
+    let new_password_hash = hash_password_async(request.new_password)
+        .await
         .map_err(|e| ApiError::InternalServerError(format!("Password hashing failed: {}", e)))?;
 
-    user.password_hash = new_password_hash;
-    user.password_reset_token = None;
-    user.password_reset_expires = None;
+    with_connection(&pool, move |conn| {
+        let user = user_repository::update_password(user.id, &new_password_hash, conn)?;
+        end_all_sessions(user.id, conn)?;
 
-    user_repository::update_user(user.id, &user, &mut conn)?;
+        Ok(user)
+    })
+    .await
+}
 
-    auth_repository::delete_user_refresh_tokens(user.id, &mut conn)?;
+pub async fn verify_email(request: VerifyEmailRequest, pool: DbPool) -> ApiResult<()> {
+    let verification_token_hash = hash_token(&request.token);
+    with_connection(&pool, move |conn| {
+        let user = user_repository::find_user_by_verification_token(&verification_token_hash, conn)
+            .map_err(|_| ApiError::BadRequest("Invalid or expired verification token".to_string()))?;
+
+        match user.verification_token_expires {
+            Some(expires) if expires < Utc::now().naive_utc() => {
+                return Err(ApiError::BadRequest("Verification token has expired".to_string()));
+            }
+            Some(_) => {}
+            None => return Err(ApiError::BadRequest("Invalid verification token".to_string())),
+        }
+
+        user_repository::mark_user_verified(user.id, conn)?;
+
+        Ok(())
+    })
+    .await
+}
+
+pub async fn resend_verification(
+    request: ResendVerificationRequest,
+    pool: DbPool,
+    verification: &EmailVerificationConfig,
+    mailer: &Arc<dyn Mailer>,
+) -> ApiResult<()> {
+    let verification = verification.clone();
+    let issued = with_connection(&pool, move |conn| {
+        let user = match user_repository::find_user_by_email(&request.email, conn) {
+            Ok(user) => user,
+            Err(_) => return Ok(None),
+        };
+
+        if user.is_verified.unwrap_or(false) {
+            return Ok(None);
+        }
+
+        if let Some(sent_at) = user.verification_sent_at {
+            if Utc::now().naive_utc() < sent_at + verification.resend_cooldown {
+                tracing::info!("Verification resend throttled for user: {}", user.email);
+                return Ok(None);
+            }
+        }
+
+        issue_verification_token(user, &verification, conn).map(Some)
+    })
+    .await?;
+
+    if let Some((user, verification_token)) = issued {
+        mail_notifications::send_verification_email(mailer, &user, &verification_token);
+    }
+
+    Ok(())
+}
+
+pub fn check_login_allowed(user: &User, verification: &EmailVerificationConfig) -> ApiResult<()> {
+    if !user.is_active.unwrap_or(false) {
+        return Err(ApiError::Forbidden("Account is deactivated".to_string()));
+    }
+
+    if verification.block_login && !user.is_verified.unwrap_or(false) {
+        return Err(ApiError::Forbidden("Email address is not verified".to_string()));
+    }
 
     Ok(())
 }
 
+// Shared by every first-factor login; accounts with TOTP enabled get an MFA
+// challenge instead of tokens.
+pub fn complete_login(user: User, client: &ClientInfo, conn: &mut PgConnection) -> ApiResult<LoginResponse> {
+    if user.totp_enabled_at.is_some() {
+        let ttl = mfa_challenge_ttl();
+        return Ok(LoginResponse::MfaRequired(MfaChallengeResponse {
+            mfa_required: true,
+            mfa_token: generate_mfa_challenge_token(user.id, ttl),
+            expires_in: ttl.num_seconds(),
+        }));
+    }
+
+    start_session(user, client, conn).map(LoginResponse::Authenticated)
+}
+
+// Each session is a refresh-token family; its id is the family id.
+pub fn start_session(user: User, client: &ClientInfo, conn: &mut PgConnection) -> ApiResult<AuthResponse> {
+    let session_id = uuid::Uuid::new_v4();
+    let access_token = generate_access_token_with(user.id, session_access_token_options(&user, session_id, conn)?);
+    let refresh_token = issue_refresh_token(user.id, session_id, None, None, None, client, conn)?;
+
+    Ok(AuthResponse {
+        access_token: Some(access_token),
+        refresh_token: Some(refresh_token),
+        user: user_to_info(user),
+    })
+}
+
+// A new access token for an existing session, for when its claims changed
+// (e.g. the active organization). The refresh token is left as it is.
+pub fn reissue_access_token(
+    user: User,
+    session_id: Option<uuid::Uuid>,
+    conn: &mut PgConnection,
+) -> ApiResult<AuthResponse> {
+    let options = match session_id {
+        Some(session_id) => session_access_token_options(&user, session_id, conn)?,
+        None => access_token_options(&user, conn)?,
+    };
+
+    Ok(AuthResponse {
+        access_token: Some(generate_access_token_with(user.id, options)),
+        refresh_token: None,
+        user: user_to_info(user),
+    })
+}
+
+pub fn issue_verification_token(
+    mut user: User,
+    verification: &EmailVerificationConfig,
+    conn: &mut PgConnection,
+) -> ApiResult<(User, String)> {
+    let verification_token = generate_random_token();
+    let now = Utc::now();
+
+    user.verification_token_hash = Some(hash_token(&verification_token));
+    user.verification_token_expires = Some((now + verification.token_ttl).naive_utc());
+    user.verification_sent_at = Some(now.naive_utc());
+
+    let user = user_repository::update_user(user.id, &user, conn)?;
+
+    tracing::info!("Verification token generated for user: {}", user.email);
+
+    Ok((user, verification_token))
+}
+
+fn refresh_reuse_grace_period() -> Duration {
+    let seconds = std::env::var("REFRESH_TOKEN_REUSE_GRACE_SECONDS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(30);
+
+    Duration::seconds(seconds)
+}
+
+fn magic_link_ttl() -> Duration {
+    let minutes = std::env::var("MAGIC_LINK_TTL_MINUTES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(15);
+
+    Duration::minutes(minutes)
+}
+
+fn mfa_challenge_ttl() -> Duration {
+    let seconds = std::env::var("MFA_CHALLENGE_TTL_SECONDS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(300);
+
+    Duration::seconds(seconds)
+}
+
+pub fn generate_random_token() -> String {
+    (0..32)
+        .map(|_| rand::rng().random::<u8>() % 26 + b'a')
+        .map(|b| b as char)
+        .collect()
+}
+
+// Application-specific claims (roles, tenant id, ...) are added here and
+// exposed to handlers through `AuthUser::claim`.
+pub fn access_token_options(user: &User, conn: &mut PgConnection) -> ApiResult<AccessTokenOptions> {
+    let mut options = AccessTokenOptions::default();
+
+    let roles = rbac_service::user_role_names(user.id, conn)?;
+    if !roles.is_empty() {
+        options.custom_claims.insert("roles".to_string(), Value::from(roles));
+    }
+
+    if let Some(organization_id) = organization_service::active_organization_id(user, conn)? {
+        options
+            .custom_claims
+            .insert("org_id".to_string(), Value::String(organization_id.to_string()));
+    }
+
+    Ok(options)
+}
+
+// Session tokens carry a `sid` claim so revoking the session cuts them off too.
+fn session_access_token_options(
+    user: &User,
+    session_id: uuid::Uuid,
+    conn: &mut PgConnection,
+) -> ApiResult<AccessTokenOptions> {
+    let mut options = access_token_options(user, conn)?;
+    options
+        .custom_claims
+        .insert("sid".to_string(), Value::String(session_id.to_string()));
+    Ok(options)
+}
+
+// A short label such as "Firefox on Windows" for the sessions list.
+fn describe_device(user_agent: &str) -> Option<String> {
+    let browser = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+    ]
+    .into_iter()
+    .find(|(marker, _)| user_agent.contains(marker))
+    .map(|(_, name)| name);
+
+    let os = [
+        ("Windows", "Windows"),
+        ("Android", "Android"),
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Mac OS X", "macOS"),
+        ("CrOS", "ChromeOS"),
+        ("Linux", "Linux"),
+    ]
+    .into_iter()
+    .find(|(marker, _)| user_agent.contains(marker))
+    .map(|(_, name)| name);
+
+    match (browser, os) {
+        (Some(browser), Some(os)) => Some(format!("{} on {}", browser, os)),
+        (Some(name), None) | (None, Some(name)) => Some(name.to_string()),
+        (None, None) => user_agent
+            .split('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .map(|name| name.chars().take(255).collect()),
+    }
+}
+
+// The newest token of a session was issued when the session was last
+// refreshed.
+fn session_to_response(token: RefreshToken, current_session: Option<uuid::Uuid>) -> SessionResponse {
+    SessionResponse {
+        id: token.family_id,
+        device_label: token.device_label,
+        user_agent: token.user_agent,
+        ip_address: token.ip_address,
+        created_at: token.session_started_at,
+        last_used_at: token.created_at,
+        expires_at: token.expires_at,
+        current: current_session == Some(token.family_id),
+    }
+}
+
 fn user_to_info(user: User) -> UserInfo {
     UserInfo {
         id: user.id,
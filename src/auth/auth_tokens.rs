@@ -1,15 +1,59 @@
+use crate::auth::auth_keys::Keyring;
 use chrono::{Duration, Utc};
+use jsonwebtoken::jwk::JwkSet;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::env;
 use uuid::Uuid;
 
+static ACCESS_TOKEN_KEYRING: Lazy<Keyring> =
+    Lazy::new(|| Keyring::from_env().unwrap_or_else(|e| panic!("Failed to load JWT signing keys: {}", e)));
+
+pub const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 7;
+
+const REGISTERED_CLAIMS: [&str; 8] = ["sub", "jti", "iss", "aud", "exp", "iat", "nbf", "scope"];
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AccessTokenClaims {
     pub sub: Uuid,
     pub jti: String,
+    pub iss: String,
+    pub aud: Vec<String>,
     pub exp: i64,
     pub iat: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(flatten)]
+    pub custom: Map<String, Value>,
+}
+
+impl AccessTokenClaims {
+    // First-party session tokens name the refresh-token family they belong to.
+    pub fn session_id(&self) -> Option<Uuid> {
+        self.custom
+            .get("sid")
+            .and_then(Value::as_str)
+            .and_then(|sid| Uuid::parse_str(sid).ok())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaChallengeClaims {
+    pub sub: Uuid,
+    pub jti: String,
+    pub iss: String,
+    pub aud: String,
+    pub exp: i64,
+    pub iat: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AccessTokenOptions {
+    pub scope: Option<String>,
+    pub custom_claims: Map<String, Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,24 +65,38 @@ pub struct RefreshTokenClaims {
 }
 
 pub fn generate_access_token(user_id: Uuid) -> String {
+    generate_access_token_with(user_id, AccessTokenOptions::default())
+}
+
+pub fn generate_access_token_with(user_id: Uuid, options: AccessTokenOptions) -> String {
     let now = Utc::now();
-    let exp = now + Duration::minutes(15);
-    
+    let exp = now + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
+
+    let custom = options
+        .custom_claims
+        .into_iter()
+        .filter(|(name, _)| !REGISTERED_CLAIMS.contains(&name.as_str()))
+        .collect();
+
     let claims = AccessTokenClaims {
         sub: user_id,
         jti: Uuid::new_v4().to_string(),
+        iss: token_issuer(),
+        aud: token_audience(),
         exp: exp.timestamp(),
         iat: now.timestamp(),
+        scope: options.scope,
+        custom,
     };
 
-    let secret = env::var("JWT_ACCESS_SECRET").expect("JWT_ACCESS_SECRET must be set");
-    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_ref()))
+    ACCESS_TOKEN_KEYRING
+        .encode(&claims, now)
         .expect("Failed to generate access token")
 }
 
 pub fn generate_refresh_token(user_id: Uuid) -> String {
     let now = Utc::now();
-    let exp = now + Duration::days(7);
+    let exp = now + Duration::days(REFRESH_TOKEN_TTL_DAYS);
     
     let claims = RefreshTokenClaims {
         sub: user_id,
@@ -53,21 +111,20 @@ pub fn generate_refresh_token(user_id: Uuid) -> String {
 }
 
 pub fn validate_access_token(token: &str) -> Result<AccessTokenClaims, String> {
-    let secret = env::var("JWT_ACCESS_SECRET").expect("JWT_ACCESS_SECRET must be set");
-    
-    match decode::<AccessTokenClaims>(
-        token,
-        &DecodingKey::from_secret(secret.as_ref()),
-        &Validation::default(),
-    ) {
-        Ok(token_data) => {
-            if token_data.claims.exp < Utc::now().timestamp() {
+    let mut validation = Validation::default();
+    validation.set_issuer(&[token_issuer()]);
+    validation.set_audience(&token_audience());
+    validation.set_required_spec_claims(&["exp", "sub", "iss", "aud"]);
+
+    match ACCESS_TOKEN_KEYRING.decode::<AccessTokenClaims>(token, Utc::now(), &validation) {
+        Some(claims) => {
+            if claims.exp < Utc::now().timestamp() {
                 Err("Access token expired".to_string())
             } else {
-                Ok(token_data.claims)
+                Ok(claims)
             }
         }
-        Err(_) => Err("Invalid access token".to_string()),
+        None => Err("Invalid access token".to_string()),
     }
 }
 
@@ -89,3 +146,59 @@ pub fn validate_refresh_token(token: &str) -> Result<RefreshTokenClaims, String>
         Err(_) => Err("Invalid refresh token".to_string()),
     }
 }
+
+pub fn generate_mfa_challenge_token(user_id: Uuid, ttl: Duration) -> String {
+    let now = Utc::now();
+
+    let claims = MfaChallengeClaims {
+        sub: user_id,
+        jti: Uuid::new_v4().to_string(),
+        iss: token_issuer(),
+        aud: mfa_challenge_audience(),
+        exp: (now + ttl).timestamp(),
+        iat: now.timestamp(),
+    };
+
+    ACCESS_TOKEN_KEYRING
+        .encode(&claims, now)
+        .expect("Failed to generate MFA challenge token")
+}
+
+pub fn validate_mfa_challenge_token(token: &str) -> Result<MfaChallengeClaims, String> {
+    let mut validation = Validation::default();
+    validation.set_issuer(&[token_issuer()]);
+    validation.set_audience(&[mfa_challenge_audience()]);
+    validation.set_required_spec_claims(&["exp", "sub", "iss", "aud"]);
+
+    ACCESS_TOKEN_KEYRING
+        .decode::<MfaChallengeClaims>(token, Utc::now(), &validation)
+        .ok_or_else(|| "Invalid MFA challenge token".to_string())
+}
+
+pub fn access_token_jwks() -> JwkSet {
+    ACCESS_TOKEN_KEYRING.jwks(Utc::now())
+}
+
+fn token_issuer() -> String {
+    env::var("JWT_ISSUER").unwrap_or_else(|_| "axum-api-template".to_string())
+}
+
+// Challenge tokens are signed with the access keys, so they get their own
+// audience to keep them from being accepted as access tokens.
+fn mfa_challenge_audience() -> String {
+    format!("{}#mfa", token_issuer())
+}
+
+fn token_audience() -> Vec<String> {
+    env::var("JWT_AUDIENCE")
+        .map(|value| {
+            value
+                .split(',')
+                .map(|audience| audience.trim().to_string())
+                .filter(|audience| !audience.is_empty())
+                .collect()
+        })
+        .ok()
+        .filter(|audience: &Vec<String>| !audience.is_empty())
+        .unwrap_or_else(|| vec![token_issuer()])
+}
@@ -5,3 +5,5 @@ pub mod auth_dto;
 pub mod auth_tokens;
 pub mod auth_middleware;
 pub mod auth_hashing;
+pub mod auth_keys;
+pub mod auth_cookies;
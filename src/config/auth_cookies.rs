@@ -0,0 +1,49 @@
+use std::env;
+use tower_cookies::cookie::SameSite;
+
+#[derive(Clone, Debug)]
+pub struct AuthCookieConfig {
+    pub enabled: bool,
+    pub access_token: bool,
+    pub secure: bool,
+    pub same_site: SameSite,
+    pub domain: Option<String>,
+}
+
+impl AuthCookieConfig {
+    pub fn from_env() -> Self {
+        let enabled = env::var("AUTH_COOKIES_ENABLED")
+            .map(|value| value.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        let access_token = env::var("AUTH_COOKIES_ACCESS_TOKEN")
+            .map(|value| value.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        let secure = env::var("AUTH_COOKIES_SECURE")
+            .map(|value| !value.eq_ignore_ascii_case("false"))
+            .unwrap_or(true);
+
+        let same_site = match env::var("AUTH_COOKIES_SAME_SITE")
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "lax" => SameSite::Lax,
+            "none" => SameSite::None,
+            _ => SameSite::Strict,
+        };
+
+        let domain = env::var("AUTH_COOKIES_DOMAIN")
+            .ok()
+            .filter(|domain| !domain.is_empty());
+
+        Self {
+            enabled,
+            access_token,
+            secure,
+            same_site,
+            domain,
+        }
+    }
+}
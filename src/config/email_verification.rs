@@ -0,0 +1,51 @@
+use chrono::Duration;
+use std::env;
+
+#[derive(Clone, Debug)]
+pub struct EmailVerificationConfig {
+    pub token_ttl: Duration,
+    pub resend_cooldown: Duration,
+    pub block_login: bool,
+    pub required_paths: Vec<String>,
+}
+
+impl EmailVerificationConfig {
+    pub fn from_env() -> Self {
+        let token_ttl_hours = env::var("EMAIL_VERIFICATION_TOKEN_TTL_HOURS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(24);
+
+        let resend_cooldown_seconds = env::var("EMAIL_VERIFICATION_RESEND_COOLDOWN_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(60);
+
+        let block_login = env::var("EMAIL_VERIFICATION_BLOCK_LOGIN")
+            .map(|value| value.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        let required_paths = env::var("EMAIL_VERIFICATION_REQUIRED_PATHS")
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|path| path.trim().to_string())
+                    .filter(|path| !path.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            token_ttl: Duration::hours(token_ttl_hours),
+            resend_cooldown: Duration::seconds(resend_cooldown_seconds),
+            block_login,
+            required_paths,
+        }
+    }
+
+    pub fn is_required_for(&self, path: &str) -> bool {
+        self.required_paths
+            .iter()
+            .any(|prefix| path.starts_with(prefix.as_str()))
+    }
+}
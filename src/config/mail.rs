@@ -0,0 +1,35 @@
+use crate::mail::file_mailer::{FileMailer, FileTarget};
+use crate::mail::mailer::Mailer;
+use crate::mail::smtp_mailer::SmtpMailer;
+use dotenv::dotenv;
+use lettre::message::Mailbox;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub fn establish_mailer() -> Arc<dyn Mailer> {
+    dotenv().ok();
+    let from: Mailbox = env::var("MAIL_FROM")
+        .unwrap_or_else(|_| "Axum API Template <no-reply@localhost>".to_string())
+        .parse()
+        .expect("MAIL_FROM must be a valid mailbox");
+
+    let transport = env::var("MAIL_TRANSPORT").unwrap_or_else(|_| "file".to_string());
+
+    match transport.as_str() {
+        "smtp" => {
+            let smtp_url = env::var("SMTP_URL")
+                .expect("SMTP_URL must be set when MAIL_TRANSPORT=smtp");
+            Arc::new(SmtpMailer::new(&smtp_url, from).expect("Failed to create SMTP transport"))
+        }
+        "mbox" => {
+            let path = env::var("MAIL_FILE_PATH").unwrap_or_else(|_| "mail/outbox.mbox".to_string());
+            Arc::new(FileMailer::new(FileTarget::Mbox(PathBuf::from(path)), from))
+        }
+        "file" => {
+            let path = env::var("MAIL_FILE_PATH").unwrap_or_else(|_| "mail".to_string());
+            Arc::new(FileMailer::new(FileTarget::Directory(PathBuf::from(path)), from))
+        }
+        other => panic!("Unsupported MAIL_TRANSPORT: {}", other),
+    }
+}
@@ -1 +1,7 @@
 pub mod database;
+pub mod email_verification;
+pub mod mail;
+pub mod webauthn;
+pub mod oidc;
+pub mod oauth;
+pub mod auth_cookies;
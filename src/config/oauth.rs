@@ -0,0 +1,32 @@
+use chrono::Duration;
+use std::env;
+
+#[derive(Clone, Debug)]
+pub struct OAuthConfig {
+    pub scopes: Vec<String>,
+    pub code_ttl: Duration,
+}
+
+impl OAuthConfig {
+    pub fn from_env() -> Self {
+        let scopes = env::var("OAUTH_SCOPES")
+            .unwrap_or_else(|_| "profile:read profile:write".to_string())
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+
+        let code_ttl_seconds = env::var("OAUTH_CODE_TTL_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(60);
+
+        Self {
+            scopes,
+            code_ttl: Duration::seconds(code_ttl_seconds),
+        }
+    }
+
+    pub fn is_supported_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|supported| supported == scope)
+    }
+}
@@ -0,0 +1,76 @@
+use chrono::Duration;
+use std::env;
+
+#[derive(Clone, Debug)]
+pub struct OidcProviderConfig {
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct OidcConfig {
+    pub providers: Vec<OidcProviderConfig>,
+    pub state_ttl: Duration,
+}
+
+impl OidcConfig {
+    // Providers are listed in OIDC_PROVIDERS and configured through
+    // OIDC_<NAME>_ISSUER, OIDC_<NAME>_CLIENT_ID, OIDC_<NAME>_CLIENT_SECRET and
+    // the optional OIDC_<NAME>_REDIRECT_URI and OIDC_<NAME>_SCOPES.
+    pub fn from_env() -> Self {
+        let app_url = env::var("APP_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+
+        let providers = env::var("OIDC_PROVIDERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| {
+                let var = |key: &str| env::var(format!("OIDC_{}_{}", name.to_uppercase(), key)).ok();
+
+                let (Some(issuer), Some(client_id), Some(client_secret)) =
+                    (var("ISSUER"), var("CLIENT_ID"), var("CLIENT_SECRET"))
+                else {
+                    tracing::warn!("OIDC provider {} is missing its issuer, client id or secret", name);
+                    return None;
+                };
+
+                let redirect_uri = var("REDIRECT_URI")
+                    .unwrap_or_else(|| format!("{}/oidc/{}/callback", app_url.trim_end_matches('/'), name));
+
+                let scopes = var("SCOPES")
+                    .unwrap_or_else(|| "openid email profile".to_string())
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect();
+
+                Some(OidcProviderConfig {
+                    issuer: issuer.trim_end_matches('/').to_string(),
+                    name,
+                    client_id,
+                    client_secret,
+                    redirect_uri,
+                    scopes,
+                })
+            })
+            .collect();
+
+        let state_ttl_seconds = env::var("OIDC_STATE_TTL_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(600);
+
+        Self {
+            providers,
+            state_ttl: Duration::seconds(state_ttl_seconds),
+        }
+    }
+
+    pub fn provider(&self, name: &str) -> Option<&OidcProviderConfig> {
+        self.providers.iter().find(|provider| provider.name == name)
+    }
+}
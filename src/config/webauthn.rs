@@ -0,0 +1,42 @@
+use chrono::Duration;
+use std::env;
+
+#[derive(Clone, Debug)]
+pub struct WebauthnConfig {
+    pub rp_id: String,
+    pub rp_name: String,
+    pub origins: Vec<String>,
+    pub challenge_ttl: Duration,
+}
+
+impl WebauthnConfig {
+    pub fn from_env() -> Self {
+        let rp_id = env::var("WEBAUTHN_RP_ID").unwrap_or_else(|_| "localhost".to_string());
+
+        let rp_name = env::var("WEBAUTHN_RP_NAME").unwrap_or_else(|_| "Axum API Template".to_string());
+
+        let origins = env::var("WEBAUTHN_ORIGINS")
+            .or_else(|_| env::var("APP_URL"))
+            .unwrap_or_else(|_| "http://localhost:3000".to_string())
+            .split(',')
+            .map(|origin| origin.trim().trim_end_matches('/').to_string())
+            .filter(|origin| !origin.is_empty())
+            .collect();
+
+        let challenge_ttl_seconds = env::var("WEBAUTHN_CHALLENGE_TTL_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(300);
+
+        Self {
+            rp_id,
+            rp_name,
+            origins,
+            challenge_ttl: Duration::seconds(challenge_ttl_seconds),
+        }
+    }
+
+    pub fn is_allowed_origin(&self, origin: &str) -> bool {
+        self.origins.iter().any(|allowed| allowed == origin)
+    }
+}
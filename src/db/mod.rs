@@ -1 +1,76 @@
 pub mod models;
+
+use crate::config::database::DbPool;
+use crate::errors::{ApiError, ApiResult};
+use diesel::sql_types::Text;
+use diesel::{Connection, PgConnection, QueryResult, RunQueryDsl};
+use std::future::Future;
+use uuid::Uuid;
+
+tokio::task_local! {
+    static DB_CONTEXT: DbContext;
+}
+
+// Who a request acts for, as seen by Postgres row-level security. Inside
+// `DbContext::scope`, `with_connection` runs in a transaction that sets
+// `app.current_user_id` and `app.current_organization_id` for its duration
+// (`SET LOCAL`), so the pooled connection carries nothing over to the next
+// checkout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DbContext {
+    pub user_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+}
+
+impl DbContext {
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        DB_CONTEXT.scope(self, f).await
+    }
+
+    // Runs `f` with an empty context, which the policies don't restrict. Only
+    // for work that already checked its caller may act on other users' rows.
+    pub async fn unrestricted<F: Future>(f: F) -> F::Output {
+        DbContext::default().scope(f).await
+    }
+
+    pub fn current() -> Option<DbContext> {
+        DB_CONTEXT.try_with(|context| *context).ok()
+    }
+
+    // `set_config(..., true)` is `SET LOCAL` with bind parameters.
+    pub fn apply(&self, conn: &mut PgConnection) -> QueryResult<()> {
+        diesel::sql_query(
+            "SELECT set_config('app.current_user_id', $1, true), \
+             set_config('app.current_organization_id', $2, true)",
+        )
+        .bind::<Text, _>(self.user_id.map(|id| id.to_string()).unwrap_or_default())
+        .bind::<Text, _>(self.organization_id.map(|id| id.to_string()).unwrap_or_default())
+        .execute(conn)?;
+
+        Ok(())
+    }
+}
+
+pub async fn with_connection<T, F>(pool: &DbPool, f: F) -> ApiResult<T>
+where
+    F: FnOnce(&mut PgConnection) -> ApiResult<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let pool = pool.clone();
+    // Task-locals don't reach the blocking pool, so the context is read here.
+    let context = DbContext::current();
+
+    tokio::task::spawn_blocking(move || {
+        let mut conn = pool.get().map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        match context {
+            Some(context) => conn.transaction(|conn| {
+                context.apply(conn)?;
+                f(conn)
+            }),
+            None => f(&mut conn),
+        }
+    })
+    .await
+    .map_err(|e| ApiError::InternalServerError(format!("Database task failed: {}", e)))?
+}
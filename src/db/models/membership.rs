@@ -0,0 +1,23 @@
+use crate::schema::memberships;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = memberships)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Membership {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = memberships)]
+pub struct NewMembership {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role: String,
+}
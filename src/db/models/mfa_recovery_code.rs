@@ -0,0 +1,23 @@
+use crate::schema::mfa_recovery_codes;
+use chrono::{DateTime, Utc, NaiveDateTime};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = mfa_recovery_codes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MfaRecoveryCode {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub code_hash: String,
+    pub used_at: Option<NaiveDateTime>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = mfa_recovery_codes)]
+pub struct NewMfaRecoveryCode {
+    pub user_id: Uuid,
+    pub code_hash: String,
+}
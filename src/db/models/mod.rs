@@ -1,3 +1,19 @@
 pub mod user;
 pub mod refresh_token;
 pub mod revoked_token;
+pub mod mfa_recovery_code;
+pub mod webauthn_credential;
+pub mod webauthn_challenge;
+pub mod user_identity;
+pub mod oidc_login_state;
+pub mod oauth_client;
+pub mod oauth_authorization_code;
+pub mod oauth_consent;
+pub mod api_key;
+pub mod role;
+pub mod permission;
+pub mod user_role;
+pub mod organization;
+pub mod membership;
+pub mod invitation;
+pub mod audit_event;
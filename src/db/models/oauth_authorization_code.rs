@@ -0,0 +1,32 @@
+use crate::schema::oauth_authorization_codes;
+use chrono::{DateTime, Utc, NaiveDateTime};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = oauth_authorization_codes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OAuthAuthorizationCode {
+    pub id: Uuid,
+    pub code_hash: String,
+    pub client_id: Uuid,
+    pub user_id: Uuid,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub code_challenge: String,
+    pub expires_at: NaiveDateTime,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = oauth_authorization_codes)]
+pub struct NewOAuthAuthorizationCode {
+    pub code_hash: String,
+    pub client_id: Uuid,
+    pub user_id: Uuid,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub code_challenge: String,
+    pub expires_at: NaiveDateTime,
+}
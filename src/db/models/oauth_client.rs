@@ -0,0 +1,34 @@
+use crate::schema::oauth_clients;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = oauth_clients)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OAuthClient {
+    pub id: Uuid,
+    pub client_id: String,
+    pub client_secret_hash: Option<String>,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub scopes: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub is_first_party: bool,
+    pub owner_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = oauth_clients)]
+pub struct NewOAuthClient {
+    pub client_id: String,
+    pub client_secret_hash: Option<String>,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub scopes: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub is_first_party: bool,
+    pub owner_id: Option<Uuid>,
+}
@@ -0,0 +1,25 @@
+use crate::schema::oauth_consents;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = oauth_consents)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OAuthConsent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub client_id: Uuid,
+    pub scope: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = oauth_consents)]
+pub struct NewOAuthConsent {
+    pub user_id: Uuid,
+    pub client_id: Uuid,
+    pub scope: String,
+}
@@ -0,0 +1,30 @@
+use crate::schema::oidc_login_states;
+use chrono::{DateTime, Utc, NaiveDateTime};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = oidc_login_states)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OidcLoginState {
+    pub id: Uuid,
+    pub state: String,
+    pub provider: String,
+    pub code_verifier: String,
+    pub nonce: String,
+    pub user_id: Option<Uuid>,
+    pub expires_at: NaiveDateTime,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = oidc_login_states)]
+pub struct NewOidcLoginState {
+    pub state: String,
+    pub provider: String,
+    pub code_verifier: String,
+    pub nonce: String,
+    pub user_id: Option<Uuid>,
+    pub expires_at: NaiveDateTime,
+}
@@ -10,15 +10,33 @@ use uuid::Uuid;
 pub struct RefreshToken {
     pub id: Uuid,
     pub user_id: Uuid,
-    pub token: String,
+    pub token_hash: String,
     pub expires_at: NaiveDateTime,
     pub created_at: Option<DateTime<Utc>>,
+    pub family_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub rotated_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub client_id: Option<Uuid>,
+    pub scope: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub device_label: Option<String>,
+    pub session_started_at: DateTime<Utc>,
 }
 
 #[derive(Insertable, Debug)]
 #[diesel(table_name = refresh_tokens)]
 pub struct NewRefreshToken {
     pub user_id: Uuid,
-    pub token: String,
+    pub token_hash: String,
     pub expires_at: NaiveDateTime,
+    pub family_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub client_id: Option<Uuid>,
+    pub scope: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub device_label: Option<String>,
+    pub session_started_at: Option<DateTime<Utc>>,
 }
@@ -10,7 +10,7 @@ use uuid::Uuid;
 pub struct RevokedToken {
     pub id: Uuid,
     pub token_jti: String,
-    pub user_id: Uuid,
+    pub user_id: Option<Uuid>,
     pub expiry: NaiveDateTime,
     pub created_at: Option<DateTime<Utc>>,
 }
@@ -19,6 +19,6 @@ pub struct RevokedToken {
 #[diesel(table_name = revoked_tokens)]
 pub struct NewRevokedToken {
     pub token_jti: String,
-    pub user_id: Uuid,
+    pub user_id: Option<Uuid>,
     pub expiry: NaiveDateTime,
 }
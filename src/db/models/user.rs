@@ -15,12 +15,21 @@ pub struct User {
     pub password_hash: String,
     pub is_active: Option<bool>,
     pub is_verified: Option<bool>,
-    pub verification_token: Option<String>,
+    pub verification_token_hash: Option<String>,
     pub verification_token_expires: Option<NaiveDateTime>,
-    pub password_reset_token: Option<String>,
+    pub password_reset_token_hash: Option<String>,
     pub password_reset_expires: Option<NaiveDateTime>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub locale: Option<String>,
+    pub tokens_valid_after: Option<NaiveDateTime>,
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<NaiveDateTime>,
+    pub totp_last_used_step: Option<i64>,
+    pub magic_link_token_hash: Option<String>,
+    pub magic_link_expires: Option<NaiveDateTime>,
+    pub active_organization_id: Option<Uuid>,
+    pub verification_sent_at: Option<NaiveDateTime>,
 }
 
 #[derive(Insertable, Debug)]
@@ -32,4 +41,5 @@ pub struct NewUser {
     pub password_hash: String,
     pub is_active: Option<bool>,
     pub is_verified: Option<bool>,
+    pub locale: Option<String>,
 }
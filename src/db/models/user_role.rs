@@ -0,0 +1,10 @@
+use crate::schema::user_roles;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = user_roles)]
+pub struct NewUserRole {
+    pub user_id: Uuid,
+    pub role_id: Uuid,
+}
@@ -0,0 +1,26 @@
+use crate::schema::webauthn_challenges;
+use chrono::{DateTime, Utc, NaiveDateTime};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = webauthn_challenges)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebauthnChallenge {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub challenge: String,
+    pub ceremony: String,
+    pub expires_at: NaiveDateTime,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = webauthn_challenges)]
+pub struct NewWebauthnChallenge {
+    pub user_id: Option<Uuid>,
+    pub challenge: String,
+    pub ceremony: String,
+    pub expires_at: NaiveDateTime,
+}
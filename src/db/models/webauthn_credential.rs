@@ -0,0 +1,29 @@
+use crate::schema::webauthn_credentials;
+use chrono::{DateTime, Utc, NaiveDateTime};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = webauthn_credentials)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebauthnCredential {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub credential_id: String,
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+    pub name: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = webauthn_credentials)]
+pub struct NewWebauthnCredential {
+    pub user_id: Uuid,
+    pub credential_id: String,
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+    pub name: Option<String>,
+}
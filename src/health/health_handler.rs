@@ -1,4 +1,4 @@
-use crate::{app::AppState, errors::ApiResult};
+use crate::{app::AppState, db::with_connection, errors::ApiResult};
 use axum::{extract::State, response::Json};
 use serde_json::{json, Value};
 
@@ -15,8 +15,8 @@ pub async fn health_check_handler(
 pub async fn readiness_check_handler(
     State(state): State<AppState>,
 ) -> ApiResult<Json<Value>> {
-    match state.pool.get() {
-        Ok(_) => Ok(Json(json!({
+    match with_connection(&state.pool, |_| Ok(())).await {
+        Ok(()) => Ok(Json(json!({
             "status": "ready",
             "timestamp": chrono::Utc::now().to_rfc3339(),
             "database": "connected"
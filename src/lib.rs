@@ -1,13 +1,23 @@
+pub mod admin;
+pub mod api_key;
 pub mod app;
+pub mod audit;
 pub mod auth;
 pub mod config;
 pub mod db;
 pub mod errors;
 pub mod health;
+pub mod mail;
+pub mod mfa;
 pub mod middleware;
+pub mod oauth;
+pub mod oidc;
+pub mod organization;
+pub mod rbac;
 pub mod routes;
 pub mod schema;
 pub mod user;
 pub mod utils;
+pub mod webauthn;
 
 pub use app::app;
@@ -0,0 +1,108 @@
+use crate::mail::mailer::{build_message, MailError, Mailer, OutgoingEmail};
+use futures::future::BoxFuture;
+use lettre::message::Mailbox;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+pub enum FileTarget {
+    Directory(PathBuf),
+    Mbox(PathBuf),
+}
+
+pub struct FileMailer {
+    target: FileTarget,
+    from: Mailbox,
+    write_lock: Mutex<()>,
+}
+
+impl FileMailer {
+    pub fn new(target: FileTarget, from: Mailbox) -> Self {
+        Self {
+            target,
+            from,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    async fn write_to_directory(&self, dir: &PathBuf, formatted: &[u8]) -> Result<(), MailError> {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| MailError::Transport(e.to_string()))?;
+
+        let file_name = format!(
+            "{}-{}.eml",
+            chrono::Utc::now().format("%Y%m%d%H%M%S%6f"),
+            Uuid::new_v4()
+        );
+
+        // Written under a temporary name first so readers never see a partial message.
+        let partial_path = dir.join(format!("{}.tmp", file_name));
+        tokio::fs::write(&partial_path, formatted)
+            .await
+            .map_err(|e| MailError::Transport(e.to_string()))?;
+        tokio::fs::rename(&partial_path, dir.join(file_name))
+            .await
+            .map_err(|e| MailError::Transport(e.to_string()))
+    }
+
+    async fn append_to_mbox(&self, path: &PathBuf, formatted: &[u8]) -> Result<(), MailError> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| MailError::Transport(e.to_string()))?;
+        }
+
+        let entry = mbox_entry(self.from.email.as_ref(), formatted);
+
+        let _guard = self.write_lock.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|e| MailError::Transport(e.to_string()))?;
+
+        file.write_all(entry.as_bytes())
+            .await
+            .map_err(|e| MailError::Transport(e.to_string()))?;
+        file.flush()
+            .await
+            .map_err(|e| MailError::Transport(e.to_string()))
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&self, email: OutgoingEmail) -> BoxFuture<'_, Result<(), MailError>> {
+        Box::pin(async move {
+            let formatted = build_message(&self.from, &email)?.formatted();
+
+            match &self.target {
+                FileTarget::Directory(dir) => self.write_to_directory(dir, &formatted).await,
+                FileTarget::Mbox(path) => self.append_to_mbox(path, &formatted).await,
+            }
+        })
+    }
+}
+
+fn mbox_entry(sender: &str, formatted: &[u8]) -> String {
+    let message = String::from_utf8_lossy(formatted).replace("\r\n", "\n");
+
+    let mut entry = format!(
+        "From {} {}\n",
+        sender,
+        chrono::Utc::now().format("%a %b %e %H:%M:%S %Y")
+    );
+
+    for line in message.lines() {
+        if line.trim_start_matches('>').starts_with("From ") {
+            entry.push('>');
+        }
+        entry.push_str(line);
+        entry.push('\n');
+    }
+    entry.push('\n');
+
+    entry
+}
@@ -0,0 +1,136 @@
+use crate::db::models::user::User;
+use crate::mail::mail_templates::render_template;
+use crate::mail::mailer::{Mailer, OutgoingEmail};
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub fn send_verification_email(mailer: &Arc<dyn Mailer>, user: &User, token: &str) {
+    let mut variables = user_variables(user);
+    variables.insert("token", token.to_string());
+    variables.insert("verification_link", format!("{}/verify-email?token={}", app_url(), token));
+
+    deliver(mailer, user, "verify_email", variables);
+}
+
+pub fn send_password_reset_email(mailer: &Arc<dyn Mailer>, user: &User, token: &str) {
+    let mut variables = user_variables(user);
+    variables.insert("token", token.to_string());
+    variables.insert("reset_link", format!("{}/reset-password?token={}", app_url(), token));
+
+    deliver(mailer, user, "password_reset", variables);
+}
+
+pub fn send_magic_link_email(mailer: &Arc<dyn Mailer>, user: &User, token: &str) {
+    let mut variables = user_variables(user);
+    variables.insert("token", token.to_string());
+    variables.insert("login_link", format!("{}/magic-link?token={}", app_url(), token));
+
+    deliver(mailer, user, "magic_link", variables);
+}
+
+pub fn send_password_changed_email(mailer: &Arc<dyn Mailer>, user: &User) {
+    deliver(mailer, user, "password_changed", user_variables(user));
+}
+
+pub fn send_refresh_token_reuse_email(mailer: &Arc<dyn Mailer>, user: &User) {
+    deliver(mailer, user, "refresh_token_reuse", user_variables(user));
+}
+
+// Invitations go to addresses that may not have an account yet, so they use
+// the default locale unless the invitee is a user.
+pub fn send_invitation_email(
+    mailer: &Arc<dyn Mailer>,
+    email: &str,
+    invitee: Option<&User>,
+    inviter: &User,
+    organization_name: &str,
+    token: &str,
+) {
+    let mut variables = HashMap::from([
+        ("email", email.to_string()),
+        ("app_url", app_url()),
+        ("inviter_name", format!("{} {}", inviter.first_name, inviter.last_name)),
+        ("organization_name", organization_name.to_string()),
+        ("token", token.to_string()),
+        ("invitation_link", format!("{}/invitations?token={}", app_url(), token)),
+    ]);
+    if let Some(invitee) = invitee {
+        variables.extend(user_variables(invitee));
+    }
+
+    deliver_to(mailer, email, invitee.and_then(|user| user.locale.as_deref()), "invitation", variables);
+}
+
+fn deliver(mailer: &Arc<dyn Mailer>, user: &User, template: &str, variables: HashMap<&'static str, String>) {
+    deliver_to(mailer, &user.email, user.locale.as_deref(), template, variables);
+}
+
+// Mail goes out in the background: the request doesn't wait on the SMTP
+// server, and its timing doesn't reveal whether an email was sent.
+fn deliver_to(
+    mailer: &Arc<dyn Mailer>,
+    to: &str,
+    locale: Option<&str>,
+    template: &str,
+    variables: HashMap<&'static str, String>,
+) {
+    let mailer = Arc::clone(mailer);
+    let to = to.to_string();
+    let locale = locale.map(str::to_string);
+    let template = template.to_string();
+
+    tokio::spawn(async move {
+        // Templates are read from disk, which would block the runtime.
+        let template_name = template.clone();
+        let rendered = tokio::task::spawn_blocking(move || {
+            render_template(&templates_dir(), &template_name, locale.as_deref(), &default_locale(), &variables)
+        })
+        .await;
+
+        let rendered = match rendered {
+            Ok(Ok(rendered)) => rendered,
+            Ok(Err(e)) => {
+                tracing::error!("Failed to render email template {}: {}", template, e);
+                return;
+            }
+            Err(e) => {
+                tracing::error!("Email template rendering task failed for {}: {}", template, e);
+                return;
+            }
+        };
+
+        let email = OutgoingEmail {
+            to: to.clone(),
+            subject: rendered.subject,
+            text_body: rendered.text_body,
+            html_body: rendered.html_body,
+        };
+
+        if let Err(e) = mailer.send(email).await {
+            tracing::error!("Failed to send email to {}: {}", to, e);
+        }
+    });
+}
+
+fn user_variables(user: &User) -> HashMap<&'static str, String> {
+    HashMap::from([
+        ("first_name", user.first_name.clone()),
+        ("last_name", user.last_name.clone()),
+        ("email", user.email.clone()),
+        ("app_url", app_url()),
+    ])
+}
+
+fn app_url() -> String {
+    env::var("APP_URL").unwrap_or_else(|_| "http://localhost:3000".to_string())
+}
+
+fn templates_dir() -> PathBuf {
+    PathBuf::from(env::var("MAIL_TEMPLATES_DIR").unwrap_or_else(|_| "templates/email".to_string()))
+}
+
+fn default_locale() -> String {
+    env::var("MAIL_DEFAULT_LOCALE").unwrap_or_else(|_| "en".to_string())
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum TemplateError {
+    NotFound(String),
+    Io(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::NotFound(msg) => write!(f, "Template not found: {}", msg),
+            TemplateError::Io(msg) => write!(f, "Template IO error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+pub fn render_template(
+    templates_dir: &Path,
+    name: &str,
+    locale: Option<&str>,
+    default_locale: &str,
+    variables: &HashMap<&str, String>,
+) -> Result<RenderedEmail, TemplateError> {
+    let locale_dir = locale_candidates(locale, default_locale)
+        .into_iter()
+        .map(|candidate| templates_dir.join(candidate))
+        .find(|dir| dir.join(format!("{}.subject.txt", name)).is_file())
+        .ok_or_else(|| TemplateError::NotFound(format!("{} ({})", name, locale.unwrap_or(default_locale))))?;
+
+    let subject = read_template(&locale_dir, name, "subject.txt")?
+        .ok_or_else(|| TemplateError::NotFound(format!("{}.subject.txt", name)))?;
+    let text_body = read_template(&locale_dir, name, "txt")?
+        .ok_or_else(|| TemplateError::NotFound(format!("{}.txt", name)))?;
+    let html_body = read_template(&locale_dir, name, "html")?;
+
+    Ok(RenderedEmail {
+        subject: substitute(subject.trim(), variables, false),
+        text_body: substitute(&text_body, variables, false),
+        html_body: html_body.map(|html| substitute(&html, variables, true)),
+    })
+}
+
+fn read_template(dir: &Path, name: &str, extension: &str) -> Result<Option<String>, TemplateError> {
+    let path: PathBuf = dir.join(format!("{}.{}", name, extension));
+
+    match std::fs::read_to_string(&path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(TemplateError::Io(format!("{}: {}", path.display(), e))),
+    }
+}
+
+fn locale_candidates(locale: Option<&str>, default_locale: &str) -> Vec<String> {
+    let mut candidates = Vec::new();
+
+    if let Some(locale) = locale.map(|l| l.trim().replace('_', "-")).filter(|l| !l.is_empty()) {
+        if let Some((language, _)) = locale.split_once('-') {
+            candidates.push(locale.clone());
+            candidates.push(language.to_string());
+        } else {
+            candidates.push(locale);
+        }
+    }
+    candidates.push(default_locale.to_string());
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .collect()
+}
+
+fn substitute(template: &str, variables: &HashMap<&str, String>, escape: bool) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+
+        match after_open.find("}}") {
+            Some(end) => {
+                let key = after_open[..end].trim();
+                let value = variables.get(key).map(String::as_str).unwrap_or_default();
+                if escape {
+                    output.push_str(&escape_html(value));
+                } else {
+                    output.push_str(value);
+                }
+                rest = &after_open[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+
+    output
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
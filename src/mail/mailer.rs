@@ -0,0 +1,55 @@
+use futures::future::BoxFuture;
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::Message;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum MailError {
+    InvalidMessage(String),
+    Transport(String),
+}
+
+impl fmt::Display for MailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MailError::InvalidMessage(msg) => write!(f, "Invalid message: {}", msg),
+            MailError::Transport(msg) => write!(f, "Transport error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for MailError {}
+
+pub trait Mailer: Send + Sync {
+    fn send(&self, email: OutgoingEmail) -> BoxFuture<'_, Result<(), MailError>>;
+}
+
+pub fn build_message(from: &Mailbox, email: &OutgoingEmail) -> Result<Message, MailError> {
+    let to: Mailbox = email
+        .to
+        .parse()
+        .map_err(|e| MailError::InvalidMessage(format!("Invalid recipient: {}", e)))?;
+
+    let builder = Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(email.subject.clone());
+
+    let message = match &email.html_body {
+        Some(html_body) => builder.multipart(MultiPart::alternative_plain_html(
+            email.text_body.clone(),
+            html_body.clone(),
+        )),
+        None => builder.singlepart(SinglePart::plain(email.text_body.clone())),
+    };
+
+    message.map_err(|e| MailError::InvalidMessage(e.to_string()))
+}
@@ -0,0 +1,5 @@
+pub mod mailer;
+pub mod smtp_mailer;
+pub mod file_mailer;
+pub mod mail_notifications;
+pub mod mail_templates;
@@ -0,0 +1,34 @@
+use crate::mail::mailer::{build_message, MailError, Mailer, OutgoingEmail};
+use futures::future::BoxFuture;
+use lettre::message::Mailbox;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(smtp_url: &str, from: Mailbox) -> Result<Self, MailError> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(smtp_url)
+            .map_err(|e| MailError::Transport(e.to_string()))?
+            .build();
+
+        Ok(Self { transport, from })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send(&self, email: OutgoingEmail) -> BoxFuture<'_, Result<(), MailError>> {
+        Box::pin(async move {
+            let message = build_message(&self.from, &email)?;
+
+            self.transport
+                .send(message)
+                .await
+                .map_err(|e| MailError::Transport(e.to_string()))?;
+
+            Ok(())
+        })
+    }
+}
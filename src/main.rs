@@ -1,14 +1,4 @@
-mod app;
-mod auth;
-mod config;
-mod db;
-mod errors;
-mod health;
-mod middleware;
-mod routes;
-mod schema;
-mod user;
-mod utils;
+use axum_api_template::app;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TotpCodeRequest {
+    #[validate(length(min = 1, max = 64, message = "Code is required"))]
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct MfaVerifyRequest {
+    #[validate(length(min = 1, message = "MFA token is required"))]
+    pub mfa_token: String,
+
+    #[validate(length(min = 1, max = 64, message = "Code is required"))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TotpEnrollmentResponse {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
@@ -0,0 +1,67 @@
+use crate::{
+    app::AppState,
+    auth::{auth_dto::AuthResponse, auth_cookies, auth_middleware::{AuthUser, ClientInfo}},
+    errors::ApiResult,
+    mfa::{
+        mfa_dto::{MfaVerifyRequest, RecoveryCodesResponse, TotpCodeRequest, TotpEnrollmentResponse},
+        mfa_service,
+    },
+};
+use axum::{
+    extract::State,
+    response::Json,
+};
+use serde_json::{json, Value};
+use tower_cookies::Cookies;
+use validator::Validate;
+
+pub async fn enroll_totp_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+) -> ApiResult<Json<TotpEnrollmentResponse>> {
+    let enrollment = mfa_service::enroll_totp(authenticated_user.id, state.pool).await?;
+    Ok(Json(enrollment))
+}
+
+pub async fn confirm_totp_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> ApiResult<Json<RecoveryCodesResponse>> {
+    payload.validate()?;
+    let recovery_codes = mfa_service::confirm_totp(authenticated_user.id, payload, state.pool).await?;
+    Ok(Json(recovery_codes))
+}
+
+pub async fn regenerate_recovery_codes_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> ApiResult<Json<RecoveryCodesResponse>> {
+    payload.validate()?;
+    let recovery_codes = mfa_service::regenerate_recovery_codes(authenticated_user.id, payload, state.pool).await?;
+    Ok(Json(recovery_codes))
+}
+
+pub async fn disable_totp_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> ApiResult<Json<Value>> {
+    payload.validate()?;
+    mfa_service::disable_totp(authenticated_user.id, payload, state.pool).await?;
+    Ok(Json(json!({
+        "message": "Two-factor authentication disabled"
+    })))
+}
+
+pub async fn verify_login_handler(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    cookies: Cookies,
+    Json(payload): Json<MfaVerifyRequest>,
+) -> ApiResult<Json<AuthResponse>> {
+    payload.validate()?;
+    let auth_response = mfa_service::verify_login(payload, client, state.pool).await?;
+    Ok(Json(auth_cookies::store_session(&cookies, &state.auth_cookies, auth_response)))
+}
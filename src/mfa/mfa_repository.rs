@@ -0,0 +1,114 @@
+use crate::db::models::mfa_recovery_code::NewMfaRecoveryCode;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub fn set_pending_totp_secret(user_id: Uuid, secret: &str, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::users::dsl::*;
+
+    diesel::update(users.filter(id.eq(user_id)).filter(totp_enabled_at.is_null()))
+        .set((
+            totp_secret.eq(Some(secret)),
+            totp_last_used_step.eq(None::<i64>),
+        ))
+        .execute(conn)
+}
+
+pub fn enable_totp(user_id: Uuid, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::users::dsl::*;
+
+    diesel::update(users.filter(id.eq(user_id)).filter(totp_secret.is_not_null()))
+        .set(totp_enabled_at.eq(Some(chrono::Utc::now().naive_utc())))
+        .execute(conn)
+}
+
+pub fn disable_totp(user_id: Uuid, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::users::dsl::*;
+
+    diesel::update(users.filter(id.eq(user_id)))
+        .set((
+            totp_secret.eq(None::<String>),
+            totp_enabled_at.eq(None::<chrono::NaiveDateTime>),
+            totp_last_used_step.eq(None::<i64>),
+        ))
+        .execute(conn)
+}
+
+// Only succeeds for a step later than the last accepted one, so a code can't
+// be replayed within its validity window.
+pub fn record_totp_step(user_id: Uuid, step: i64, conn: &mut PgConnection) -> QueryResult<bool> {
+    use crate::schema::users::dsl::*;
+
+    let updated = diesel::update(
+        users
+            .filter(id.eq(user_id))
+            .filter(totp_last_used_step.is_null().or(totp_last_used_step.lt(step))),
+    )
+    .set(totp_last_used_step.eq(Some(step)))
+    .execute(conn)?;
+
+    Ok(updated > 0)
+}
+
+pub fn replace_recovery_codes(user_id_val: Uuid, code_hashes: &[String], conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::mfa_recovery_codes::dsl::*;
+
+    let new_codes: Vec<NewMfaRecoveryCode> = code_hashes
+        .iter()
+        .map(|hash| NewMfaRecoveryCode {
+            user_id: user_id_val,
+            code_hash: hash.clone(),
+        })
+        .collect();
+
+    conn.transaction(|conn| {
+        diesel::delete(mfa_recovery_codes.filter(user_id.eq(user_id_val))).execute(conn)?;
+        diesel::insert_into(mfa_recovery_codes).values(&new_codes).execute(conn)
+    })
+}
+
+pub fn delete_recovery_codes(user_id_val: Uuid, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::mfa_recovery_codes::dsl::*;
+
+    diesel::delete(mfa_recovery_codes.filter(user_id.eq(user_id_val))).execute(conn)
+}
+
+pub fn use_recovery_code(user_id_val: Uuid, code_hash_val: &str, conn: &mut PgConnection) -> QueryResult<bool> {
+    use crate::schema::mfa_recovery_codes::dsl::*;
+
+    let updated = diesel::update(
+        mfa_recovery_codes
+            .filter(user_id.eq(user_id_val))
+            .filter(code_hash.eq(code_hash_val))
+            .filter(used_at.is_null()),
+    )
+    .set(used_at.eq(Some(chrono::Utc::now().naive_utc())))
+    .execute(conn)?;
+
+    Ok(updated > 0)
+}
+
+// Returns how many codes have failed for this challenge so far.
+pub fn record_challenge_failure(
+    jti: &str,
+    user_id_val: Uuid,
+    expires: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> QueryResult<i32> {
+    use crate::schema::mfa_challenge_failures::dsl::*;
+
+    diesel::insert_into(mfa_challenge_failures)
+        .values((token_jti.eq(jti), user_id.eq(user_id_val), expires_at.eq(expires)))
+        .on_conflict(token_jti)
+        .do_update()
+        .set(failed_attempts.eq(failed_attempts + 1))
+        .returning(failed_attempts)
+        .get_result(conn)
+}
+
+pub fn clean_expired_challenge_failures(conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::mfa_challenge_failures::dsl::*;
+
+    diesel::delete(mfa_challenge_failures.filter(expires_at.lt(chrono::Utc::now().naive_utc())))
+        .execute(conn)
+}
@@ -0,0 +1,238 @@
+use crate::{
+    audit::audit_service::{self, AuditEntry, AuditLog},
+    auth::{
+        auth_dto::AuthResponse,
+        auth_hashing::hash_token,
+        auth_middleware::ClientInfo,
+        auth_repository,
+        auth_service,
+        auth_tokens::validate_mfa_challenge_token,
+    },
+    config::database::DbPool,
+    db::{
+        models::{revoked_token::NewRevokedToken, user::User},
+        with_connection,
+    },
+    errors::{ApiError, ApiResult},
+    mfa::{
+        mfa_dto::{MfaVerifyRequest, RecoveryCodesResponse, TotpCodeRequest, TotpEnrollmentResponse},
+        mfa_repository,
+        mfa_totp::{generate_recovery_codes, generate_secret, matching_step, normalize_recovery_code, provisioning_uri},
+    },
+    user::user_repository,
+};
+use chrono::{DateTime, Utc};
+use diesel::PgConnection;
+use uuid::Uuid;
+
+const RECOVERY_CODE_COUNT: usize = 10;
+const MAX_CHALLENGE_FAILURES: i32 = 5;
+
+pub async fn enroll_totp(user_id: Uuid, pool: DbPool) -> ApiResult<TotpEnrollmentResponse> {
+    with_connection(&pool, move |conn| {
+        let user = user_repository::find_user_by_id(user_id, conn)?;
+
+        if user.totp_enabled_at.is_some() {
+            return Err(ApiError::BadRequest("Two-factor authentication is already enabled".to_string()));
+        }
+
+        let secret = generate_secret();
+        let provisioning_uri = provisioning_uri(&secret, &user.email)?;
+
+        mfa_repository::set_pending_totp_secret(user.id, &secret, conn)?;
+
+        Ok(TotpEnrollmentResponse {
+            secret,
+            provisioning_uri,
+        })
+    })
+    .await
+}
+
+pub async fn confirm_totp(
+    user_id: Uuid,
+    request: TotpCodeRequest,
+    pool: DbPool,
+) -> ApiResult<RecoveryCodesResponse> {
+    with_connection(&pool, move |conn| {
+        let user = user_repository::find_user_by_id(user_id, conn)?;
+
+        if user.totp_enabled_at.is_some() {
+            return Err(ApiError::BadRequest("Two-factor authentication is already enabled".to_string()));
+        }
+
+        let Some(secret) = user.totp_secret.as_deref() else {
+            return Err(ApiError::BadRequest("Two-factor enrollment has not been started".to_string()));
+        };
+
+        if !verify_totp_code(&user, secret, &request.code, conn)? {
+            return Err(ApiError::BadRequest("Invalid authentication code".to_string()));
+        }
+
+        mfa_repository::enable_totp(user.id, conn)?;
+        let recovery_codes = issue_recovery_codes(user.id, conn)?;
+
+        tracing::info!("Two-factor authentication enabled for user: {}", user.email);
+
+        Ok(RecoveryCodesResponse { recovery_codes })
+    })
+    .await
+}
+
+pub async fn regenerate_recovery_codes(
+    user_id: Uuid,
+    request: TotpCodeRequest,
+    pool: DbPool,
+) -> ApiResult<RecoveryCodesResponse> {
+    with_connection(&pool, move |conn| {
+        let user = find_user_with_totp(user_id, conn)?;
+        let secret = user.totp_secret.as_deref().unwrap_or_default();
+
+        if !verify_totp_code(&user, secret, &request.code, conn)? {
+            return Err(ApiError::BadRequest("Invalid authentication code".to_string()));
+        }
+
+        let recovery_codes = issue_recovery_codes(user.id, conn)?;
+
+        Ok(RecoveryCodesResponse { recovery_codes })
+    })
+    .await
+}
+
+pub async fn disable_totp(user_id: Uuid, request: TotpCodeRequest, pool: DbPool) -> ApiResult<()> {
+    with_connection(&pool, move |conn| {
+        let user = find_user_with_totp(user_id, conn)?;
+
+        if !verify_second_factor(&user, &request.code, conn)? {
+            return Err(ApiError::BadRequest("Invalid authentication code".to_string()));
+        }
+
+        mfa_repository::disable_totp(user.id, conn)?;
+        mfa_repository::delete_recovery_codes(user.id, conn)?;
+
+        tracing::info!("Two-factor authentication disabled for user: {}", user.email);
+
+        Ok(())
+    })
+    .await
+}
+
+pub async fn verify_login(request: MfaVerifyRequest, client: ClientInfo, pool: DbPool) -> ApiResult<AuthResponse> {
+    let audit = AuditLog::new(&pool, &client);
+    let challenged_user = validate_mfa_challenge_token(&request.mfa_token).ok().map(|claims| claims.sub);
+    let method = if is_totp_code(&request.code) { "totp" } else { "recovery_code" };
+    let result = complete_challenge(request, client, pool).await;
+
+    let user_id = result.as_ref().ok().map(|response| response.user.id);
+    audit
+        .record(
+            AuditEntry::from_result(audit_service::LOGIN, &result)
+                .actor(user_id)
+                .target_user(challenged_user)
+                .with("method", method),
+        )
+        .await;
+
+    result
+}
+
+async fn complete_challenge(request: MfaVerifyRequest, client: ClientInfo, pool: DbPool) -> ApiResult<AuthResponse> {
+    let claims = validate_mfa_challenge_token(&request.mfa_token)
+        .map_err(|_| ApiError::Unauthorized("Invalid or expired MFA token".to_string()))?;
+
+    with_connection(&pool, move |conn| {
+        if auth_repository::is_token_revoked(&claims.jti, conn)? {
+            return Err(ApiError::Unauthorized("Invalid or expired MFA token".to_string()));
+        }
+
+        let user = user_repository::find_user_by_id(claims.sub, conn)
+            .map_err(|_| ApiError::Unauthorized("Invalid or expired MFA token".to_string()))?;
+
+        if user.totp_enabled_at.is_none() {
+            return Err(ApiError::Unauthorized("Invalid or expired MFA token".to_string()));
+        }
+
+        let expiry = DateTime::<Utc>::from_timestamp(claims.exp, 0)
+            .map(|dt| dt.naive_utc())
+            .unwrap_or_else(|| Utc::now().naive_utc());
+
+        // Each challenge allows a few wrong codes before the password has to
+        // be entered again.
+        if !verify_second_factor(&user, &request.code, conn)? {
+            let failures = mfa_repository::record_challenge_failure(&claims.jti, user.id, expiry, conn)?;
+
+            if failures >= MAX_CHALLENGE_FAILURES {
+                auth_repository::revoke_token(
+                    &NewRevokedToken {
+                        token_jti: claims.jti,
+                        user_id: Some(user.id),
+                        expiry,
+                    },
+                    conn,
+                )?;
+
+                tracing::warn!(target: "security", user_id = %user.id, "MFA challenge revoked after too many invalid codes");
+            }
+
+            return Err(ApiError::Unauthorized("Invalid authentication code".to_string()));
+        }
+
+        auth_repository::revoke_token(
+            &NewRevokedToken {
+                token_jti: claims.jti,
+                user_id: Some(user.id),
+                expiry,
+            },
+            conn,
+        )?;
+
+        auth_service::start_session(user, &client, conn)
+    })
+    .await
+}
+
+fn find_user_with_totp(user_id: Uuid, conn: &mut PgConnection) -> ApiResult<User> {
+    let user = user_repository::find_user_by_id(user_id, conn)?;
+
+    if user.totp_enabled_at.is_none() || user.totp_secret.is_none() {
+        return Err(ApiError::BadRequest("Two-factor authentication is not enabled".to_string()));
+    }
+
+    Ok(user)
+}
+
+fn verify_totp_code(user: &User, secret: &str, code: &str, conn: &mut PgConnection) -> ApiResult<bool> {
+    let now = Utc::now().timestamp().max(0) as u64;
+
+    match matching_step(secret, code.trim(), now)? {
+        Some(step) => Ok(mfa_repository::record_totp_step(user.id, step, conn)?),
+        None => Ok(false),
+    }
+}
+
+fn verify_second_factor(user: &User, code: &str, conn: &mut PgConnection) -> ApiResult<bool> {
+    let code = code.trim();
+
+    if is_totp_code(code) {
+        let secret = user.totp_secret.as_deref().unwrap_or_default();
+        return verify_totp_code(user, secret, code, conn);
+    }
+
+    let code_hash = hash_token(&normalize_recovery_code(code));
+    Ok(mfa_repository::use_recovery_code(user.id, &code_hash, conn)?)
+}
+
+// Anything that isn't six digits is treated as a recovery code.
+fn is_totp_code(code: &str) -> bool {
+    let code = code.trim();
+    code.len() == 6 && code.chars().all(|c| c.is_ascii_digit())
+}
+
+fn issue_recovery_codes(user_id: Uuid, conn: &mut PgConnection) -> ApiResult<Vec<String>> {
+    let recovery_codes = generate_recovery_codes(RECOVERY_CODE_COUNT);
+    let code_hashes: Vec<String> = recovery_codes.iter().map(|code| hash_token(code)).collect();
+
+    mfa_repository::replace_recovery_codes(user_id, &code_hashes, conn)?;
+
+    Ok(recovery_codes)
+}
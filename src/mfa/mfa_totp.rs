@@ -0,0 +1,76 @@
+use crate::auth::auth_hashing::constant_time_eq;
+use crate::errors::{ApiError, ApiResult};
+use rand::Rng;
+use std::env;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+const SECRET_BYTES: usize = 20;
+const STEP_SECONDS: u64 = 30;
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+pub fn generate_secret() -> String {
+    let bytes: Vec<u8> = (0..SECRET_BYTES).map(|_| rand::rng().random()).collect();
+    Secret::Raw(bytes).to_encoded().to_string()
+}
+
+pub fn provisioning_uri(secret: &str, account: &str) -> ApiResult<String> {
+    Ok(totp(secret, account)?.get_url())
+}
+
+// Returns the time step the code was generated for, allowing one step of
+// clock drift either way.
+pub fn matching_step(secret: &str, code: &str, unix_time: u64) -> ApiResult<Option<i64>> {
+    let totp = totp(secret, "")?;
+    let current_step = unix_time / STEP_SECONDS;
+
+    Ok([current_step.saturating_sub(1), current_step, current_step + 1]
+        .into_iter()
+        .find(|step| constant_time_eq(totp.generate(step * STEP_SECONDS).as_bytes(), code.as_bytes()))
+        .map(|step| step as i64))
+}
+
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|_| {
+            let chars: String = (0..10)
+                .map(|_| RECOVERY_CODE_ALPHABET[rand::rng().random_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+                .collect();
+            format!("{}-{}", &chars[..5], &chars[5..])
+        })
+        .collect()
+}
+
+pub fn normalize_recovery_code(code: &str) -> String {
+    let chars: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    if chars.len() == 10 {
+        format!("{}-{}", &chars[..5], &chars[5..])
+    } else {
+        chars
+    }
+}
+
+fn totp(secret: &str, account: &str) -> ApiResult<TOTP> {
+    let secret = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| ApiError::InternalServerError(format!("Invalid TOTP secret: {}", e)))?;
+
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        STEP_SECONDS,
+        secret,
+        Some(totp_issuer()),
+        account.to_string(),
+    )
+    .map_err(|e| ApiError::InternalServerError(format!("Invalid TOTP parameters: {}", e)))
+}
+
+fn totp_issuer() -> String {
+    env::var("TOTP_ISSUER").unwrap_or_else(|_| "Axum API Template".to_string())
+}
@@ -0,0 +1,5 @@
+pub mod mfa_handler;
+pub mod mfa_service;
+pub mod mfa_repository;
+pub mod mfa_dto;
+pub mod mfa_totp;
@@ -0,0 +1,60 @@
+use crate::auth::auth_cookies::{ACCESS_TOKEN_COOKIE, CSRF_TOKEN_COOKIE, CSRF_TOKEN_HEADER, REFRESH_TOKEN_COOKIE};
+use crate::auth::auth_hashing::constant_time_eq;
+use crate::config::auth_cookies::AuthCookieConfig;
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use tower_cookies::Cookies;
+
+// Browsers attach the auth cookies to cross-site requests too, so a
+// state-changing request that carries them must echo the CSRF cookie in the
+// `X-CSRF-Token` header (double-submit). Requests that authenticate with a
+// bearer token or API key are not exposed and pass through; any other
+// `Authorization` header doesn't count, since cookies may still be used.
+pub async fn csrf_middleware(
+    State(config): State<AuthCookieConfig>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if !config.enabled
+        || req.method().is_safe()
+        || req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|header| header.to_str().ok())
+            .is_some_and(|header| header.starts_with("Bearer "))
+        || req.headers().contains_key("X-API-Key")
+    {
+        return Ok(next.run(req).await);
+    }
+
+    let Some(cookies) = req.extensions().get::<Cookies>() else {
+        return Ok(next.run(req).await);
+    };
+
+    if cookies.get(ACCESS_TOKEN_COOKIE).is_none() && cookies.get(REFRESH_TOKEN_COOKIE).is_none() {
+        return Ok(next.run(req).await);
+    }
+
+    let expected = cookies
+        .get(CSRF_TOKEN_COOKIE)
+        .map(|cookie| cookie.value().to_string())
+        .filter(|value| !value.is_empty());
+    let provided = req
+        .headers()
+        .get(CSRF_TOKEN_HEADER)
+        .and_then(|header| header.to_str().ok());
+
+    match (expected, provided) {
+        (Some(expected), Some(provided)) if constant_time_eq(expected.as_bytes(), provided.as_bytes()) => {
+            Ok(next.run(req).await)
+        }
+        _ => {
+            tracing::warn!(target: "security", path = %req.uri().path(), "Rejected request without a valid CSRF token");
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
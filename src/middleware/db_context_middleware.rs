@@ -0,0 +1,18 @@
+use crate::auth::auth_middleware::AuthUser;
+use crate::db::DbContext;
+use axum::{extract::Request, middleware::Next, response::Response};
+
+// Runs after `auth_middleware`: database work done for the rest of the request
+// is tagged with the caller and their active organization for RLS policies.
+pub async fn db_context_middleware(req: Request, next: Next) -> Response {
+    let Some(user) = req.extensions().get::<AuthUser>() else {
+        return next.run(req).await;
+    };
+
+    let context = DbContext {
+        user_id: Some(user.id),
+        organization_id: user.claim("org_id"),
+    };
+
+    context.scope(next.run(req)).await
+}
@@ -1,2 +1,4 @@
+pub mod csrf_middleware;
 pub mod error_middleware;
 pub mod rate_limiter;
+pub mod db_context_middleware;
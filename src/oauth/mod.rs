@@ -0,0 +1,5 @@
+pub mod oauth_handler;
+pub mod oauth_service;
+pub mod oauth_repository;
+pub mod oauth_dto;
+pub mod oauth_error;
@@ -0,0 +1,165 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RegisterClientRequest {
+    #[validate(length(min = 1, max = 255, message = "Name must be between 1 and 255 characters"))]
+    pub name: String,
+
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+
+    #[validate(length(min = 1, message = "At least one scope is required"))]
+    pub scopes: Vec<String>,
+
+    pub grant_types: Option<Vec<String>>,
+
+    // Public clients (SPAs, mobile apps) can't keep a secret and authenticate
+    // with PKCE only.
+    #[serde(default = "default_confidential")]
+    pub confidential: bool,
+}
+
+fn default_confidential() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClientResponse {
+    pub id: Uuid,
+    pub client_id: String,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub scopes: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub confidential: bool,
+    pub first_party: bool,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegisteredClientResponse {
+    #[serde(flatten)]
+    pub client: ClientResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthorizeRequest {
+    pub response_type: String,
+    pub client_id: String,
+    pub redirect_uri: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsentDecisionRequest {
+    #[serde(flatten)]
+    pub authorization: AuthorizeRequest,
+    pub approve: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthorizationRedirect {
+    pub redirect_to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConsentClientInfo {
+    pub client_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConsentRequiredResponse {
+    pub consent_required: bool,
+    pub client: ConsentClientInfo,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum AuthorizeResponse {
+    Redirect(AuthorizationRedirect),
+    ConsentRequired(ConsentRequiredResponse),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    pub grant_type: String,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    pub scope: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConsentResponse {
+    pub id: Uuid,
+    pub client_id: String,
+    pub client_name: String,
+    pub scopes: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IntrospectionRequest {
+    #[serde(default)]
+    pub token: String,
+    pub token_type_hint: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevocationRequest {
+    #[serde(default)]
+    pub token: String,
+    pub token_type_hint: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+// Inactive tokens are described by `active: false` alone.
+#[derive(Debug, Default, Serialize)]
+pub struct IntrospectionResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
@@ -0,0 +1,101 @@
+use crate::errors::ApiError;
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::fmt;
+
+// Errors from the token endpoint use the RFC 6749 format so that standard
+// OAuth client libraries can read them.
+#[derive(Debug)]
+pub enum OAuthError {
+    InvalidRequest(String),
+    InvalidClient,
+    InvalidGrant(String),
+    UnauthorizedClient(String),
+    UnsupportedGrantType,
+    InvalidScope(String),
+    Server(ApiError),
+}
+
+impl OAuthError {
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            OAuthError::InvalidRequest(_) => "invalid_request",
+            OAuthError::InvalidClient => "invalid_client",
+            OAuthError::InvalidGrant(_) => "invalid_grant",
+            OAuthError::UnauthorizedClient(_) => "unauthorized_client",
+            OAuthError::UnsupportedGrantType => "unsupported_grant_type",
+            OAuthError::InvalidScope(_) => "invalid_scope",
+            OAuthError::Server(_) => "server_error",
+        }
+    }
+}
+
+impl fmt::Display for OAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OAuthError::InvalidRequest(msg)
+            | OAuthError::InvalidGrant(msg)
+            | OAuthError::UnauthorizedClient(msg)
+            | OAuthError::InvalidScope(msg) => write!(f, "{}", msg),
+            OAuthError::InvalidClient => write!(f, "Client authentication failed"),
+            OAuthError::UnsupportedGrantType => write!(f, "Unsupported grant type"),
+            OAuthError::Server(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for OAuthError {}
+
+impl IntoResponse for OAuthError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            OAuthError::Server(error) => return error.into_response(),
+            OAuthError::InvalidClient => StatusCode::UNAUTHORIZED,
+            _ => StatusCode::BAD_REQUEST,
+        };
+
+        tracing::info!("OAuth error: {}: {}", self.error_code(), self);
+
+        let mut response = (
+            status,
+            [(header::CACHE_CONTROL, "no-store")],
+            Json(json!({
+                "error": self.error_code(),
+                "error_description": self.to_string(),
+            })),
+        )
+            .into_response();
+
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, "Basic realm=\"oauth\"".parse().unwrap());
+        }
+
+        response
+    }
+}
+
+// Failures from the shared session code map onto the grant: an unusable
+// refresh token or a blocked account is `invalid_grant`.
+impl From<ApiError> for OAuthError {
+    fn from(error: ApiError) -> Self {
+        match error {
+            ApiError::Unauthorized(msg) | ApiError::Forbidden(msg) | ApiError::NotFound(msg) => {
+                OAuthError::InvalidGrant(msg)
+            }
+            ApiError::BadRequest(msg) => OAuthError::InvalidRequest(msg),
+            error => OAuthError::Server(error),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for OAuthError {
+    fn from(error: diesel::result::Error) -> Self {
+        OAuthError::Server(error.into())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    app::AppState,
+    auth::auth_middleware::AuthUser,
+    errors::ApiResult,
+    oauth::{
+        oauth_dto::{
+            AuthorizeRequest, AuthorizeResponse, ClientResponse, ConsentDecisionRequest, ConsentResponse,
+            IntrospectionRequest, RegisterClientRequest, RegisteredClientResponse, RevocationRequest, TokenRequest,
+            TokenResponse,
+        },
+        oauth_error::OAuthError,
+        oauth_service,
+    },
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+    Form,
+};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn authorize_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Query(params): Query<AuthorizeRequest>,
+) -> ApiResult<Json<AuthorizeResponse>> {
+    let response = oauth_service::authorize(authenticated_user.id, params, state.pool, &state.oauth).await?;
+    Ok(Json(response))
+}
+
+pub async fn consent_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Json(payload): Json<ConsentDecisionRequest>,
+) -> ApiResult<Json<AuthorizeResponse>> {
+    let response = oauth_service::decide(authenticated_user.id, payload, state.pool, &state.oauth).await?;
+    Ok(Json(response))
+}
+
+pub async fn token_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(payload): Form<TokenRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let credentials = basic_credentials(&headers)?;
+    let response: TokenResponse = oauth_service::token(
+        payload,
+        credentials,
+        state.pool,
+        &state.oauth,
+        &state.email_verification,
+        &state.mailer,
+    )
+    .await?;
+
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+pub async fn introspect_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(payload): Form<IntrospectionRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let credentials = basic_credentials(&headers)?;
+    let response = oauth_service::introspect(payload, credentials, state.pool).await?;
+
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+pub async fn revoke_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(payload): Form<RevocationRequest>,
+) -> Result<StatusCode, OAuthError> {
+    let credentials = basic_credentials(&headers)?;
+    oauth_service::revoke(payload, credentials, state.pool).await?;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn register_client_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Json(payload): Json<RegisterClientRequest>,
+) -> ApiResult<Json<RegisteredClientResponse>> {
+    payload.validate()?;
+    let client = oauth_service::register_client(authenticated_user.id, payload, state.pool, &state.oauth).await?;
+    Ok(Json(client))
+}
+
+pub async fn list_clients_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+) -> ApiResult<Json<Vec<ClientResponse>>> {
+    let clients = oauth_service::list_clients(authenticated_user.id, state.pool).await?;
+    Ok(Json(clients))
+}
+
+pub async fn delete_client_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    oauth_service::delete_client(authenticated_user.id, id, state.pool).await?;
+    Ok(Json(json!({
+        "message": "OAuth client deleted"
+    })))
+}
+
+pub async fn list_consents_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+) -> ApiResult<Json<Vec<ConsentResponse>>> {
+    let consents = oauth_service::list_consents(authenticated_user.id, state.pool).await?;
+    Ok(Json(consents))
+}
+
+pub async fn revoke_consent_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    oauth_service::revoke_consent(authenticated_user.id, id, state.pool).await?;
+    Ok(Json(json!({
+        "message": "Consent revoked"
+    })))
+}
+
+fn basic_credentials(headers: &HeaderMap) -> Result<Option<(String, String)>, OAuthError> {
+    let Some(value) = headers.get(header::AUTHORIZATION) else {
+        return Ok(None);
+    };
+
+    let encoded = value
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix("Basic "))
+        .ok_or(OAuthError::InvalidClient)?;
+    let decoded = STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or(OAuthError::InvalidClient)?;
+    let (client_id, client_secret) = decoded.split_once(':').ok_or(OAuthError::InvalidClient)?;
+
+    Ok(Some((client_id.to_string(), client_secret.to_string())))
+}
@@ -0,0 +1,121 @@
+use crate::db::models::oauth_authorization_code::{NewOAuthAuthorizationCode, OAuthAuthorizationCode};
+use crate::db::models::oauth_client::{NewOAuthClient, OAuthClient};
+use crate::db::models::oauth_consent::{NewOAuthConsent, OAuthConsent};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub fn create_client(new_client: &NewOAuthClient, conn: &mut PgConnection) -> QueryResult<OAuthClient> {
+    use crate::schema::oauth_clients::dsl::*;
+
+    diesel::insert_into(oauth_clients)
+        .values(new_client)
+        .returning(OAuthClient::as_returning())
+        .get_result(conn)
+}
+
+pub fn find_client_by_client_id(client_id_value: &str, conn: &mut PgConnection) -> QueryResult<Option<OAuthClient>> {
+    use crate::schema::oauth_clients::dsl::*;
+
+    oauth_clients
+        .filter(client_id.eq(client_id_value))
+        .select(OAuthClient::as_select())
+        .first(conn)
+        .optional()
+}
+
+pub fn list_clients(owner: Uuid, conn: &mut PgConnection) -> QueryResult<Vec<OAuthClient>> {
+    use crate::schema::oauth_clients::dsl::*;
+
+    oauth_clients
+        .filter(owner_id.eq(owner))
+        .order(created_at.asc())
+        .select(OAuthClient::as_select())
+        .load(conn)
+}
+
+pub fn delete_client(owner: Uuid, client: Uuid, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::oauth_clients::dsl::*;
+
+    diesel::delete(oauth_clients.filter(id.eq(client)).filter(owner_id.eq(owner))).execute(conn)
+}
+
+pub fn create_authorization_code(
+    new_code: &NewOAuthAuthorizationCode,
+    conn: &mut PgConnection,
+) -> QueryResult<usize> {
+    use crate::schema::oauth_authorization_codes::dsl::*;
+
+    diesel::insert_into(oauth_authorization_codes)
+        .values(new_code)
+        .execute(conn)
+}
+
+// Deletes the code while reading it, so it can only be redeemed once.
+pub fn take_authorization_code(
+    code_hash_value: &str,
+    conn: &mut PgConnection,
+) -> QueryResult<Option<OAuthAuthorizationCode>> {
+    use crate::schema::oauth_authorization_codes::dsl::*;
+
+    diesel::delete(
+        oauth_authorization_codes
+            .filter(code_hash.eq(code_hash_value))
+            .filter(expires_at.gt(chrono::Utc::now().naive_utc())),
+    )
+    .returning(OAuthAuthorizationCode::as_returning())
+    .get_result(conn)
+    .optional()
+}
+
+pub fn clean_expired_authorization_codes(conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::oauth_authorization_codes::dsl::*;
+
+    diesel::delete(oauth_authorization_codes.filter(expires_at.lt(chrono::Utc::now().naive_utc())))
+        .execute(conn)
+}
+
+pub fn find_consent(user: Uuid, client: Uuid, conn: &mut PgConnection) -> QueryResult<Option<OAuthConsent>> {
+    use crate::schema::oauth_consents::dsl::*;
+
+    oauth_consents
+        .filter(user_id.eq(user))
+        .filter(client_id.eq(client))
+        .select(OAuthConsent::as_select())
+        .first(conn)
+        .optional()
+}
+
+pub fn save_consent(new_consent: &NewOAuthConsent, conn: &mut PgConnection) -> QueryResult<OAuthConsent> {
+    use crate::schema::oauth_consents::dsl::*;
+
+    diesel::insert_into(oauth_consents)
+        .values(new_consent)
+        .on_conflict((user_id, client_id))
+        .do_update()
+        .set((
+            scope.eq(&new_consent.scope),
+            updated_at.eq(Some(chrono::Utc::now())),
+        ))
+        .returning(OAuthConsent::as_returning())
+        .get_result(conn)
+}
+
+pub fn list_consents(user: Uuid, conn: &mut PgConnection) -> QueryResult<Vec<(OAuthConsent, OAuthClient)>> {
+    use crate::schema::{oauth_clients, oauth_consents};
+
+    oauth_consents::table
+        .inner_join(oauth_clients::table)
+        .filter(oauth_consents::user_id.eq(user))
+        .order(oauth_consents::created_at.asc())
+        .select((OAuthConsent::as_select(), OAuthClient::as_select()))
+        .load(conn)
+}
+
+pub fn delete_consent(user: Uuid, consent: Uuid, conn: &mut PgConnection) -> QueryResult<Option<OAuthConsent>> {
+    use crate::schema::oauth_consents::dsl::*;
+
+    diesel::delete(oauth_consents.filter(id.eq(consent)).filter(user_id.eq(user)))
+        .returning(OAuthConsent::as_returning())
+        .get_result(conn)
+        .optional()
+}
@@ -0,0 +1,832 @@
+use crate::{
+    auth::{
+        auth_hashing::{constant_time_eq, hash_token, pkce_challenge, random_value},
+        auth_middleware::ClientInfo,
+        auth_repository, auth_service,
+        auth_service::RefreshOutcome,
+        auth_tokens::{
+            generate_access_token_with, validate_access_token, validate_refresh_token, AccessTokenOptions,
+            ACCESS_TOKEN_TTL_MINUTES,
+        },
+    },
+    config::{database::DbPool, email_verification::EmailVerificationConfig, oauth::OAuthConfig},
+    db::{
+        models::{
+            oauth_authorization_code::NewOAuthAuthorizationCode,
+            oauth_client::{NewOAuthClient, OAuthClient},
+            oauth_consent::NewOAuthConsent,
+            revoked_token::NewRevokedToken,
+            user::User,
+        },
+        with_connection,
+    },
+    errors::{ApiError, ApiResult},
+    mail::mailer::Mailer,
+    oauth::{
+        oauth_dto::{
+            AuthorizationRedirect, AuthorizeRequest, AuthorizeResponse, ClientResponse, ConsentClientInfo,
+            ConsentDecisionRequest, ConsentRequiredResponse, ConsentResponse, IntrospectionRequest,
+            IntrospectionResponse, RegisterClientRequest, RegisteredClientResponse, RevocationRequest, TokenRequest,
+            TokenResponse,
+        },
+        oauth_error::OAuthError,
+        oauth_repository,
+    },
+    user::user_repository,
+};
+use chrono::{DateTime, Utc};
+use diesel::PgConnection;
+use reqwest::Url;
+use serde_json::Value;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub const AUTHORIZATION_CODE: &str = "authorization_code";
+pub const REFRESH_TOKEN: &str = "refresh_token";
+pub const CLIENT_CREDENTIALS: &str = "client_credentials";
+
+const ACCESS_TOKEN_TYPE: &str = "access_token";
+const REFRESH_TOKEN_TYPE: &str = "refresh_token";
+
+struct ValidatedAuthorization {
+    client: OAuthClient,
+    redirect_uri: String,
+    scopes: Vec<String>,
+    code_challenge: String,
+    state: Option<String>,
+}
+
+pub async fn register_client(
+    owner_id: Uuid,
+    request: RegisterClientRequest,
+    pool: DbPool,
+    config: &OAuthConfig,
+) -> ApiResult<RegisteredClientResponse> {
+    let grant_types = request
+        .grant_types
+        .unwrap_or_else(|| vec![AUTHORIZATION_CODE.to_string(), REFRESH_TOKEN.to_string()]);
+
+    if let Some(grant_type) = grant_types
+        .iter()
+        .find(|grant_type| ![AUTHORIZATION_CODE, REFRESH_TOKEN, CLIENT_CREDENTIALS].contains(&grant_type.as_str()))
+    {
+        return Err(ApiError::BadRequest(format!("Unsupported grant type: {}", grant_type)));
+    }
+    if !request.confidential && grant_types.iter().any(|grant_type| grant_type == CLIENT_CREDENTIALS) {
+        return Err(ApiError::BadRequest("Public clients can't use the client credentials grant".to_string()));
+    }
+    if let Some(scope) = request.scopes.iter().find(|scope| !config.is_supported_scope(scope)) {
+        return Err(ApiError::BadRequest(format!("Unsupported scope: {}", scope)));
+    }
+    if grant_types.iter().any(|grant_type| grant_type == AUTHORIZATION_CODE) && request.redirect_uris.is_empty() {
+        return Err(ApiError::BadRequest("At least one redirect URI is required".to_string()));
+    }
+    if let Some(uri) = request
+        .redirect_uris
+        .iter()
+        .find(|uri| Url::parse(uri).map_or(true, |url| url.fragment().is_some()))
+    {
+        return Err(ApiError::BadRequest(format!("Invalid redirect URI: {}", uri)));
+    }
+
+    let client_secret = request.confidential.then(|| random_value(32));
+    let new_client = NewOAuthClient {
+        client_id: random_value(16),
+        client_secret_hash: client_secret.as_deref().map(hash_token),
+        name: request.name,
+        redirect_uris: request.redirect_uris,
+        scopes: dedup(request.scopes),
+        grant_types: dedup(grant_types),
+        is_first_party: false,
+        owner_id: Some(owner_id),
+    };
+
+    with_connection(&pool, move |conn| {
+        let client = oauth_repository::create_client(&new_client, conn)?;
+
+        tracing::info!("OAuth client {} registered by user: {}", client.client_id, owner_id);
+
+        Ok(RegisteredClientResponse {
+            client: client_to_response(client),
+            client_secret,
+        })
+    })
+    .await
+}
+
+pub async fn list_clients(owner_id: Uuid, pool: DbPool) -> ApiResult<Vec<ClientResponse>> {
+    with_connection(&pool, move |conn| {
+        let clients = oauth_repository::list_clients(owner_id, conn)?;
+        Ok(clients.into_iter().map(client_to_response).collect())
+    })
+    .await
+}
+
+pub async fn delete_client(owner_id: Uuid, client_id: Uuid, pool: DbPool) -> ApiResult<()> {
+    with_connection(&pool, move |conn| {
+        if oauth_repository::delete_client(owner_id, client_id, conn)? == 0 {
+            return Err(ApiError::NotFound("OAuth client not found".to_string()));
+        }
+        Ok(())
+    })
+    .await
+}
+
+// Answers with a redirect back to the client when the request can be
+// completed (or must fail) without the user, and with the details for a
+// consent screen otherwise.
+pub async fn authorize(
+    user_id: Uuid,
+    request: AuthorizeRequest,
+    pool: DbPool,
+    config: &OAuthConfig,
+) -> ApiResult<AuthorizeResponse> {
+    let config = config.clone();
+
+    with_connection(&pool, move |conn| {
+        let authorization = match validate_authorization(request, &config, conn)? {
+            Ok(authorization) => authorization,
+            Err(rejection) => return Ok(rejection),
+        };
+
+        let consented = authorization.client.is_first_party
+            || oauth_repository::find_consent(user_id, authorization.client.id, conn)?.is_some_and(|consent| {
+                let granted = split_scope(&consent.scope);
+                authorization.scopes.iter().all(|scope| granted.contains(scope))
+            });
+
+        if consented {
+            return issue_authorization_code(user_id, authorization, &config, conn);
+        }
+
+        Ok(AuthorizeResponse::ConsentRequired(ConsentRequiredResponse {
+            consent_required: true,
+            client: ConsentClientInfo {
+                client_id: authorization.client.client_id,
+                name: authorization.client.name,
+            },
+            scopes: authorization.scopes,
+        }))
+    })
+    .await
+}
+
+pub async fn decide(
+    user_id: Uuid,
+    request: ConsentDecisionRequest,
+    pool: DbPool,
+    config: &OAuthConfig,
+) -> ApiResult<AuthorizeResponse> {
+    let config = config.clone();
+
+    with_connection(&pool, move |conn| {
+        let authorization = match validate_authorization(request.authorization, &config, conn)? {
+            Ok(authorization) => authorization,
+            Err(rejection) => return Ok(rejection),
+        };
+
+        if !request.approve {
+            return error_redirect(
+                &authorization.redirect_uri,
+                "access_denied",
+                "The user denied the request",
+                authorization.state.as_deref(),
+            );
+        }
+
+        // Consent accumulates: approving new scopes keeps the earlier ones.
+        let mut scopes = oauth_repository::find_consent(user_id, authorization.client.id, conn)?
+            .map(|consent| split_scope(&consent.scope))
+            .unwrap_or_default();
+        scopes.extend(authorization.scopes.iter().cloned());
+
+        oauth_repository::save_consent(
+            &NewOAuthConsent {
+                user_id,
+                client_id: authorization.client.id,
+                scope: dedup(scopes).join(" "),
+            },
+            conn,
+        )?;
+
+        tracing::info!("User {} granted consent to OAuth client {}", user_id, authorization.client.client_id);
+
+        issue_authorization_code(user_id, authorization, &config, conn)
+    })
+    .await
+}
+
+pub async fn list_consents(user_id: Uuid, pool: DbPool) -> ApiResult<Vec<ConsentResponse>> {
+    with_connection(&pool, move |conn| {
+        let consents = oauth_repository::list_consents(user_id, conn)?;
+
+        Ok(consents
+            .into_iter()
+            .map(|(consent, client)| ConsentResponse {
+                id: consent.id,
+                client_id: client.client_id,
+                client_name: client.name,
+                scopes: split_scope(&consent.scope),
+                created_at: consent.created_at,
+                updated_at: consent.updated_at,
+            })
+            .collect())
+    })
+    .await
+}
+
+// Revoking consent also ends the client's refresh tokens for the user, so it
+// has to ask again.
+pub async fn revoke_consent(user_id: Uuid, consent_id: Uuid, pool: DbPool) -> ApiResult<()> {
+    with_connection(&pool, move |conn| {
+        let consent = oauth_repository::delete_consent(user_id, consent_id, conn)?
+            .ok_or_else(|| ApiError::NotFound("Consent not found".to_string()))?;
+
+        auth_repository::revoke_client_refresh_tokens(user_id, consent.client_id, conn)?;
+
+        tracing::info!("User {} revoked consent for OAuth client {}", user_id, consent.client_id);
+
+        Ok(())
+    })
+    .await
+}
+
+pub async fn token(
+    request: TokenRequest,
+    basic_credentials: Option<(String, String)>,
+    pool: DbPool,
+    config: &OAuthConfig,
+    verification: &EmailVerificationConfig,
+    mailer: &Arc<dyn Mailer>,
+) -> Result<TokenResponse, OAuthError> {
+    let config = config.clone();
+    let verification = verification.clone();
+
+    match request.grant_type.as_str() {
+        AUTHORIZATION_CODE => with_connection(&pool, move |conn| {
+            Ok(authorization_code_grant(request, basic_credentials, &verification, conn))
+        })
+        .await?,
+        CLIENT_CREDENTIALS => with_connection(&pool, move |conn| {
+            Ok(client_credentials_grant(request, basic_credentials, &config, conn))
+        })
+        .await?,
+        REFRESH_TOKEN => {
+            let outcome = with_connection(&pool, move |conn| {
+                Ok(refresh_token_grant(request, basic_credentials, &verification, conn))
+            })
+            .await??;
+
+            Ok(auth_service::finish_refresh(outcome, mailer)?)
+        }
+        _ => Err(OAuthError::UnsupportedGrantType),
+    }
+}
+
+fn authorization_code_grant(
+    request: TokenRequest,
+    basic_credentials: Option<(String, String)>,
+    verification: &EmailVerificationConfig,
+    conn: &mut PgConnection,
+) -> Result<TokenResponse, OAuthError> {
+    let client = authenticate_client(
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        basic_credentials,
+        conn,
+    )?;
+    require_grant_type(&client, AUTHORIZATION_CODE)?;
+
+    let code = request
+        .code
+        .ok_or_else(|| OAuthError::InvalidRequest("Missing authorization code".to_string()))?;
+    let code_verifier = request
+        .code_verifier
+        .ok_or_else(|| OAuthError::InvalidRequest("Missing code verifier".to_string()))?;
+
+    let authorization = oauth_repository::take_authorization_code(&hash_token(&code), conn)?
+        .filter(|authorization| authorization.client_id == client.id)
+        .ok_or_else(|| OAuthError::InvalidGrant("Invalid or expired authorization code".to_string()))?;
+
+    if request.redirect_uri.as_deref() != Some(authorization.redirect_uri.as_str()) {
+        return Err(OAuthError::InvalidGrant("Redirect URI mismatch".to_string()));
+    }
+    if !(43..=128).contains(&code_verifier.len()) || pkce_challenge(&code_verifier) != authorization.code_challenge {
+        return Err(OAuthError::InvalidGrant("Invalid code verifier".to_string()));
+    }
+
+    let user = user_repository::find_user_by_id(authorization.user_id, conn)
+        .map_err(|_| OAuthError::InvalidGrant("Invalid or expired authorization code".to_string()))?;
+    auth_service::check_login_allowed(&user, verification)?;
+
+    // Client grants are managed through consents and aren't listed as sessions,
+    // so their refresh tokens carry no device details.
+    let scopes = split_scope(&authorization.scope);
+    let refresh_token = if client.grant_types.iter().any(|grant_type| grant_type == REFRESH_TOKEN) {
+        Some(auth_service::issue_refresh_token(
+            user.id,
+            Uuid::new_v4(),
+            None,
+            Some(client.id),
+            Some(authorization.scope.clone()),
+            &ClientInfo::default(),
+            conn,
+        )?)
+    } else {
+        None
+    };
+
+    tracing::info!("Issued tokens to OAuth client {} for user: {}", client.client_id, user.id);
+
+    Ok(user_token_response(&user, &client, scopes, refresh_token, conn)?)
+}
+
+fn refresh_token_grant(
+    request: TokenRequest,
+    basic_credentials: Option<(String, String)>,
+    verification: &EmailVerificationConfig,
+    conn: &mut PgConnection,
+) -> Result<RefreshOutcome<TokenResponse>, OAuthError> {
+    let client = authenticate_client(
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        basic_credentials,
+        conn,
+    )?;
+    require_grant_type(&client, REFRESH_TOKEN)?;
+
+    let refresh_token = request
+        .refresh_token
+        .ok_or_else(|| OAuthError::InvalidRequest("Missing refresh token".to_string()))?;
+
+    // A refresh may narrow the original grant but never widen it. The scope
+    // is checked before rotating so a bad request doesn't burn the token.
+    let granted = auth_repository::find_refresh_token(&hash_token(&refresh_token), conn)
+        .ok()
+        .filter(|stored| stored.client_id == Some(client.id))
+        .and_then(|stored| stored.scope)
+        .map(|scope| split_scope(&scope))
+        .ok_or_else(|| OAuthError::InvalidGrant("Invalid refresh token".to_string()))?;
+
+    let scopes = match request.scope.as_deref() {
+        Some(requested) => {
+            let requested = dedup(split_scope(requested));
+            if let Some(scope) = requested.iter().find(|scope| !granted.contains(scope)) {
+                return Err(OAuthError::InvalidScope(format!("Scope {} was not granted", scope)));
+            }
+            requested
+        }
+        None => granted.clone(),
+    };
+
+    Ok(auth_service::rotate_refresh_token(
+        &refresh_token,
+        Some(client.id),
+        &ClientInfo::default(),
+        conn,
+        |user, stored_token, conn| {
+            auth_service::check_login_allowed(&user, verification)?;
+
+            // The new refresh token keeps the full original grant.
+            let refresh_token = auth_service::issue_refresh_token(
+                user.id,
+                stored_token.family_id,
+                Some(&stored_token),
+                Some(client.id),
+                stored_token.scope.clone(),
+                &ClientInfo::default(),
+                conn,
+            )?;
+
+            user_token_response(&user, &client, scopes, Some(refresh_token), conn)
+        },
+    )?)
+}
+
+// Client credentials tokens act for the client itself, so their subject is
+// the client's id rather than a user.
+fn client_credentials_grant(
+    request: TokenRequest,
+    basic_credentials: Option<(String, String)>,
+    config: &OAuthConfig,
+    conn: &mut PgConnection,
+) -> Result<TokenResponse, OAuthError> {
+    let client = authenticate_client(
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        basic_credentials,
+        conn,
+    )?;
+    require_grant_type(&client, CLIENT_CREDENTIALS)?;
+    if client.client_secret_hash.is_none() {
+        return Err(OAuthError::UnauthorizedClient("Public clients can't use this grant".to_string()));
+    }
+
+    let scopes = resolve_scopes(request.scope.as_deref(), &client, config).map_err(OAuthError::InvalidScope)?;
+
+    let mut custom_claims = serde_json::Map::new();
+    custom_claims.insert("client_id".to_string(), Value::String(client.client_id.clone()));
+
+    let access_token = generate_access_token_with(
+        client.id,
+        AccessTokenOptions {
+            scope: Some(scopes.join(" ")),
+            custom_claims,
+        },
+    );
+
+    Ok(TokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: ACCESS_TOKEN_TTL_MINUTES * 60,
+        refresh_token: None,
+        scope: scopes.join(" "),
+    })
+}
+
+// Only confidential clients (resource servers, gateways) may introspect.
+// Unknown, expired and revoked tokens all come back as `active: false`.
+pub async fn introspect(
+    request: IntrospectionRequest,
+    basic_credentials: Option<(String, String)>,
+    pool: DbPool,
+) -> Result<IntrospectionResponse, OAuthError> {
+    with_connection(&pool, move |conn| Ok(introspect_token(request, basic_credentials, conn))).await?
+}
+
+// Per RFC 7009 the response is the same whether or not the token was valid or
+// belonged to the calling client; only tokens issued to that client are
+// actually revoked.
+pub async fn revoke(
+    request: RevocationRequest,
+    basic_credentials: Option<(String, String)>,
+    pool: DbPool,
+) -> Result<(), OAuthError> {
+    with_connection(&pool, move |conn| Ok(revoke_token(request, basic_credentials, conn))).await?
+}
+
+fn introspect_token(
+    request: IntrospectionRequest,
+    basic_credentials: Option<(String, String)>,
+    conn: &mut PgConnection,
+) -> Result<IntrospectionResponse, OAuthError> {
+    let client = authenticate_client(
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        basic_credentials,
+        conn,
+    )?;
+    if client.client_secret_hash.is_none() {
+        return Err(OAuthError::UnauthorizedClient("Public clients can't introspect tokens".to_string()));
+    }
+    if request.token.is_empty() {
+        return Err(OAuthError::InvalidRequest("Missing token".to_string()));
+    }
+
+    let response = if request.token_type_hint.as_deref() == Some(REFRESH_TOKEN_TYPE) {
+        introspect_refresh_token(&request.token, &client, conn)
+            .or_else(|| introspect_access_token(&request.token, conn))
+    } else {
+        introspect_access_token(&request.token, conn)
+            .or_else(|| introspect_refresh_token(&request.token, &client, conn))
+    };
+
+    Ok(response.unwrap_or_default())
+}
+
+fn introspect_access_token(token: &str, conn: &mut PgConnection) -> Option<IntrospectionResponse> {
+    let claims = validate_access_token(token).ok()?;
+    if auth_service::is_access_token_revoked(&claims.jti, claims.sub, claims.iat, claims.session_id(), conn)
+        .unwrap_or(true)
+    {
+        return None;
+    }
+
+    Some(IntrospectionResponse {
+        active: true,
+        scope: claims.scope,
+        client_id: claims.custom.get("client_id").and_then(Value::as_str).map(str::to_string),
+        sub: Some(claims.sub),
+        token_type: Some(ACCESS_TOKEN_TYPE.to_string()),
+        exp: Some(claims.exp),
+        iat: Some(claims.iat),
+        iss: Some(claims.iss),
+        aud: Some(claims.aud),
+        jti: Some(claims.jti),
+    })
+}
+
+// Refresh tokens are only described to the client they were issued to.
+fn introspect_refresh_token(token: &str, client: &OAuthClient, conn: &mut PgConnection) -> Option<IntrospectionResponse> {
+    let claims = validate_refresh_token(token).ok()?;
+    let stored = auth_repository::find_refresh_token(&hash_token(token), conn).ok()?;
+
+    if stored.user_id != claims.sub
+        || stored.revoked_at.is_some()
+        || stored.rotated_at.is_some()
+        || !issued_to(stored.client_id, client)
+    {
+        return None;
+    }
+
+    Some(IntrospectionResponse {
+        active: true,
+        scope: stored.scope,
+        client_id: stored.client_id.map(|_| client.client_id.clone()),
+        sub: Some(claims.sub),
+        token_type: Some(REFRESH_TOKEN_TYPE.to_string()),
+        exp: Some(claims.exp),
+        iat: Some(claims.iat),
+        ..Default::default()
+    })
+}
+
+fn revoke_token(
+    request: RevocationRequest,
+    basic_credentials: Option<(String, String)>,
+    conn: &mut PgConnection,
+) -> Result<(), OAuthError> {
+    let client = authenticate_client(
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        basic_credentials,
+        conn,
+    )?;
+    if request.token.is_empty() {
+        return Err(OAuthError::InvalidRequest("Missing token".to_string()));
+    }
+
+    if request.token_type_hint.as_deref() == Some(ACCESS_TOKEN_TYPE) {
+        if !revoke_access_token(&request.token, &client, conn)? {
+            revoke_refresh_token(&request.token, &client, conn)?;
+        }
+    } else if !revoke_refresh_token(&request.token, &client, conn)? {
+        revoke_access_token(&request.token, &client, conn)?;
+    }
+
+    Ok(())
+}
+
+// Revoking a refresh token ends its whole rotation family.
+fn revoke_refresh_token(token: &str, client: &OAuthClient, conn: &mut PgConnection) -> Result<bool, OAuthError> {
+    let Ok(claims) = validate_refresh_token(token) else {
+        return Ok(false);
+    };
+    let Ok(stored) = auth_repository::find_refresh_token(&hash_token(token), conn) else {
+        return Ok(false);
+    };
+    if stored.user_id != claims.sub || !issued_to(stored.client_id, client) {
+        return Ok(false);
+    }
+
+    auth_repository::revoke_refresh_token_family(stored.family_id, conn)?;
+
+    tracing::info!("OAuth client {} revoked a refresh token of user: {}", client.client_id, stored.user_id);
+
+    Ok(true)
+}
+
+fn revoke_access_token(token: &str, client: &OAuthClient, conn: &mut PgConnection) -> Result<bool, OAuthError> {
+    let Ok(claims) = validate_access_token(token) else {
+        return Ok(false);
+    };
+
+    let allowed = match claims.custom.get("client_id").and_then(Value::as_str) {
+        Some(token_client) => token_client == client.client_id,
+        None => client.is_first_party,
+    };
+    if !allowed {
+        return Ok(false);
+    }
+
+    let expiry = DateTime::from_timestamp(claims.exp, 0)
+        .map(|dt| dt.naive_utc())
+        .unwrap_or_else(|| Utc::now().naive_utc());
+
+    auth_repository::revoke_token(
+        &NewRevokedToken {
+            token_jti: claims.jti,
+            // Client credentials tokens have the client, not a user, as subject.
+            user_id: (claims.sub != client.id).then_some(claims.sub),
+            expiry,
+        },
+        conn,
+    )?;
+
+    tracing::info!("OAuth client {} revoked access token for subject: {}", client.client_id, claims.sub);
+
+    Ok(true)
+}
+
+// First-party session tokens have no client; trusted first-party clients may
+// act on them.
+fn issued_to(token_client: Option<Uuid>, client: &OAuthClient) -> bool {
+    match token_client {
+        Some(token_client) => token_client == client.id,
+        None => client.is_first_party,
+    }
+}
+
+// Confidential clients authenticate with HTTP Basic or with `client_secret`
+// in the form; public clients only identify themselves with `client_id`.
+fn authenticate_client(
+    client_id: Option<&str>,
+    client_secret: Option<&str>,
+    basic_credentials: Option<(String, String)>,
+    conn: &mut PgConnection,
+) -> Result<OAuthClient, OAuthError> {
+    let (client_id, client_secret) = match (basic_credentials, client_id, client_secret) {
+        (Some((id, secret)), form_id, None) if form_id.is_none_or(|form_id| form_id == id) => (id, Some(secret)),
+        (Some(_), _, _) => {
+            return Err(OAuthError::InvalidRequest(
+                "Only one client authentication method may be used".to_string(),
+            ))
+        }
+        (None, Some(id), secret) => (id.to_string(), secret.map(str::to_string)),
+        (None, None, _) => return Err(OAuthError::InvalidClient),
+    };
+
+    let client = oauth_repository::find_client_by_client_id(&client_id, conn)?.ok_or(OAuthError::InvalidClient)?;
+
+    let authenticated = match (&client.client_secret_hash, client_secret) {
+        (Some(secret_hash), Some(secret)) => constant_time_eq(secret_hash.as_bytes(), hash_token(&secret).as_bytes()),
+        (None, None) => true,
+        _ => false,
+    };
+    if !authenticated {
+        tracing::warn!(target: "security", client_id = %client.client_id, "OAuth client authentication failed");
+        return Err(OAuthError::InvalidClient);
+    }
+
+    Ok(client)
+}
+
+fn require_grant_type(client: &OAuthClient, grant_type: &str) -> Result<(), OAuthError> {
+    if !client.grant_types.iter().any(|allowed| allowed == grant_type) {
+        return Err(OAuthError::UnauthorizedClient(format!(
+            "The client is not allowed to use the {} grant",
+            grant_type
+        )));
+    }
+    Ok(())
+}
+
+// Problems with the client or redirect URI are reported to the user, since the
+// redirect target can't be trusted; everything else goes back to the client.
+fn validate_authorization(
+    request: AuthorizeRequest,
+    config: &OAuthConfig,
+    conn: &mut PgConnection,
+) -> ApiResult<Result<ValidatedAuthorization, AuthorizeResponse>> {
+    let client = oauth_repository::find_client_by_client_id(&request.client_id, conn)?
+        .ok_or_else(|| ApiError::BadRequest("Unknown OAuth client".to_string()))?;
+
+    let redirect_uri = match &request.redirect_uri {
+        Some(uri) if client.redirect_uris.contains(uri) => uri.clone(),
+        None if client.redirect_uris.len() == 1 => client.redirect_uris[0].clone(),
+        _ => return Err(ApiError::BadRequest("Invalid redirect URI".to_string())),
+    };
+
+    let state = request.state;
+    let reject = |error: &str, description: &str| error_redirect(&redirect_uri, error, description, state.as_deref()).map(Err);
+
+    if request.response_type != "code" {
+        return reject("unsupported_response_type", "Only the code response type is supported");
+    }
+    if !client.grant_types.iter().any(|grant_type| grant_type == AUTHORIZATION_CODE) {
+        return reject("unauthorized_client", "The client is not allowed to use the authorization code grant");
+    }
+
+    let code_challenge = match (request.code_challenge, request.code_challenge_method.as_deref()) {
+        (Some(challenge), Some("S256")) if !challenge.is_empty() => challenge,
+        _ => return reject("invalid_request", "PKCE with the S256 method is required"),
+    };
+
+    let scopes = match resolve_scopes(request.scope.as_deref(), &client, config) {
+        Ok(scopes) => scopes,
+        Err(description) => return reject("invalid_scope", &description),
+    };
+
+    Ok(Ok(ValidatedAuthorization {
+        client,
+        redirect_uri,
+        scopes,
+        code_challenge,
+        state,
+    }))
+}
+
+fn issue_authorization_code(
+    user_id: Uuid,
+    authorization: ValidatedAuthorization,
+    config: &OAuthConfig,
+    conn: &mut PgConnection,
+) -> ApiResult<AuthorizeResponse> {
+    let code = random_value(32);
+
+    oauth_repository::create_authorization_code(
+        &NewOAuthAuthorizationCode {
+            code_hash: hash_token(&code),
+            client_id: authorization.client.id,
+            user_id,
+            redirect_uri: authorization.redirect_uri.clone(),
+            scope: authorization.scopes.join(" "),
+            code_challenge: authorization.code_challenge,
+            expires_at: (Utc::now() + config.code_ttl).naive_utc(),
+        },
+        conn,
+    )?;
+
+    let mut params = vec![("code", code.as_str())];
+    params.extend(authorization.state.as_deref().map(|state| ("state", state)));
+
+    redirect_to(&authorization.redirect_uri, &params)
+}
+
+fn error_redirect(
+    redirect_uri: &str,
+    error: &str,
+    description: &str,
+    state: Option<&str>,
+) -> ApiResult<AuthorizeResponse> {
+    let mut params = vec![("error", error), ("error_description", description)];
+    params.extend(state.map(|state| ("state", state)));
+
+    redirect_to(redirect_uri, &params)
+}
+
+fn redirect_to(redirect_uri: &str, params: &[(&str, &str)]) -> ApiResult<AuthorizeResponse> {
+    let mut url = Url::parse(redirect_uri)
+        .map_err(|e| ApiError::InternalServerError(format!("Invalid stored redirect URI: {}", e)))?;
+    url.query_pairs_mut().extend_pairs(params);
+
+    Ok(AuthorizeResponse::Redirect(AuthorizationRedirect {
+        redirect_to: url.to_string(),
+    }))
+}
+
+// Without an explicit scope the client gets everything it registered for.
+fn resolve_scopes(requested: Option<&str>, client: &OAuthClient, config: &OAuthConfig) -> Result<Vec<String>, String> {
+    let scopes = match requested.map(split_scope).filter(|scopes| !scopes.is_empty()) {
+        Some(scopes) => dedup(scopes),
+        None => client.scopes.clone(),
+    };
+
+    match scopes
+        .iter()
+        .find(|scope| !client.scopes.contains(scope) || !config.is_supported_scope(scope))
+    {
+        Some(scope) => Err(format!("Scope {} is not available to this client", scope)),
+        None => Ok(scopes),
+    }
+}
+
+fn user_token_response(
+    user: &User,
+    client: &OAuthClient,
+    scopes: Vec<String>,
+    refresh_token: Option<String>,
+    conn: &mut PgConnection,
+) -> ApiResult<TokenResponse> {
+    let mut options = auth_service::access_token_options(user, conn)?;
+    options.scope = Some(scopes.join(" "));
+    options
+        .custom_claims
+        .insert("client_id".to_string(), Value::String(client.client_id.clone()));
+
+    Ok(TokenResponse {
+        access_token: generate_access_token_with(user.id, options),
+        token_type: "Bearer".to_string(),
+        expires_in: ACCESS_TOKEN_TTL_MINUTES * 60,
+        refresh_token,
+        scope: scopes.join(" "),
+    })
+}
+
+fn split_scope(scope: &str) -> Vec<String> {
+    scope.split_whitespace().map(str::to_string).collect()
+}
+
+fn dedup(values: Vec<String>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::with_capacity(values.len());
+    for value in values {
+        if !unique.contains(&value) {
+            unique.push(value);
+        }
+    }
+    unique
+}
+
+fn client_to_response(client: OAuthClient) -> ClientResponse {
+    ClientResponse {
+        id: client.id,
+        client_id: client.client_id,
+        name: client.name,
+        redirect_uris: client.redirect_uris,
+        scopes: client.scopes,
+        grant_types: client.grant_types,
+        confidential: client.client_secret_hash.is_some(),
+        first_party: client.is_first_party,
+        created_at: client.created_at,
+    }
+}
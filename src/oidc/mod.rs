@@ -0,0 +1,5 @@
+pub mod oidc_handler;
+pub mod oidc_service;
+pub mod oidc_repository;
+pub mod oidc_dto;
+pub mod oidc_client;
@@ -0,0 +1,222 @@
+use crate::auth::auth_hashing::pkce_challenge;
+use crate::config::oidc::OidcProviderConfig;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use once_cell::sync::Lazy;
+use reqwest::Url;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("Failed to build HTTP client")
+});
+
+static METADATA_CACHE: Lazy<Mutex<HashMap<String, ProviderMetadata>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug)]
+pub enum OidcError {
+    Http(String),
+    InvalidResponse(String),
+    InvalidIdToken(String),
+}
+
+impl fmt::Display for OidcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OidcError::Http(msg) => write!(f, "OIDC request failed: {}", msg),
+            OidcError::InvalidResponse(msg) => write!(f, "Invalid OIDC response: {}", msg),
+            OidcError::InvalidIdToken(msg) => write!(f, "Invalid ID token: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for OidcError {}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_email_verified")]
+    pub email_verified: bool,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+// Discovery documents are cached per issuer for the life of the process.
+pub async fn discover(provider: &OidcProviderConfig) -> Result<ProviderMetadata, OidcError> {
+    if let Some(metadata) = METADATA_CACHE.lock().unwrap().get(&provider.issuer) {
+        return Ok(metadata.clone());
+    }
+
+    let url = format!("{}/.well-known/openid-configuration", provider.issuer);
+    let metadata: ProviderMetadata = get_json(&url).await?;
+
+    if metadata.issuer.trim_end_matches('/') != provider.issuer {
+        return Err(OidcError::InvalidResponse(format!(
+            "discovery document is for issuer {}",
+            metadata.issuer
+        )));
+    }
+
+    METADATA_CACHE
+        .lock()
+        .unwrap()
+        .insert(provider.issuer.clone(), metadata.clone());
+
+    Ok(metadata)
+}
+
+pub fn authorization_url(
+    metadata: &ProviderMetadata,
+    provider: &OidcProviderConfig,
+    state: &str,
+    nonce: &str,
+    code_verifier: &str,
+) -> Result<String, OidcError> {
+    let url = Url::parse_with_params(
+        &metadata.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", provider.client_id.as_str()),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("scope", provider.scopes.join(" ").as_str()),
+            ("state", state),
+            ("nonce", nonce),
+            ("code_challenge", pkce_challenge(code_verifier).as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|e| OidcError::InvalidResponse(format!("invalid authorization endpoint: {}", e)))?;
+
+    Ok(url.to_string())
+}
+
+pub async fn exchange_code(
+    metadata: &ProviderMetadata,
+    provider: &OidcProviderConfig,
+    code: &str,
+    code_verifier: &str,
+) -> Result<String, OidcError> {
+    let response = HTTP_CLIENT
+        .post(&metadata.token_endpoint)
+        .basic_auth(&provider.client_id, Some(&provider.client_secret))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("code_verifier", code_verifier),
+        ])
+        .send()
+        .await
+        .map_err(|e| OidcError::Http(e.to_string()))?;
+
+    if !response.status().is_success() {
+        return Err(OidcError::InvalidResponse(format!(
+            "token endpoint returned {}",
+            response.status()
+        )));
+    }
+
+    let tokens: TokenResponse = response
+        .json()
+        .await
+        .map_err(|e| OidcError::InvalidResponse(e.to_string()))?;
+
+    tokens
+        .id_token
+        .ok_or_else(|| OidcError::InvalidResponse("token response has no id_token".to_string()))
+}
+
+pub async fn verify_id_token(
+    metadata: &ProviderMetadata,
+    provider: &OidcProviderConfig,
+    id_token: &str,
+    expected_nonce: &str,
+) -> Result<IdTokenClaims, OidcError> {
+    let header = decode_header(id_token).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+    // Provider keys are asymmetric; accepting HMAC here would let the client
+    // secret sign tokens.
+    if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+        return Err(OidcError::InvalidIdToken("symmetric algorithms are not accepted".to_string()));
+    }
+
+    let jwks: JwkSet = get_json(&metadata.jwks_uri).await?;
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| OidcError::InvalidIdToken("no matching signing key".to_string()))?;
+
+    let key = DecodingKey::from_jwk(jwk).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[metadata.issuer.as_str()]);
+    validation.set_audience(&[provider.client_id.as_str()]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+        .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?
+        .claims;
+
+    if claims.nonce.as_deref() != Some(expected_nonce) {
+        return Err(OidcError::InvalidIdToken("nonce mismatch".to_string()));
+    }
+
+    Ok(claims)
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(url: &str) -> Result<T, OidcError> {
+    let response = HTTP_CLIENT
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| OidcError::Http(e.to_string()))?;
+
+    if !response.status().is_success() {
+        return Err(OidcError::InvalidResponse(format!("{} returned {}", url, response.status())));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| OidcError::InvalidResponse(e.to_string()))
+}
+
+// Some providers send `email_verified` as the string "true".
+fn deserialize_email_verified<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Text(String),
+    }
+
+    Ok(match Option::<Flag>::deserialize(deserializer)? {
+        Some(Flag::Bool(value)) => value,
+        Some(Flag::Text(value)) => value.eq_ignore_ascii_case("true"),
+        None => false,
+    })
+}
@@ -0,0 +1,28 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OidcCallbackRequest {
+    #[validate(length(min = 1, message = "Authorization code is required"))]
+    pub code: String,
+
+    #[validate(length(min = 1, message = "State is required"))]
+    pub state: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthorizationUrlResponse {
+    pub authorization_url: String,
+    pub state: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IdentityResponse {
+    pub id: Uuid,
+    pub provider: String,
+    pub email: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub last_login_at: Option<NaiveDateTime>,
+}
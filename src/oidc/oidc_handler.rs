@@ -0,0 +1,78 @@
+use crate::{
+    app::AppState,
+    auth::{auth_dto::LoginResponse, auth_cookies, auth_middleware::{AuthUser, ClientInfo}},
+    errors::ApiResult,
+    oidc::{
+        oidc_dto::{AuthorizationUrlResponse, IdentityResponse, OidcCallbackRequest},
+        oidc_service,
+    },
+};
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use serde_json::{json, Value};
+use tower_cookies::Cookies;
+use validator::Validate;
+
+pub async fn login_authorization_handler(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+) -> ApiResult<Json<AuthorizationUrlResponse>> {
+    let response = oidc_service::authorization_url(&provider, None, state.pool, &state.oidc).await?;
+    Ok(Json(response))
+}
+
+pub async fn login_callback_handler(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    client: ClientInfo,
+    cookies: Cookies,
+    Json(payload): Json<OidcCallbackRequest>,
+) -> ApiResult<Json<LoginResponse>> {
+    payload.validate()?;
+    let login_response =
+        oidc_service::login(&provider, payload, client, state.pool, &state.oidc, &state.email_verification).await?;
+    Ok(Json(auth_cookies::store_login(&cookies, &state.auth_cookies, login_response)))
+}
+
+pub async fn list_identities_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+) -> ApiResult<Json<Vec<IdentityResponse>>> {
+    let identities = oidc_service::list_identities(authenticated_user.id, state.pool).await?;
+    Ok(Json(identities))
+}
+
+pub async fn link_authorization_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Path(provider): Path<String>,
+) -> ApiResult<Json<AuthorizationUrlResponse>> {
+    let response =
+        oidc_service::authorization_url(&provider, Some(authenticated_user.id), state.pool, &state.oidc).await?;
+    Ok(Json(response))
+}
+
+pub async fn link_identity_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Path(provider): Path<String>,
+    Json(payload): Json<OidcCallbackRequest>,
+) -> ApiResult<Json<IdentityResponse>> {
+    payload.validate()?;
+    let identity =
+        oidc_service::link_identity(authenticated_user.id, &provider, payload, state.pool, &state.oidc).await?;
+    Ok(Json(identity))
+}
+
+pub async fn unlink_identity_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Path(provider): Path<String>,
+) -> ApiResult<Json<Value>> {
+    oidc_service::unlink_identity(authenticated_user.id, provider, state.pool).await?;
+    Ok(Json(json!({
+        "message": "Identity unlinked"
+    })))
+}
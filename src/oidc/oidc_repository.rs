@@ -0,0 +1,91 @@
+use crate::db::models::oidc_login_state::{NewOidcLoginState, OidcLoginState};
+use crate::db::models::user_identity::{NewUserIdentity, UserIdentity};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub fn create_login_state(new_state: &NewOidcLoginState, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::oidc_login_states::dsl::*;
+
+    diesel::insert_into(oidc_login_states)
+        .values(new_state)
+        .execute(conn)
+}
+
+// Deletes the state while reading it, so an authorization response can only
+// be redeemed once.
+pub fn take_login_state(
+    state_value: &str,
+    provider_value: &str,
+    conn: &mut PgConnection,
+) -> QueryResult<Option<OidcLoginState>> {
+    use crate::schema::oidc_login_states::dsl::*;
+
+    diesel::delete(
+        oidc_login_states
+            .filter(state.eq(state_value))
+            .filter(provider.eq(provider_value))
+            .filter(expires_at.gt(chrono::Utc::now().naive_utc())),
+    )
+    .returning(OidcLoginState::as_returning())
+    .get_result(conn)
+    .optional()
+}
+
+pub fn clean_expired_login_states(conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::oidc_login_states::dsl::*;
+
+    diesel::delete(oidc_login_states.filter(expires_at.lt(chrono::Utc::now().naive_utc())))
+        .execute(conn)
+}
+
+pub fn find_identity(provider_value: &str, subject_value: &str, conn: &mut PgConnection) -> QueryResult<Option<UserIdentity>> {
+    use crate::schema::user_identities::dsl::*;
+
+    user_identities
+        .filter(provider.eq(provider_value))
+        .filter(subject.eq(subject_value))
+        .select(UserIdentity::as_select())
+        .first(conn)
+        .optional()
+}
+
+pub fn create_identity(new_identity: &NewUserIdentity, conn: &mut PgConnection) -> QueryResult<UserIdentity> {
+    use crate::schema::user_identities::dsl::*;
+
+    diesel::insert_into(user_identities)
+        .values(new_identity)
+        .returning(UserIdentity::as_returning())
+        .get_result(conn)
+}
+
+pub fn list_identities(user_id_val: Uuid, conn: &mut PgConnection) -> QueryResult<Vec<UserIdentity>> {
+    use crate::schema::user_identities::dsl::*;
+
+    user_identities
+        .filter(user_id.eq(user_id_val))
+        .order(created_at.asc())
+        .select(UserIdentity::as_select())
+        .load(conn)
+}
+
+pub fn delete_identity(user_id_val: Uuid, provider_value: &str, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::user_identities::dsl::*;
+
+    diesel::delete(
+        user_identities
+            .filter(user_id.eq(user_id_val))
+            .filter(provider.eq(provider_value)),
+    )
+    .execute(conn)
+}
+
+pub fn record_identity_login(identity_id: Uuid, email_value: Option<&str>, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::user_identities::dsl::*;
+
+    diesel::update(user_identities.filter(id.eq(identity_id)))
+        .set((
+            email.eq(email_value),
+            last_login_at.eq(Some(chrono::Utc::now().naive_utc())),
+        ))
+        .execute(conn)
+}
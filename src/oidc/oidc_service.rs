@@ -0,0 +1,361 @@
+use crate::{
+    audit::audit_service::AuditLog,
+    auth::{auth_dto::LoginResponse, auth_hashing::{hash_password_async, random_value}, auth_middleware::ClientInfo, auth_service},
+    config::{
+        database::DbPool,
+        email_verification::EmailVerificationConfig,
+        oidc::{OidcConfig, OidcProviderConfig},
+    },
+    db::{
+        models::{
+            oidc_login_state::NewOidcLoginState,
+            user::{NewUser, User},
+            user_identity::{NewUserIdentity, UserIdentity},
+        },
+        with_connection,
+    },
+    errors::{ApiError, ApiResult},
+    oidc::{
+        oidc_client::{self, IdTokenClaims, OidcError},
+        oidc_dto::{AuthorizationUrlResponse, IdentityResponse, OidcCallbackRequest},
+        oidc_repository,
+    },
+    user::user_repository,
+};
+use chrono::Utc;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::{Connection, PgConnection};
+use uuid::Uuid;
+
+// `user_id` is set when the flow links the provider to an existing session
+// rather than logging in.
+pub async fn authorization_url(
+    provider_name: &str,
+    user_id: Option<Uuid>,
+    pool: DbPool,
+    config: &OidcConfig,
+) -> ApiResult<AuthorizationUrlResponse> {
+    let provider = find_provider(provider_name, config)?;
+    let metadata = oidc_client::discover(&provider).await.map_err(provider_error)?;
+
+    let state = random_value(32);
+    let nonce = random_value(32);
+    let code_verifier = random_value(32);
+    let authorization_url =
+        oidc_client::authorization_url(&metadata, &provider, &state, &nonce, &code_verifier).map_err(provider_error)?;
+
+    let new_state = NewOidcLoginState {
+        state: state.clone(),
+        provider: provider.name.clone(),
+        code_verifier,
+        nonce,
+        user_id,
+        expires_at: (Utc::now() + config.state_ttl).naive_utc(),
+    };
+
+    with_connection(&pool, move |conn| {
+        oidc_repository::create_login_state(&new_state, conn)?;
+        Ok(())
+    })
+    .await?;
+
+    Ok(AuthorizationUrlResponse {
+        authorization_url,
+        state,
+    })
+}
+
+pub async fn login(
+    provider_name: &str,
+    request: OidcCallbackRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    config: &OidcConfig,
+    verification: &EmailVerificationConfig,
+) -> ApiResult<LoginResponse> {
+    let audit = AuditLog::new(&pool, &client);
+    let result = provider_login(provider_name, request, client, pool, config, verification).await;
+
+    audit
+        .record(
+            auth_service::login_entry(&result)
+                .with("method", "oidc")
+                .with("provider", provider_name),
+        )
+        .await;
+
+    result
+}
+
+async fn provider_login(
+    provider_name: &str,
+    request: OidcCallbackRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    config: &OidcConfig,
+    verification: &EmailVerificationConfig,
+) -> ApiResult<LoginResponse> {
+    let provider = find_provider(provider_name, config)?;
+    let claims = authenticate(&provider, request, None, pool.clone()).await?;
+
+    let resolve_provider = provider.name.clone();
+    let subject = claims.sub.clone();
+    let email = verified_email(&claims);
+    let resolved = with_connection(&pool, move |conn| {
+        resolve_account(&resolve_provider, &subject, email.as_deref(), conn)
+    })
+    .await?;
+
+    let user = match resolved {
+        Some(user) => user,
+        None => {
+            // Accounts created through a provider get an unusable random
+            // password; a password can be set later through a reset.
+            let password_hash = hash_password_async(random_value(32))
+                .await
+                .map_err(|e| ApiError::InternalServerError(format!("Password hashing failed: {}", e)))?;
+            let provider_name = provider.name.clone();
+
+            with_connection(&pool, move |conn| create_account(&provider_name, &claims, password_hash, conn)).await?
+        }
+    };
+
+    let verification = verification.clone();
+    with_connection(&pool, move |conn| {
+        auth_service::check_login_allowed(&user, &verification)?;
+        auth_service::complete_login(user, &client, conn)
+    })
+    .await
+}
+
+pub async fn link_identity(
+    user_id: Uuid,
+    provider_name: &str,
+    request: OidcCallbackRequest,
+    pool: DbPool,
+    config: &OidcConfig,
+) -> ApiResult<IdentityResponse> {
+    let provider = find_provider(provider_name, config)?;
+    let claims = authenticate(&provider, request, Some(user_id), pool.clone()).await?;
+
+    with_connection(&pool, move |conn| {
+        if let Some(identity) = oidc_repository::find_identity(&provider.name, &claims.sub, conn)? {
+            if identity.user_id != user_id {
+                return Err(ApiError::ResourceAlreadyExists(
+                    "This account is already linked to another user".to_string(),
+                ));
+            }
+            return Ok(identity_to_response(identity));
+        }
+
+        let identity = create_identity(user_id, &provider.name, &claims, conn)?;
+
+        tracing::info!("Linked {} identity to user: {}", provider.name, user_id);
+
+        Ok(identity_to_response(identity))
+    })
+    .await
+}
+
+pub async fn list_identities(user_id: Uuid, pool: DbPool) -> ApiResult<Vec<IdentityResponse>> {
+    with_connection(&pool, move |conn| {
+        let identities = oidc_repository::list_identities(user_id, conn)?;
+        Ok(identities.into_iter().map(identity_to_response).collect())
+    })
+    .await
+}
+
+pub async fn unlink_identity(user_id: Uuid, provider_name: String, pool: DbPool) -> ApiResult<()> {
+    with_connection(&pool, move |conn| {
+        if oidc_repository::delete_identity(user_id, &provider_name, conn)? == 0 {
+            return Err(ApiError::NotFound("Linked identity not found".to_string()));
+        }
+
+        tracing::info!("Unlinked {} identity from user: {}", provider_name, user_id);
+
+        Ok(())
+    })
+    .await
+}
+
+async fn authenticate(
+    provider: &OidcProviderConfig,
+    request: OidcCallbackRequest,
+    expected_user: Option<Uuid>,
+    pool: DbPool,
+) -> ApiResult<IdTokenClaims> {
+    let provider_name = provider.name.clone();
+    let login_state = with_connection(&pool, move |conn| {
+        oidc_repository::take_login_state(&request.state, &provider_name, conn)?
+            .ok_or_else(|| ApiError::BadRequest("Invalid or expired login state".to_string()))
+    })
+    .await?;
+
+    if login_state.user_id != expected_user {
+        return Err(ApiError::BadRequest("Invalid or expired login state".to_string()));
+    }
+
+    let metadata = oidc_client::discover(provider).await.map_err(provider_error)?;
+    let id_token = oidc_client::exchange_code(&metadata, provider, &request.code, &login_state.code_verifier)
+        .await
+        .map_err(provider_error)?;
+
+    oidc_client::verify_id_token(&metadata, provider, &id_token, &login_state.nonce)
+        .await
+        .map_err(provider_error)
+}
+
+// Known identities log straight in. Otherwise a verified email that matches an
+// existing account links the identity to it; unverified emails never link,
+// since anyone can claim an address at a provider that does not check it.
+// The local account must have verified the address too, or whoever registered
+// it first would take over the provider login. `None` means a new account
+// should be created.
+fn resolve_account(
+    provider: &str,
+    subject: &str,
+    verified_email: Option<&str>,
+    conn: &mut PgConnection,
+) -> ApiResult<Option<User>> {
+    if let Some(identity) = oidc_repository::find_identity(provider, subject, conn)? {
+        let user = user_repository::find_user_by_id(identity.user_id, conn)
+            .map_err(|_| ApiError::Forbidden("Account is deactivated".to_string()))?;
+        oidc_repository::record_identity_login(identity.id, verified_email.or(identity.email.as_deref()), conn)?;
+        return Ok(Some(user));
+    }
+
+    let Some(email) = verified_email else {
+        return Err(ApiError::BadRequest(
+            "The identity provider did not return a verified email address".to_string(),
+        ));
+    };
+
+    match user_repository::find_user_by_email(email, conn) {
+        Ok(user) if user.is_verified != Some(true) => Err(ApiError::ResourceAlreadyExists(
+            "An account with this email already exists; log in and link this provider from your account"
+                .to_string(),
+        )),
+        Ok(user) => {
+            let identity = oidc_repository::create_identity(
+                &NewUserIdentity {
+                    user_id: user.id,
+                    provider: provider.to_string(),
+                    subject: subject.to_string(),
+                    email: Some(email.to_string()),
+                },
+                conn,
+            )
+            .map_err(identity_conflict)?;
+            oidc_repository::record_identity_login(identity.id, Some(email), conn)?;
+
+            tracing::info!("Linked {} identity to user {} by verified email", provider, user.id);
+
+            Ok(Some(user))
+        }
+        Err(DieselError::NotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn create_account(
+    provider: &str,
+    claims: &IdTokenClaims,
+    password_hash: String,
+    conn: &mut PgConnection,
+) -> ApiResult<User> {
+    let email = verified_email(claims)
+        .ok_or_else(|| ApiError::BadRequest("The identity provider did not return a verified email address".to_string()))?;
+
+    let first_name = claims
+        .given_name
+        .clone()
+        .or_else(|| claims.name.clone())
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| email.split('@').next().unwrap_or_default().to_string());
+
+    let new_user = NewUser {
+        first_name,
+        last_name: claims.family_name.clone().unwrap_or_default(),
+        email,
+        password_hash,
+        is_active: Some(true),
+        is_verified: Some(true),
+        locale: None,
+    };
+
+    conn.transaction(|conn| {
+        let user = user_repository::create_user(&new_user, conn).map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                ApiError::ResourceAlreadyExists("Email already registered".to_string())
+            }
+            e => e.into(),
+        })?;
+        let identity = create_identity(user.id, provider, claims, conn)?;
+        oidc_repository::record_identity_login(identity.id, identity.email.as_deref(), conn)?;
+
+        tracing::info!("User registered through {}: {}", provider, user.email);
+
+        Ok(user)
+    })
+}
+
+fn create_identity(
+    user_id: Uuid,
+    provider: &str,
+    claims: &IdTokenClaims,
+    conn: &mut PgConnection,
+) -> ApiResult<UserIdentity> {
+    oidc_repository::create_identity(
+        &NewUserIdentity {
+            user_id,
+            provider: provider.to_string(),
+            subject: claims.sub.clone(),
+            email: claims.email.clone(),
+        },
+        conn,
+    )
+    .map_err(identity_conflict)
+}
+
+fn verified_email(claims: &IdTokenClaims) -> Option<String> {
+    claims
+        .email
+        .as_ref()
+        .filter(|_| claims.email_verified)
+        .map(|email| email.trim().to_string())
+}
+
+fn find_provider(name: &str, config: &OidcConfig) -> ApiResult<OidcProviderConfig> {
+    config
+        .provider(name)
+        .cloned()
+        .ok_or_else(|| ApiError::NotFound("Unknown identity provider".to_string()))
+}
+
+fn provider_error(error: OidcError) -> ApiError {
+    tracing::warn!("OIDC authentication failed: {}", error);
+
+    match error {
+        OidcError::InvalidIdToken(_) => ApiError::Unauthorized("Identity provider authentication failed".to_string()),
+        _ => ApiError::InternalServerError("Identity provider request failed".to_string()),
+    }
+}
+
+fn identity_conflict(error: DieselError) -> ApiError {
+    match error {
+        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+            ApiError::ResourceAlreadyExists("An account from this provider is already linked".to_string())
+        }
+        e => e.into(),
+    }
+}
+
+fn identity_to_response(identity: UserIdentity) -> IdentityResponse {
+    IdentityResponse {
+        id: identity.id,
+        provider: identity.provider,
+        email: identity.email,
+        created_at: identity.created_at,
+        last_login_at: identity.last_login_at,
+    }
+}
@@ -0,0 +1,5 @@
+pub mod organization_handler;
+pub mod organization_service;
+pub mod organization_repository;
+pub mod organization_dto;
+pub mod organization_middleware;
@@ -0,0 +1,97 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+static SLUG_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z0-9]+(-[a-z0-9]+)*$").unwrap());
+
+// Ordered from least to most privileged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrganizationRole {
+    Member,
+    Admin,
+    Owner,
+}
+
+impl OrganizationRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrganizationRole::Member => "member",
+            OrganizationRole::Admin => "admin",
+            OrganizationRole::Owner => "owner",
+        }
+    }
+
+    pub fn parse(role: &str) -> Option<Self> {
+        match role {
+            "member" => Some(OrganizationRole::Member),
+            "admin" => Some(OrganizationRole::Admin),
+            "owner" => Some(OrganizationRole::Owner),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateOrganizationRequest {
+    #[validate(length(min = 1, max = 255, message = "Name must be between 1 and 255 characters"))]
+    pub name: String,
+
+    #[validate(
+        length(min = 3, max = 64, message = "Slug must be between 3 and 64 characters"),
+        regex(path = *SLUG_PATTERN, message = "Slug may only contain lowercase letters, digits and single hyphens")
+    )]
+    pub slug: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMemberRequest {
+    pub role: OrganizationRole,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateInvitationRequest {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+
+    pub role: Option<OrganizationRole>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct InvitationTokenRequest {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrganizationResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub role: OrganizationRole,
+    pub active: bool,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberResponse {
+    pub user_id: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: OrganizationRole,
+    pub joined_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InvitationResponse {
+    pub id: Uuid,
+    pub email: String,
+    pub role: OrganizationRole,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: NaiveDateTime,
+    pub created_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,130 @@
+use crate::{
+    app::AppState,
+    auth::{auth_cookies, auth_dto::AuthResponse, auth_middleware::AuthUser},
+    errors::ApiResult,
+    organization::{
+        organization_dto::{
+            CreateInvitationRequest, CreateOrganizationRequest, InvitationResponse, InvitationTokenRequest,
+            MemberResponse, OrganizationResponse, UpdateMemberRequest,
+        },
+        organization_middleware::Tenant,
+        organization_service,
+    },
+};
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use tower_cookies::Cookies;
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn list_organizations_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+) -> ApiResult<Json<Vec<OrganizationResponse>>> {
+    let organizations = organization_service::list_organizations(authenticated_user.id, state.pool).await?;
+    Ok(Json(organizations))
+}
+
+pub async fn create_organization_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Json(payload): Json<CreateOrganizationRequest>,
+) -> ApiResult<Json<OrganizationResponse>> {
+    payload.validate()?;
+    let organization = organization_service::create_organization(authenticated_user.id, payload, state.pool).await?;
+    Ok(Json(organization))
+}
+
+pub async fn activate_organization_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    cookies: Cookies,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<AuthResponse>> {
+    let auth_response = organization_service::activate_organization(authenticated_user, id, state.pool).await?;
+    Ok(Json(auth_cookies::store_session(&cookies, &state.auth_cookies, auth_response)))
+}
+
+pub async fn get_organization_handler(
+    State(state): State<AppState>,
+    tenant: Tenant,
+) -> ApiResult<Json<OrganizationResponse>> {
+    let organization = organization_service::get_organization(tenant, state.pool).await?;
+    Ok(Json(organization))
+}
+
+pub async fn list_members_handler(
+    State(state): State<AppState>,
+    tenant: Tenant,
+) -> ApiResult<Json<Vec<MemberResponse>>> {
+    let members = organization_service::list_members(tenant, state.pool).await?;
+    Ok(Json(members))
+}
+
+pub async fn update_member_handler(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<UpdateMemberRequest>,
+) -> ApiResult<Json<MemberResponse>> {
+    let member = organization_service::update_member_role(tenant, user_id, payload.role, state.pool).await?;
+    Ok(Json(member))
+}
+
+pub async fn remove_member_handler(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<serde_json::Value>> {
+    organization_service::remove_member(tenant, user_id, state.pool).await?;
+    Ok(Json(serde_json::json!({"message": "Member removed"})))
+}
+
+pub async fn list_invitations_handler(
+    State(state): State<AppState>,
+    tenant: Tenant,
+) -> ApiResult<Json<Vec<InvitationResponse>>> {
+    let invitations = organization_service::list_invitations(tenant, state.pool).await?;
+    Ok(Json(invitations))
+}
+
+pub async fn create_invitation_handler(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Json(payload): Json<CreateInvitationRequest>,
+) -> ApiResult<Json<InvitationResponse>> {
+    payload.validate()?;
+    let invitation = organization_service::create_invitation(tenant, payload, state.pool, &state.mailer).await?;
+    Ok(Json(invitation))
+}
+
+pub async fn revoke_invitation_handler(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<serde_json::Value>> {
+    organization_service::revoke_invitation(tenant, id, state.pool).await?;
+    Ok(Json(serde_json::json!({"message": "Invitation revoked"})))
+}
+
+pub async fn accept_invitation_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Json(payload): Json<InvitationTokenRequest>,
+) -> ApiResult<Json<OrganizationResponse>> {
+    payload.validate()?;
+    let organization = organization_service::accept_invitation(authenticated_user.id, payload.token, state.pool).await?;
+    Ok(Json(organization))
+}
+
+pub async fn decline_invitation_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Json(payload): Json<InvitationTokenRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    payload.validate()?;
+    organization_service::decline_invitation(authenticated_user.id, payload.token, state.pool).await?;
+    Ok(Json(serde_json::json!({"message": "Invitation declined"})))
+}
@@ -0,0 +1,71 @@
+use crate::{
+    app::AppState,
+    auth::auth_middleware::AuthUser,
+    errors::{ApiError, ApiResult},
+    organization::{organization_dto::OrganizationRole, organization_service},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use uuid::Uuid;
+
+// Extractor for tenant-scoped handlers. It resolves the `org_id` claim and
+// checks the membership on every request, so a `Tenant` always names an
+// organization the caller belongs to. Repositories for tenant-owned data take
+// `tenant.organization_id()` rather than an id from the request.
+#[derive(Clone, Debug)]
+pub struct Tenant {
+    user: AuthUser,
+    organization_id: Uuid,
+    role: OrganizationRole,
+}
+
+impl Tenant {
+    pub fn user(&self) -> &AuthUser {
+        &self.user
+    }
+
+    pub fn user_id(&self) -> Uuid {
+        self.user.id
+    }
+
+    pub fn organization_id(&self) -> Uuid {
+        self.organization_id
+    }
+
+    pub fn role(&self) -> OrganizationRole {
+        self.role
+    }
+
+    pub fn require_role(&self, role: OrganizationRole) -> ApiResult<()> {
+        if self.role >= role {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(format!("The {} organization role is required", role.as_str())))
+        }
+    }
+}
+
+impl FromRequestParts<AppState> for Tenant {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let user = parts
+            .extensions
+            .get::<AuthUser>()
+            .cloned()
+            .ok_or_else(|| ApiError::Unauthorized("Authentication required".to_string()))?;
+
+        let organization_id: Uuid = user
+            .claim("org_id")
+            .ok_or_else(|| ApiError::Forbidden("No active organization".to_string()))?;
+
+        let role = organization_service::membership_role(user.id, organization_id, state.pool.clone())
+            .await?
+            .ok_or_else(|| ApiError::Forbidden("You are not a member of the active organization".to_string()))?;
+
+        Ok(Tenant {
+            user,
+            organization_id,
+            role,
+        })
+    }
+}
@@ -0,0 +1,175 @@
+use crate::db::models::{
+    invitation::{Invitation, NewInvitation},
+    membership::{Membership, NewMembership},
+    organization::{NewOrganization, Organization},
+};
+use crate::schema::{invitations, memberships, organizations, users};
+use chrono::Utc;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub fn create_organization(new_organization: &NewOrganization, conn: &mut PgConnection) -> QueryResult<Organization> {
+    diesel::insert_into(organizations::table)
+        .values(new_organization)
+        .returning(Organization::as_returning())
+        .get_result(conn)
+}
+
+pub fn find_organization(organization_id: Uuid, conn: &mut PgConnection) -> QueryResult<Organization> {
+    organizations::table
+        .filter(organizations::id.eq(organization_id))
+        .select(Organization::as_select())
+        .first(conn)
+}
+
+pub fn slug_exists(slug: &str, conn: &mut PgConnection) -> QueryResult<bool> {
+    diesel::select(diesel::dsl::exists(organizations::table.filter(organizations::slug.eq(slug)))).get_result(conn)
+}
+
+pub fn find_user_organizations(user_id: Uuid, conn: &mut PgConnection) -> QueryResult<Vec<(Organization, String)>> {
+    memberships::table
+        .inner_join(organizations::table)
+        .filter(memberships::user_id.eq(user_id))
+        .order(organizations::name.asc())
+        .select((Organization::as_select(), memberships::role))
+        .load(conn)
+}
+
+pub fn create_membership(new_membership: &NewMembership, conn: &mut PgConnection) -> QueryResult<Membership> {
+    diesel::insert_into(memberships::table)
+        .values(new_membership)
+        .returning(Membership::as_returning())
+        .get_result(conn)
+}
+
+pub fn find_membership(
+    organization_id: Uuid,
+    user_id: Uuid,
+    conn: &mut PgConnection,
+) -> QueryResult<Option<Membership>> {
+    memberships::table
+        .filter(memberships::organization_id.eq(organization_id))
+        .filter(memberships::user_id.eq(user_id))
+        .select(Membership::as_select())
+        .first(conn)
+        .optional()
+}
+
+pub fn find_members(
+    organization_id: Uuid,
+    conn: &mut PgConnection,
+) -> QueryResult<Vec<(Membership, String, String, String)>> {
+    memberships::table
+        .inner_join(users::table)
+        .filter(memberships::organization_id.eq(organization_id))
+        .order(memberships::created_at.asc())
+        .select((Membership::as_select(), users::email, users::first_name, users::last_name))
+        .load(conn)
+}
+
+pub fn update_membership_role(
+    organization_id: Uuid,
+    user_id: Uuid,
+    role: &str,
+    conn: &mut PgConnection,
+) -> QueryResult<usize> {
+    diesel::update(
+        memberships::table
+            .filter(memberships::organization_id.eq(organization_id))
+            .filter(memberships::user_id.eq(user_id)),
+    )
+    .set(memberships::role.eq(role))
+    .execute(conn)
+}
+
+// Also clears the organization from the user's active organization.
+pub fn delete_membership(organization_id: Uuid, user_id: Uuid, conn: &mut PgConnection) -> QueryResult<usize> {
+    let deleted = diesel::delete(
+        memberships::table
+            .filter(memberships::organization_id.eq(organization_id))
+            .filter(memberships::user_id.eq(user_id)),
+    )
+    .execute(conn)?;
+
+    diesel::update(
+        users::table
+            .filter(users::id.eq(user_id))
+            .filter(users::active_organization_id.eq(organization_id)),
+    )
+    .set(users::active_organization_id.eq(None::<Uuid>))
+    .execute(conn)?;
+
+    Ok(deleted)
+}
+
+pub fn count_owners(organization_id: Uuid, conn: &mut PgConnection) -> QueryResult<i64> {
+    memberships::table
+        .filter(memberships::organization_id.eq(organization_id))
+        .filter(memberships::role.eq("owner"))
+        .count()
+        .get_result(conn)
+}
+
+pub fn create_invitation(new_invitation: &NewInvitation, conn: &mut PgConnection) -> QueryResult<Invitation> {
+    diesel::insert_into(invitations::table)
+        .values(new_invitation)
+        .returning(Invitation::as_returning())
+        .get_result(conn)
+}
+
+pub fn find_invitation_by_token_hash(token_hash: &str, conn: &mut PgConnection) -> QueryResult<Option<Invitation>> {
+    invitations::table
+        .filter(invitations::token_hash.eq(token_hash))
+        .select(Invitation::as_select())
+        .first(conn)
+        .optional()
+}
+
+// Pending invitations are neither answered nor expired.
+pub fn find_pending_invitations(organization_id: Uuid, conn: &mut PgConnection) -> QueryResult<Vec<Invitation>> {
+    invitations::table
+        .filter(invitations::organization_id.eq(organization_id))
+        .filter(invitations::accepted_at.is_null())
+        .filter(invitations::declined_at.is_null())
+        .filter(invitations::expires_at.gt(Utc::now().naive_utc()))
+        .order(invitations::created_at.desc())
+        .select(Invitation::as_select())
+        .load(conn)
+}
+
+pub fn has_pending_invitation(organization_id: Uuid, email: &str, conn: &mut PgConnection) -> QueryResult<bool> {
+    diesel::select(diesel::dsl::exists(
+        invitations::table
+            .filter(invitations::organization_id.eq(organization_id))
+            .filter(invitations::email.eq(email))
+            .filter(invitations::accepted_at.is_null())
+            .filter(invitations::declined_at.is_null())
+            .filter(invitations::expires_at.gt(Utc::now().naive_utc())),
+    ))
+    .get_result(conn)
+}
+
+// Answers a pending invitation; returns 0 when it was already answered or expired.
+pub fn answer_invitation(invitation_id: Uuid, accepted: bool, conn: &mut PgConnection) -> QueryResult<usize> {
+    let pending = invitations::table
+        .filter(invitations::id.eq(invitation_id))
+        .filter(invitations::accepted_at.is_null())
+        .filter(invitations::declined_at.is_null())
+        .filter(invitations::expires_at.gt(Utc::now().naive_utc()));
+    let now = Some(Utc::now().naive_utc());
+
+    if accepted {
+        diesel::update(pending).set(invitations::accepted_at.eq(now)).execute(conn)
+    } else {
+        diesel::update(pending).set(invitations::declined_at.eq(now)).execute(conn)
+    }
+}
+
+pub fn delete_invitation(organization_id: Uuid, invitation_id: Uuid, conn: &mut PgConnection) -> QueryResult<usize> {
+    diesel::delete(
+        invitations::table
+            .filter(invitations::id.eq(invitation_id))
+            .filter(invitations::organization_id.eq(organization_id)),
+    )
+    .execute(conn)
+}
@@ -0,0 +1,418 @@
+use crate::{
+    auth::{
+        auth_dto::AuthResponse,
+        auth_hashing::hash_token,
+        auth_middleware::AuthUser,
+        auth_service,
+    },
+    config::database::DbPool,
+    db::{
+        models::{
+            invitation::{Invitation, NewInvitation},
+            membership::NewMembership,
+            organization::{NewOrganization, Organization},
+            user::User,
+        },
+        with_connection,
+    },
+    errors::{ApiError, ApiResult},
+    mail::{mail_notifications, mailer::Mailer},
+    organization::{
+        organization_dto::{
+            CreateInvitationRequest, CreateOrganizationRequest, InvitationResponse, MemberResponse,
+            OrganizationResponse, OrganizationRole,
+        },
+        organization_middleware::Tenant,
+        organization_repository,
+    },
+    user::user_repository,
+};
+use chrono::{Duration, Utc};
+use diesel::{Connection, OptionalExtension, PgConnection};
+use std::sync::Arc;
+use uuid::Uuid;
+
+// The organization behind the `org_id` claim. A stale choice (the user left
+// the organization) yields no claim at all.
+pub fn active_organization_id(user: &User, conn: &mut PgConnection) -> ApiResult<Option<Uuid>> {
+    let Some(organization_id) = user.active_organization_id else {
+        return Ok(None);
+    };
+
+    Ok(organization_repository::find_membership(organization_id, user.id, conn)?.map(|_| organization_id))
+}
+
+pub async fn membership_role(user_id: Uuid, organization_id: Uuid, pool: DbPool) -> ApiResult<Option<OrganizationRole>> {
+    with_connection(&pool, move |conn| {
+        organization_repository::find_membership(organization_id, user_id, conn)?
+            .map(|membership| parse_role(&membership.role))
+            .transpose()
+    })
+    .await
+}
+
+pub async fn create_organization(
+    user_id: Uuid,
+    request: CreateOrganizationRequest,
+    pool: DbPool,
+) -> ApiResult<OrganizationResponse> {
+    with_connection(&pool, move |conn| {
+        if organization_repository::slug_exists(&request.slug, conn)? {
+            return Err(ApiError::ResourceAlreadyExists("Slug already taken".to_string()));
+        }
+
+        conn.transaction(|conn| {
+            let organization = organization_repository::create_organization(
+                &NewOrganization {
+                    name: request.name,
+                    slug: request.slug,
+                },
+                conn,
+            )?;
+            add_member(organization.id, user_id, OrganizationRole::Owner, conn)?;
+
+            tracing::info!("Organization {} created by user: {}", organization.id, user_id);
+
+            let active = user_repository::find_user_by_id(user_id, conn)?.active_organization_id == Some(organization.id);
+            Ok(organization_to_response(organization, OrganizationRole::Owner, active))
+        })
+    })
+    .await
+}
+
+pub async fn list_organizations(user_id: Uuid, pool: DbPool) -> ApiResult<Vec<OrganizationResponse>> {
+    with_connection(&pool, move |conn| {
+        let user = user_repository::find_user_by_id(user_id, conn)?;
+
+        organization_repository::find_user_organizations(user_id, conn)?
+            .into_iter()
+            .map(|(organization, role)| {
+                let active = user.active_organization_id == Some(organization.id);
+                Ok(organization_to_response(organization, parse_role(&role)?, active))
+            })
+            .collect()
+    })
+    .await
+}
+
+// Makes the organization the active one and returns an access token carrying
+// it, bound to the current session.
+pub async fn activate_organization(user: AuthUser, organization_id: Uuid, pool: DbPool) -> ApiResult<AuthResponse> {
+    with_connection(&pool, move |conn| {
+        organization_repository::find_membership(organization_id, user.id, conn)?
+            .ok_or_else(|| ApiError::NotFound("Organization not found".to_string()))?;
+
+        let account = user_repository::set_active_organization(user.id, Some(organization_id), conn)?;
+        auth_service::reissue_access_token(account, user.claim("sid"), conn)
+    })
+    .await
+}
+
+pub async fn get_organization(tenant: Tenant, pool: DbPool) -> ApiResult<OrganizationResponse> {
+    with_connection(&pool, move |conn| {
+        let organization = organization_repository::find_organization(tenant.organization_id(), conn)?;
+        Ok(organization_to_response(organization, tenant.role(), true))
+    })
+    .await
+}
+
+pub async fn list_members(tenant: Tenant, pool: DbPool) -> ApiResult<Vec<MemberResponse>> {
+    with_connection(&pool, move |conn| list_members_of(tenant.organization_id(), conn)).await
+}
+
+// Admins manage members; only owners can hand out or take away ownership.
+pub async fn update_member_role(
+    tenant: Tenant,
+    user_id: Uuid,
+    role: OrganizationRole,
+    pool: DbPool,
+) -> ApiResult<MemberResponse> {
+    tenant.require_role(OrganizationRole::Admin)?;
+
+    with_connection(&pool, move |conn| {
+        let organization_id = tenant.organization_id();
+        let current = member_role(organization_id, user_id, conn)?;
+        if role == OrganizationRole::Owner || current == OrganizationRole::Owner {
+            tenant.require_role(OrganizationRole::Owner)?;
+        }
+
+        conn.transaction(|conn| {
+            organization_repository::update_membership_role(organization_id, user_id, role.as_str(), conn)?;
+            ensure_owner_remains(organization_id, conn)
+        })?;
+
+        tracing::info!(
+            "Organization {} member {} changed to {} by user: {}",
+            organization_id,
+            user_id,
+            role.as_str(),
+            tenant.user_id()
+        );
+
+        list_members_of(organization_id, conn)?
+            .into_iter()
+            .find(|member| member.user_id == user_id)
+            .ok_or_else(|| ApiError::NotFound("Member not found".to_string()))
+    })
+    .await
+}
+
+// Members can always leave; removing someone else needs the admin role, and
+// removing an owner needs the owner role.
+pub async fn remove_member(tenant: Tenant, user_id: Uuid, pool: DbPool) -> ApiResult<()> {
+    if user_id != tenant.user_id() {
+        tenant.require_role(OrganizationRole::Admin)?;
+    }
+
+    with_connection(&pool, move |conn| {
+        let organization_id = tenant.organization_id();
+        if member_role(organization_id, user_id, conn)? == OrganizationRole::Owner && user_id != tenant.user_id() {
+            tenant.require_role(OrganizationRole::Owner)?;
+        }
+
+        conn.transaction(|conn| {
+            organization_repository::delete_membership(organization_id, user_id, conn)?;
+            ensure_owner_remains(organization_id, conn)
+        })?;
+
+        tracing::info!("User {} removed from organization {} by user: {}", user_id, organization_id, tenant.user_id());
+
+        Ok(())
+    })
+    .await
+}
+
+pub async fn list_invitations(tenant: Tenant, pool: DbPool) -> ApiResult<Vec<InvitationResponse>> {
+    tenant.require_role(OrganizationRole::Admin)?;
+
+    with_connection(&pool, move |conn| {
+        organization_repository::find_pending_invitations(tenant.organization_id(), conn)?
+            .into_iter()
+            .map(invitation_to_response)
+            .collect()
+    })
+    .await
+}
+
+pub async fn create_invitation(
+    tenant: Tenant,
+    request: CreateInvitationRequest,
+    pool: DbPool,
+    mailer: &Arc<dyn Mailer>,
+) -> ApiResult<InvitationResponse> {
+    let role = request.role.unwrap_or(OrganizationRole::Member);
+    tenant.require_role(OrganizationRole::Admin)?;
+    if role == OrganizationRole::Owner {
+        tenant.require_role(OrganizationRole::Owner)?;
+    }
+
+    let email = request.email.trim().to_lowercase();
+    let token = auth_service::generate_random_token();
+    let token_hash = hash_token(&token);
+
+    let (invitation, organization, inviter, invitee) = with_connection(&pool, move |conn| {
+        let organization_id = tenant.organization_id();
+        let organization = organization_repository::find_organization(organization_id, conn)?;
+        let invitee = user_repository::find_user_by_email(&email, conn).optional()?;
+
+        if let Some(invitee) = &invitee {
+            if organization_repository::find_membership(organization_id, invitee.id, conn)?.is_some() {
+                return Err(ApiError::ResourceAlreadyExists("The user is already a member".to_string()));
+            }
+        }
+        if organization_repository::has_pending_invitation(organization_id, &email, conn)? {
+            return Err(ApiError::ResourceAlreadyExists("The user has already been invited".to_string()));
+        }
+
+        let invitation = organization_repository::create_invitation(
+            &NewInvitation {
+                organization_id,
+                email,
+                role: role.as_str().to_string(),
+                token_hash,
+                invited_by: Some(tenant.user_id()),
+                expires_at: (Utc::now() + invitation_ttl()).naive_utc(),
+            },
+            conn,
+        )?;
+        let inviter = user_repository::find_user_by_id(tenant.user_id(), conn)?;
+
+        tracing::info!("Invitation {} to organization {} created by user: {}", invitation.id, organization_id, inviter.id);
+
+        Ok((invitation, organization, inviter, invitee))
+    })
+    .await?;
+
+    mail_notifications::send_invitation_email(
+        mailer,
+        &invitation.email,
+        invitee.as_ref(),
+        &inviter,
+        &organization.name,
+        &token,
+    );
+
+    invitation_to_response(invitation)
+}
+
+pub async fn revoke_invitation(tenant: Tenant, invitation_id: Uuid, pool: DbPool) -> ApiResult<()> {
+    tenant.require_role(OrganizationRole::Admin)?;
+
+    with_connection(&pool, move |conn| {
+        if organization_repository::delete_invitation(tenant.organization_id(), invitation_id, conn)? == 0 {
+            return Err(ApiError::NotFound("Invitation not found".to_string()));
+        }
+
+        Ok(())
+    })
+    .await
+}
+
+// Invitations are bound to the invited address: only the account with that
+// email can answer them.
+pub async fn accept_invitation(user_id: Uuid, token: String, pool: DbPool) -> ApiResult<OrganizationResponse> {
+    with_connection(&pool, move |conn| {
+        let invitation = find_invitation_for(user_id, &token, conn)?;
+        let role = parse_role(&invitation.role)?;
+
+        if organization_repository::find_membership(invitation.organization_id, user_id, conn)?.is_some() {
+            return Err(ApiError::ResourceAlreadyExists("You are already a member".to_string()));
+        }
+
+        conn.transaction(|conn| {
+            if organization_repository::answer_invitation(invitation.id, true, conn)? == 0 {
+                return Err(invalid_invitation());
+            }
+            add_member(invitation.organization_id, user_id, role, conn)?;
+
+            tracing::info!("Invitation {} accepted by user: {}", invitation.id, user_id);
+
+            let organization = organization_repository::find_organization(invitation.organization_id, conn)?;
+            let active = user_repository::find_user_by_id(user_id, conn)?.active_organization_id == Some(organization.id);
+            Ok(organization_to_response(organization, role, active))
+        })
+    })
+    .await
+}
+
+pub async fn decline_invitation(user_id: Uuid, token: String, pool: DbPool) -> ApiResult<()> {
+    with_connection(&pool, move |conn| {
+        let invitation = find_invitation_for(user_id, &token, conn)?;
+
+        if organization_repository::answer_invitation(invitation.id, false, conn)? == 0 {
+            return Err(invalid_invitation());
+        }
+
+        tracing::info!("Invitation {} declined by user: {}", invitation.id, user_id);
+
+        Ok(())
+    })
+    .await
+}
+
+fn find_invitation_for(user_id: Uuid, token: &str, conn: &mut PgConnection) -> ApiResult<Invitation> {
+    let invitation = organization_repository::find_invitation_by_token_hash(&hash_token(token), conn)?
+        .filter(|invitation| {
+            invitation.accepted_at.is_none()
+                && invitation.declined_at.is_none()
+                && invitation.expires_at > Utc::now().naive_utc()
+        })
+        .ok_or_else(invalid_invitation)?;
+
+    let user = user_repository::find_user_by_id(user_id, conn)?;
+    if user.email.to_lowercase() != invitation.email {
+        return Err(ApiError::Forbidden("The invitation was sent to another email address".to_string()));
+    }
+
+    Ok(invitation)
+}
+
+// New members whose account has no active organization yet get this one.
+fn add_member(organization_id: Uuid, user_id: Uuid, role: OrganizationRole, conn: &mut PgConnection) -> ApiResult<()> {
+    organization_repository::create_membership(
+        &NewMembership {
+            organization_id,
+            user_id,
+            role: role.as_str().to_string(),
+        },
+        conn,
+    )?;
+
+    let user = user_repository::find_user_by_id(user_id, conn)?;
+    if active_organization_id(&user, conn)?.is_none() {
+        user_repository::set_active_organization(user_id, Some(organization_id), conn)?;
+    }
+
+    Ok(())
+}
+
+fn member_role(organization_id: Uuid, user_id: Uuid, conn: &mut PgConnection) -> ApiResult<OrganizationRole> {
+    let membership = organization_repository::find_membership(organization_id, user_id, conn)?
+        .ok_or_else(|| ApiError::NotFound("Member not found".to_string()))?;
+
+    parse_role(&membership.role)
+}
+
+fn ensure_owner_remains(organization_id: Uuid, conn: &mut PgConnection) -> ApiResult<()> {
+    if organization_repository::count_owners(organization_id, conn)? == 0 {
+        return Err(ApiError::BadRequest("An organization needs at least one owner".to_string()));
+    }
+
+    Ok(())
+}
+
+fn list_members_of(organization_id: Uuid, conn: &mut PgConnection) -> ApiResult<Vec<MemberResponse>> {
+    organization_repository::find_members(organization_id, conn)?
+        .into_iter()
+        .map(|(membership, email, first_name, last_name)| {
+            Ok(MemberResponse {
+                user_id: membership.user_id,
+                email,
+                first_name,
+                last_name,
+                role: parse_role(&membership.role)?,
+                joined_at: membership.created_at,
+            })
+        })
+        .collect()
+}
+
+fn parse_role(role: &str) -> ApiResult<OrganizationRole> {
+    OrganizationRole::parse(role)
+        .ok_or_else(|| ApiError::InternalServerError(format!("Unknown organization role: {}", role)))
+}
+
+fn invalid_invitation() -> ApiError {
+    ApiError::BadRequest("Invalid or expired invitation".to_string())
+}
+
+fn invitation_ttl() -> Duration {
+    let hours = std::env::var("INVITATION_TTL_HOURS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(168);
+
+    Duration::hours(hours)
+}
+
+fn organization_to_response(organization: Organization, role: OrganizationRole, active: bool) -> OrganizationResponse {
+    OrganizationResponse {
+        id: organization.id,
+        name: organization.name,
+        slug: organization.slug,
+        role,
+        active,
+        created_at: organization.created_at,
+    }
+}
+
+fn invitation_to_response(invitation: Invitation) -> ApiResult<InvitationResponse> {
+    Ok(InvitationResponse {
+        role: parse_role(&invitation.role)?,
+        id: invitation.id,
+        email: invitation.email,
+        invited_by: invitation.invited_by,
+        expires_at: invitation.expires_at,
+        created_at: invitation.created_at,
+    })
+}
@@ -0,0 +1,5 @@
+pub mod rbac_handler;
+pub mod rbac_service;
+pub mod rbac_repository;
+pub mod rbac_dto;
+pub mod rbac_middleware;
@@ -0,0 +1,15 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize)]
+pub struct RoleResponse {
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserRolesResponse {
+    pub user_id: Uuid,
+    pub roles: Vec<String>,
+}
@@ -0,0 +1,45 @@
+use crate::{
+    app::AppState,
+    auth::auth_middleware::AuthUser,
+    errors::ApiResult,
+    rbac::{
+        rbac_dto::{RoleResponse, UserRolesResponse},
+        rbac_service,
+    },
+};
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use uuid::Uuid;
+
+pub async fn list_roles_handler(State(state): State<AppState>) -> ApiResult<Json<Vec<RoleResponse>>> {
+    let roles = rbac_service::list_roles(state.pool).await?;
+    Ok(Json(roles))
+}
+
+pub async fn list_user_roles_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<UserRolesResponse>> {
+    let roles = rbac_service::list_user_roles(user_id, state.pool).await?;
+    Ok(Json(roles))
+}
+
+pub async fn assign_role_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Path((user_id, role)): Path<(Uuid, String)>,
+) -> ApiResult<Json<UserRolesResponse>> {
+    let roles = rbac_service::assign_role(authenticated_user.id, user_id, role, state.pool).await?;
+    Ok(Json(roles))
+}
+
+pub async fn remove_role_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Path((user_id, role)): Path<(Uuid, String)>,
+) -> ApiResult<Json<UserRolesResponse>> {
+    let roles = rbac_service::remove_role(authenticated_user.id, user_id, role, state.pool).await?;
+    Ok(Json(roles))
+}
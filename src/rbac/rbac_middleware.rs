@@ -0,0 +1,80 @@
+use crate::{
+    app::AppState,
+    auth::auth_middleware::AuthUser,
+    errors::ApiError,
+    rbac::rbac_service,
+};
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::{request::Parts, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use std::marker::PhantomData;
+
+pub trait Permission {
+    const NAME: &'static str;
+}
+
+macro_rules! permissions {
+    ($($(#[$meta:meta])* $marker:ident => $name:literal,)*) => {
+        $(
+            $(#[$meta])*
+            pub struct $marker;
+
+            impl Permission for $marker {
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+// Seeded by the roles migration and granted to `admin`.
+permissions! {
+    UsersRead => "users:read",
+    UsersWrite => "users:write",
+    RolesManage => "roles:manage",
+    AuditRead => "audit:read",
+}
+
+// Extractor for handlers that need a permission:
+// `RequirePermission(admin, _): RequirePermission<UsersWrite>`.
+pub struct RequirePermission<P: Permission>(pub AuthUser, pub PhantomData<P>);
+
+impl<P: Permission> FromRequestParts<AppState> for RequirePermission<P> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let user = parts
+            .extensions
+            .get::<AuthUser>()
+            .cloned()
+            .ok_or_else(|| ApiError::Unauthorized("Authentication required".to_string()))?;
+
+        rbac_service::require_permission(&user, P::NAME, state.pool.clone()).await?;
+
+        Ok(Self(user, PhantomData))
+    }
+}
+
+// Route layer for whole groups of routes:
+// `.route_layer(from_fn_with_state(state, require_permission::<RolesManage>))`.
+pub async fn require_permission<P: Permission>(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let user = req
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if !rbac_service::has_permission(user, P::NAME, state.pool.clone())
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(next.run(req).await)
+}
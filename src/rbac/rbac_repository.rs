@@ -0,0 +1,70 @@
+use crate::db::models::{role::Role, user_role::NewUserRole};
+use crate::schema::{permissions, role_permissions, roles, user_roles};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub fn find_user_role_names(user_id: Uuid, conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+    user_roles::table
+        .inner_join(roles::table)
+        .filter(user_roles::user_id.eq(user_id))
+        .order(roles::name.asc())
+        .select(roles::name)
+        .load(conn)
+}
+
+pub fn user_has_permission(user_id: Uuid, permission: &str, conn: &mut PgConnection) -> QueryResult<bool> {
+    diesel::select(diesel::dsl::exists(
+        user_roles::table
+            .inner_join(role_permissions::table.on(role_permissions::role_id.eq(user_roles::role_id)))
+            .inner_join(permissions::table.on(permissions::id.eq(role_permissions::permission_id)))
+            .filter(user_roles::user_id.eq(user_id))
+            .filter(permissions::name.eq(permission)),
+    ))
+    .get_result(conn)
+}
+
+pub fn find_role_by_name(name: &str, conn: &mut PgConnection) -> QueryResult<Option<Role>> {
+    roles::table
+        .filter(roles::name.eq(name))
+        .select(Role::as_select())
+        .first(conn)
+        .optional()
+}
+
+pub fn list_roles(conn: &mut PgConnection) -> QueryResult<Vec<Role>> {
+    roles::table
+        .order(roles::name.asc())
+        .select(Role::as_select())
+        .load(conn)
+}
+
+pub fn list_role_permission_names(conn: &mut PgConnection) -> QueryResult<Vec<(Uuid, String)>> {
+    role_permissions::table
+        .inner_join(permissions::table)
+        .order(permissions::name.asc())
+        .select((role_permissions::role_id, permissions::name))
+        .load(conn)
+}
+
+pub fn assign_role(new_user_role: &NewUserRole, conn: &mut PgConnection) -> QueryResult<usize> {
+    diesel::insert_into(user_roles::table)
+        .values(new_user_role)
+        .on_conflict_do_nothing()
+        .execute(conn)
+}
+
+pub fn remove_role(user_id: Uuid, role_id: Uuid, conn: &mut PgConnection) -> QueryResult<usize> {
+    diesel::delete(
+        user_roles::table
+            .filter(user_roles::user_id.eq(user_id))
+            .filter(user_roles::role_id.eq(role_id)),
+    )
+    .execute(conn)
+}
+
+pub fn count_role_members(role_id: Uuid, conn: &mut PgConnection) -> QueryResult<i64> {
+    user_roles::table
+        .filter(user_roles::role_id.eq(role_id))
+        .count()
+        .get_result(conn)
+}
@@ -0,0 +1,123 @@
+use crate::{
+    auth::auth_middleware::AuthUser,
+    config::database::DbPool,
+    db::{models::user_role::NewUserRole, with_connection},
+    errors::{ApiError, ApiResult},
+    rbac::{
+        rbac_dto::{RoleResponse, UserRolesResponse},
+        rbac_repository,
+    },
+    user::user_repository,
+};
+use diesel::{Connection, PgConnection};
+use uuid::Uuid;
+
+pub const ADMIN_ROLE: &str = "admin";
+
+// Permissions are resolved from the database on every check, so removing a
+// role takes effect immediately rather than when the access token expires.
+// Scoped tokens (OAuth clients, API keys) never carry permissions.
+pub async fn has_permission(user: &AuthUser, permission: &'static str, pool: DbPool) -> ApiResult<bool> {
+    if user.scopes.is_some() {
+        return Ok(false);
+    }
+
+    let user_id = user.id;
+    with_connection(&pool, move |conn| {
+        Ok(rbac_repository::user_has_permission(user_id, permission, conn)?)
+    })
+    .await
+}
+
+pub async fn require_permission(user: &AuthUser, permission: &'static str, pool: DbPool) -> ApiResult<()> {
+    if has_permission(user, permission, pool).await? {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden(format!("The {} permission is required", permission)))
+    }
+}
+
+pub fn user_role_names(user_id: Uuid, conn: &mut PgConnection) -> ApiResult<Vec<String>> {
+    Ok(rbac_repository::find_user_role_names(user_id, conn)?)
+}
+
+pub async fn list_roles(pool: DbPool) -> ApiResult<Vec<RoleResponse>> {
+    with_connection(&pool, move |conn| {
+        let roles = rbac_repository::list_roles(conn)?;
+        let role_permissions = rbac_repository::list_role_permission_names(conn)?;
+
+        Ok(roles
+            .into_iter()
+            .map(|role| RoleResponse {
+                permissions: role_permissions
+                    .iter()
+                    .filter(|(role_id, _)| *role_id == role.id)
+                    .map(|(_, permission)| permission.clone())
+                    .collect(),
+                name: role.name,
+                description: role.description,
+            })
+            .collect())
+    })
+    .await
+}
+
+pub async fn list_user_roles(user_id: Uuid, pool: DbPool) -> ApiResult<UserRolesResponse> {
+    with_connection(&pool, move |conn| {
+        user_repository::find_user_by_id(user_id, conn)
+            .map_err(|_| ApiError::NotFound("User not found".to_string()))?;
+
+        Ok(UserRolesResponse {
+            user_id,
+            roles: user_role_names(user_id, conn)?,
+        })
+    })
+    .await
+}
+
+pub async fn assign_role(actor_id: Uuid, user_id: Uuid, role_name: String, pool: DbPool) -> ApiResult<UserRolesResponse> {
+    with_connection(&pool, move |conn| {
+        user_repository::find_user_by_id(user_id, conn)
+            .map_err(|_| ApiError::NotFound("User not found".to_string()))?;
+        let role = rbac_repository::find_role_by_name(&role_name, conn)?
+            .ok_or_else(|| ApiError::NotFound("Role not found".to_string()))?;
+
+        rbac_repository::assign_role(&NewUserRole { user_id, role_id: role.id }, conn)?;
+
+        tracing::info!(target: "security", actor_id = %actor_id, user_id = %user_id, role = %role.name, "Role assigned");
+
+        Ok(UserRolesResponse {
+            user_id,
+            roles: user_role_names(user_id, conn)?,
+        })
+    })
+    .await
+}
+
+pub async fn remove_role(actor_id: Uuid, user_id: Uuid, role_name: String, pool: DbPool) -> ApiResult<UserRolesResponse> {
+    with_connection(&pool, move |conn| {
+        let role = rbac_repository::find_role_by_name(&role_name, conn)?
+            .ok_or_else(|| ApiError::NotFound("Role not found".to_string()))?;
+
+        conn.transaction(|conn| {
+            if rbac_repository::remove_role(user_id, role.id, conn)? == 0 {
+                return Err(ApiError::NotFound("The user doesn't have this role".to_string()));
+            }
+
+            // Keep at least one administrator so the admin API stays reachable.
+            if role.name == ADMIN_ROLE && rbac_repository::count_role_members(role.id, conn)? == 0 {
+                return Err(ApiError::BadRequest("The last administrator can't be removed".to_string()));
+            }
+
+            Ok(())
+        })?;
+
+        tracing::info!(target: "security", actor_id = %actor_id, user_id = %user_id, role = %role.name, "Role removed");
+
+        Ok(UserRolesResponse {
+            user_id,
+            roles: user_role_names(user_id, conn)?,
+        })
+    })
+    .await
+}
@@ -1,9 +1,20 @@
-use crate::auth::{auth_handler, auth_middleware::auth_middleware};
+use crate::admin::admin_handler;
+use crate::api_key::api_key_handler;
+use crate::audit::audit_handler;
+use crate::auth::{auth_handler, auth_middleware::{auth_middleware, reject_scoped_tokens, require_verified_email}};
 use crate::app::AppState;
 use crate::health::health_handler;
+use crate::mfa::mfa_handler;
+use crate::oauth::oauth_handler;
+use crate::oidc::oidc_handler;
+use crate::organization::organization_handler;
+use crate::rbac::{rbac_handler, rbac_middleware::{require_permission, RolesManage}};
+use crate::middleware::csrf_middleware::csrf_middleware;
+use crate::middleware::db_context_middleware::db_context_middleware;
 use crate::middleware::error_middleware::error_handling_middleware;
 use crate::middleware::rate_limiter::{rate_limit_middleware, RateLimiter};
 use crate::user::user_handler;
+use crate::webauthn::webauthn_handler;
 use axum::http::{HeaderName, Method};
 use axum::middleware::from_fn_with_state;
 use axum::{middleware::from_fn, Router};
@@ -29,35 +40,160 @@ pub fn create_routes(app_state: AppState) -> Router {
             HeaderName::from_static("content-type"),
             HeaderName::from_static("authorization"),
             HeaderName::from_static("x-requested-with"),
+            HeaderName::from_static("x-api-key"),
+            HeaderName::from_static("x-csrf-token"),
         ])
         .allow_credentials(true);
 
     let strict_limiter = RateLimiter::new(5, 60);
     let normal_limiter = RateLimiter::new(60, 60);
+    let verification_limiter = RateLimiter::new(3, 3600);
+    let oauth_limiter = RateLimiter::new(60, 60);
+
+    let resend_verification_routes = Router::new()
+        .route("/resend-verification", axum::routing::post(auth_handler::resend_verification_handler))
+        .layer(from_fn_with_state(verification_limiter, rate_limit_middleware));
 
     let auth_routes = Router::new()
         .route("/login", axum::routing::post(auth_handler::login_handler))
         .route("/register", axum::routing::post(auth_handler::register_handler))
         .route("/refresh", axum::routing::post(auth_handler::refresh_token_handler))
+        .route("/magic-link", axum::routing::post(auth_handler::magic_link_handler))
+        .route("/magic-link/consume", axum::routing::post(auth_handler::consume_magic_link_handler))
         .route("/forgot-password", axum::routing::post(auth_handler::forgot_password_handler))
         .route("/reset-password", axum::routing::post(auth_handler::reset_password_handler))
+        .route("/verify-email", axum::routing::post(auth_handler::verify_email_handler))
+        .route("/2fa/verify", axum::routing::post(mfa_handler::verify_login_handler))
+        .route("/passkey/options", axum::routing::post(webauthn_handler::login_options_handler))
+        .route("/passkey/login", axum::routing::post(webauthn_handler::login_handler))
+        .route("/oidc/{provider}/authorize", axum::routing::post(oidc_handler::login_authorization_handler))
+        .route("/oidc/{provider}/callback", axum::routing::post(oidc_handler::login_callback_handler))
+        .merge(resend_verification_routes)
         .layer(from_fn_with_state(strict_limiter, rate_limit_middleware));
 
-    let user_routes = Router::new()
+    // Scoped tokens (OAuth, API keys) can reach the profile; everything else needs a
+    // first-party session.
+    let profile_routes = Router::new()
         .route("/profile", axum::routing::get(user_handler::get_user_profile_handler))
         .route("/profile", axum::routing::put(user_handler::update_user_profile_handler));
 
+    let user_routes = Router::new()
+        .route("/2fa/totp", axum::routing::post(mfa_handler::enroll_totp_handler))
+        .route("/2fa/totp/confirm", axum::routing::post(mfa_handler::confirm_totp_handler))
+        .route("/2fa/recovery-codes", axum::routing::post(mfa_handler::regenerate_recovery_codes_handler))
+        .route("/2fa/disable", axum::routing::post(mfa_handler::disable_totp_handler))
+        .route("/passkeys", axum::routing::get(webauthn_handler::list_passkeys_handler))
+        .route("/passkeys", axum::routing::post(webauthn_handler::register_passkey_handler))
+        .route("/passkeys/options", axum::routing::post(webauthn_handler::registration_options_handler))
+        .route("/passkeys/{id}", axum::routing::delete(webauthn_handler::delete_passkey_handler))
+        .route("/identities", axum::routing::get(oidc_handler::list_identities_handler))
+        .route("/identities/{provider}", axum::routing::post(oidc_handler::link_identity_handler))
+        .route("/identities/{provider}", axum::routing::delete(oidc_handler::unlink_identity_handler))
+        .route("/identities/{provider}/authorize", axum::routing::post(oidc_handler::link_authorization_handler))
+        .route("/oauth-clients", axum::routing::get(oauth_handler::list_clients_handler))
+        .route("/oauth-clients", axum::routing::post(oauth_handler::register_client_handler))
+        .route("/oauth-clients/{id}", axum::routing::delete(oauth_handler::delete_client_handler))
+        .route("/oauth-consents", axum::routing::get(oauth_handler::list_consents_handler))
+        .route("/oauth-consents/{id}", axum::routing::delete(oauth_handler::revoke_consent_handler))
+        .route("/sessions", axum::routing::get(auth_handler::list_sessions_handler))
+        .route("/sessions/revoke-others", axum::routing::post(auth_handler::revoke_other_sessions_handler))
+        .route("/sessions/{id}", axum::routing::delete(auth_handler::revoke_session_handler))
+        .route("/api-keys", axum::routing::get(api_key_handler::list_api_keys_handler))
+        .route("/api-keys", axum::routing::post(api_key_handler::create_api_key_handler))
+        .route("/api-keys/{id}", axum::routing::delete(api_key_handler::revoke_api_key_handler))
+        .route_layer(from_fn(reject_scoped_tokens))
+        .merge(profile_routes);
+
+    let role_routes = Router::new()
+        .route("/roles", axum::routing::get(rbac_handler::list_roles_handler))
+        .route("/users/{id}/roles", axum::routing::get(rbac_handler::list_user_roles_handler))
+        .route(
+            "/users/{id}/roles/{role}",
+            axum::routing::put(rbac_handler::assign_role_handler).delete(rbac_handler::remove_role_handler),
+        )
+        .route_layer(from_fn_with_state(app_state.clone(), require_permission::<RolesManage>));
+
+    // User administration and the audit log check their permission in each handler.
+    let admin_routes = Router::new()
+        .route(
+            "/users",
+            axum::routing::get(admin_handler::list_users_handler).post(admin_handler::create_user_handler),
+        )
+        .route("/users/{id}", axum::routing::get(admin_handler::get_user_handler))
+        .route("/users/{id}/deactivate", axum::routing::post(admin_handler::deactivate_user_handler))
+        .route("/users/{id}/reactivate", axum::routing::post(admin_handler::reactivate_user_handler))
+        .route("/users/{id}/password-reset", axum::routing::post(admin_handler::force_password_reset_handler))
+        .route("/users/{id}/sessions", axum::routing::delete(admin_handler::revoke_sessions_handler))
+        .route("/users/{id}/sessions/{session_id}", axum::routing::delete(admin_handler::revoke_session_handler))
+        .route("/audit-events", axum::routing::get(audit_handler::list_audit_events_handler))
+        .merge(role_routes)
+        .route_layer(from_fn(reject_scoped_tokens));
+
+    // Routes under `/organizations/current` act on the organization in the
+    // `org_id` claim through the `Tenant` extractor.
+    let organization_routes = Router::new()
+        .route(
+            "/organizations",
+            axum::routing::get(organization_handler::list_organizations_handler)
+                .post(organization_handler::create_organization_handler),
+        )
+        .route("/organizations/current", axum::routing::get(organization_handler::get_organization_handler))
+        .route("/organizations/current/members", axum::routing::get(organization_handler::list_members_handler))
+        .route(
+            "/organizations/current/members/{user_id}",
+            axum::routing::put(organization_handler::update_member_handler)
+                .delete(organization_handler::remove_member_handler),
+        )
+        .route(
+            "/organizations/current/invitations",
+            axum::routing::get(organization_handler::list_invitations_handler)
+                .post(organization_handler::create_invitation_handler),
+        )
+        .route(
+            "/organizations/current/invitations/{id}",
+            axum::routing::delete(organization_handler::revoke_invitation_handler),
+        )
+        .route("/organizations/{id}/activate", axum::routing::post(organization_handler::activate_organization_handler))
+        .route("/invitations/accept", axum::routing::post(organization_handler::accept_invitation_handler))
+        .route("/invitations/decline", axum::routing::post(organization_handler::decline_invitation_handler))
+        .route_layer(from_fn(reject_scoped_tokens));
+
     let protected_routes = Router::new()
-        .nest("/user", user_routes)
         .route("/logout", axum::routing::post(auth_handler::logout_handler))
-        .layer(from_fn_with_state(app_state.pool.clone(), auth_middleware))
+        .route("/logout-all", axum::routing::post(auth_handler::logout_everywhere_handler))
+        .route_layer(from_fn(reject_scoped_tokens))
+        .merge(organization_routes)
+        .nest("/user", user_routes)
+        .nest("/admin", admin_routes)
+        .layer(from_fn_with_state(app_state.clone(), require_verified_email))
+        .layer(from_fn(db_context_middleware))
+        .layer(from_fn_with_state(app_state.clone(), auth_middleware))
         .layer(from_fn_with_state(normal_limiter, rate_limit_middleware));
 
+    let oauth_authorize_routes = Router::new()
+        .route(
+            "/authorize",
+            axum::routing::get(oauth_handler::authorize_handler).post(oauth_handler::consent_handler),
+        )
+        .route_layer(from_fn(reject_scoped_tokens))
+        .layer(from_fn(db_context_middleware))
+        .layer(from_fn_with_state(app_state.clone(), auth_middleware));
+
+    let oauth_routes = Router::new()
+        .route("/token", axum::routing::post(oauth_handler::token_handler))
+        .route("/introspect", axum::routing::post(oauth_handler::introspect_handler))
+        .route("/revoke", axum::routing::post(oauth_handler::revoke_handler))
+        .merge(oauth_authorize_routes)
+        .layer(from_fn_with_state(oauth_limiter, rate_limit_middleware));
+
     Router::new()
         .route("/health", axum::routing::get(health_handler::health_check_handler))
         .route("/ready", axum::routing::get(health_handler::readiness_check_handler))
+        .route("/.well-known/jwks.json", axum::routing::get(auth_handler::jwks_handler))
         .nest("/auth", auth_routes)
         .nest("/api", protected_routes)
+        .nest("/oauth", oauth_routes)
+        .layer(from_fn_with_state(app_state.auth_cookies.clone(), csrf_middleware))
         .layer(from_fn(error_handling_middleware))
         .layer(CookieManagerLayer::new())
         .layer(cors)
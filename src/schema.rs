@@ -1,12 +1,184 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit_events (id) {
+        id -> Uuid,
+        occurred_at -> Timestamptz,
+        actor_id -> Nullable<Uuid>,
+        #[max_length = 64]
+        action -> Varchar,
+        #[max_length = 64]
+        target_type -> Nullable<Varchar>,
+        #[max_length = 255]
+        target_id -> Nullable<Varchar>,
+        #[max_length = 45]
+        ip_address -> Nullable<Varchar>,
+        user_agent -> Nullable<Text>,
+        #[max_length = 16]
+        outcome -> Varchar,
+        payload -> Jsonb,
+    }
+}
+
+diesel::table! {
+    api_keys (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 32]
+        prefix -> Varchar,
+        key_hash -> Text,
+        scopes -> Array<Text>,
+        expires_at -> Nullable<Timestamp>,
+        last_used_at -> Nullable<Timestamp>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    invitations (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        #[max_length = 255]
+        email -> Varchar,
+        #[max_length = 32]
+        role -> Varchar,
+        token_hash -> Text,
+        invited_by -> Nullable<Uuid>,
+        expires_at -> Timestamp,
+        accepted_at -> Nullable<Timestamp>,
+        declined_at -> Nullable<Timestamp>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    memberships (organization_id, user_id) {
+        organization_id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 32]
+        role -> Varchar,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    mfa_challenge_failures (token_jti) {
+        token_jti -> Text,
+        user_id -> Uuid,
+        failed_attempts -> Int4,
+        expires_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    mfa_recovery_codes (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        code_hash -> Text,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    oauth_authorization_codes (id) {
+        id -> Uuid,
+        code_hash -> Text,
+        client_id -> Uuid,
+        user_id -> Uuid,
+        redirect_uri -> Text,
+        scope -> Text,
+        code_challenge -> Text,
+        expires_at -> Timestamp,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    oauth_clients (id) {
+        id -> Uuid,
+        #[max_length = 64]
+        client_id -> Varchar,
+        client_secret_hash -> Nullable<Text>,
+        #[max_length = 255]
+        name -> Varchar,
+        redirect_uris -> Array<Text>,
+        scopes -> Array<Text>,
+        grant_types -> Array<Text>,
+        is_first_party -> Bool,
+        owner_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    oauth_consents (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        client_id -> Uuid,
+        scope -> Text,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    oidc_login_states (id) {
+        id -> Uuid,
+        state -> Text,
+        #[max_length = 50]
+        provider -> Varchar,
+        code_verifier -> Text,
+        nonce -> Text,
+        user_id -> Nullable<Uuid>,
+        expires_at -> Timestamp,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    organizations (id) {
+        id -> Uuid,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 64]
+        slug -> Varchar,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    permissions (id) {
+        id -> Uuid,
+        #[max_length = 128]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     refresh_tokens (id) {
         id -> Uuid,
         user_id -> Uuid,
-        token -> Text,
+        token_hash -> Text,
         expires_at -> Timestamp,
         created_at -> Nullable<Timestamptz>,
+        family_id -> Uuid,
+        parent_id -> Nullable<Uuid>,
+        rotated_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+        client_id -> Nullable<Uuid>,
+        scope -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+        #[max_length = 45]
+        ip_address -> Nullable<Varchar>,
+        #[max_length = 255]
+        device_label -> Nullable<Varchar>,
+        session_started_at -> Timestamptz,
     }
 }
 
@@ -14,12 +186,51 @@ diesel::table! {
     revoked_tokens (id) {
         id -> Uuid,
         token_jti -> Text,
-        user_id -> Uuid,
+        user_id -> Nullable<Uuid>,
         expiry -> Timestamp,
         created_at -> Nullable<Timestamptz>,
     }
 }
 
+diesel::table! {
+    role_permissions (role_id, permission_id) {
+        role_id -> Uuid,
+        permission_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    roles (id) {
+        id -> Uuid,
+        #[max_length = 64]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    user_identities (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 50]
+        provider -> Varchar,
+        subject -> Text,
+        #[max_length = 255]
+        email -> Nullable<Varchar>,
+        created_at -> Nullable<Timestamptz>,
+        last_login_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    user_roles (user_id, role_id) {
+        user_id -> Uuid,
+        role_id -> Uuid,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Uuid,
@@ -32,20 +243,96 @@ diesel::table! {
         password_hash -> Text,
         is_active -> Nullable<Bool>,
         is_verified -> Nullable<Bool>,
-        verification_token -> Nullable<Text>,
+        verification_token_hash -> Nullable<Text>,
         verification_token_expires -> Nullable<Timestamp>,
-        password_reset_token -> Nullable<Text>,
+        password_reset_token_hash -> Nullable<Text>,
         password_reset_expires -> Nullable<Timestamp>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
+        #[max_length = 35]
+        locale -> Nullable<Varchar>,
+        tokens_valid_after -> Nullable<Timestamp>,
+        totp_secret -> Nullable<Text>,
+        totp_enabled_at -> Nullable<Timestamp>,
+        totp_last_used_step -> Nullable<Int8>,
+        magic_link_token_hash -> Nullable<Text>,
+        magic_link_expires -> Nullable<Timestamp>,
+        active_organization_id -> Nullable<Uuid>,
+        verification_sent_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    webauthn_challenges (id) {
+        id -> Uuid,
+        user_id -> Nullable<Uuid>,
+        challenge -> Text,
+        #[max_length = 20]
+        ceremony -> Varchar,
+        expires_at -> Timestamp,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    webauthn_credentials (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        credential_id -> Text,
+        public_key -> Bytea,
+        sign_count -> Int8,
+        #[max_length = 255]
+        name -> Nullable<Varchar>,
+        created_at -> Nullable<Timestamptz>,
+        last_used_at -> Nullable<Timestamp>,
     }
 }
 
+diesel::joinable!(api_keys -> users (user_id));
+diesel::joinable!(invitations -> organizations (organization_id));
+diesel::joinable!(invitations -> users (invited_by));
+diesel::joinable!(memberships -> organizations (organization_id));
+diesel::joinable!(memberships -> users (user_id));
+diesel::joinable!(mfa_challenge_failures -> users (user_id));
+diesel::joinable!(mfa_recovery_codes -> users (user_id));
+diesel::joinable!(oauth_authorization_codes -> oauth_clients (client_id));
+diesel::joinable!(oauth_authorization_codes -> users (user_id));
+diesel::joinable!(oauth_clients -> users (owner_id));
+diesel::joinable!(oauth_consents -> oauth_clients (client_id));
+diesel::joinable!(oauth_consents -> users (user_id));
+diesel::joinable!(oidc_login_states -> users (user_id));
+diesel::joinable!(refresh_tokens -> oauth_clients (client_id));
 diesel::joinable!(refresh_tokens -> users (user_id));
 diesel::joinable!(revoked_tokens -> users (user_id));
+diesel::joinable!(role_permissions -> permissions (permission_id));
+diesel::joinable!(role_permissions -> roles (role_id));
+diesel::joinable!(user_identities -> users (user_id));
+diesel::joinable!(user_roles -> roles (role_id));
+diesel::joinable!(user_roles -> users (user_id));
+diesel::joinable!(users -> organizations (active_organization_id));
+diesel::joinable!(webauthn_challenges -> users (user_id));
+diesel::joinable!(webauthn_credentials -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_keys,
+    audit_events,
+    invitations,
+    memberships,
+    mfa_challenge_failures,
+    mfa_recovery_codes,
+    oauth_authorization_codes,
+    oauth_clients,
+    oauth_consents,
+    oidc_login_states,
+    organizations,
+    permissions,
     refresh_tokens,
     revoked_tokens,
+    role_permissions,
+    roles,
+    user_identities,
+    user_roles,
     users,
+    webauthn_challenges,
+    webauthn_credentials,
 );
@@ -13,6 +13,9 @@ pub struct UpdateUserProfileRequest {
 
     #[validate(email(message = "Invalid email format"))]
     pub email: Option<String>,
+
+    #[validate(length(min = 2, max = 35, message = "Locale must be between 2 and 35 characters"))]
+    pub locale: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -23,6 +26,8 @@ pub struct UserProfileResponse {
     pub email: String,
     pub is_active: bool,
     pub is_verified: bool,
+    pub two_factor_enabled: bool,
+    pub locale: Option<String>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -1,6 +1,6 @@
 use crate::{
     app::AppState,
-    auth::auth_middleware::AuthUser,
+    auth::auth_middleware::{AuthUser, ClientInfo},
     errors::ApiResult,
     user::{
         user_dto::{UpdateUserProfileRequest, UserProfileResponse},
@@ -10,28 +10,33 @@ use crate::{
 use axum::{
     extract::State,
     response::Json,
-    Extension,
 };
 use validator::Validate;
 
 pub async fn get_user_profile_handler(
     State(state): State<AppState>,
-    Extension(authenticated_user): Extension<AuthUser>,
+    authenticated_user: AuthUser,
 ) -> ApiResult<Json<UserProfileResponse>> {
-    let profile = user_service::get_user_profile(authenticated_user.0, state.pool).await?;
+    authenticated_user.require_scope("profile:read")?;
+    let profile = user_service::get_user_profile(authenticated_user.id, state.pool).await?;
     Ok(Json(profile))
 }
 
 pub async fn update_user_profile_handler(
     State(state): State<AppState>,
-    Extension(authenticated_user): Extension<AuthUser>,
+    authenticated_user: AuthUser,
+    client: ClientInfo,
     Json(payload): Json<UpdateUserProfileRequest>,
 ) -> ApiResult<Json<UserProfileResponse>> {
+    authenticated_user.require_scope("profile:write")?;
     payload.validate()?;
     let profile = user_service::update_user_profile(
-        authenticated_user.0,
+        authenticated_user.id,
         payload,
+        client,
         state.pool,
+        &state.email_verification,
+        &state.mailer,
     ).await?;
     Ok(Json(profile))
 }
@@ -1,5 +1,6 @@
 use crate::db::models::user::{User, NewUser};
-use crate::schema::users::dsl::*;
+use crate::schema::users::{self, dsl::*};
+use diesel::pg::Pg;
 use diesel::prelude::*;
 use uuid::Uuid;
 
@@ -18,6 +19,78 @@ pub fn find_user_by_id(user_id: Uuid, conn: &mut PgConnection) -> QueryResult<Us
         .first(conn)
 }
 
+// Unlike `find_user_by_id`, this also finds deactivated users; meant for
+// administration only.
+pub fn find_any_user_by_id(user_id: Uuid, conn: &mut PgConnection) -> QueryResult<User> {
+    users
+        .filter(id.eq(user_id))
+        .select(User::as_select())
+        .first(conn)
+}
+
+pub fn email_exists(user_email: &str, conn: &mut PgConnection) -> QueryResult<bool> {
+    diesel::select(diesel::dsl::exists(users.filter(email.eq(user_email)))).get_result(conn)
+}
+
+fn search_filter<'a>(search: Option<&'a str>, active: Option<bool>) -> users::BoxedQuery<'a, Pg> {
+    let mut query = users.into_boxed();
+
+    if let Some(search) = search {
+        let pattern = format!(
+            "%{}%",
+            search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        query = query.filter(
+            email
+                .ilike(pattern.clone())
+                .or(first_name.ilike(pattern.clone()))
+                .or(last_name.ilike(pattern)),
+        );
+    }
+    if let Some(active) = active {
+        query = query.filter(is_active.eq(active));
+    }
+
+    query
+}
+
+pub fn search_users(
+    search: Option<&str>,
+    active: Option<bool>,
+    limit: i64,
+    offset: i64,
+    conn: &mut PgConnection,
+) -> QueryResult<Vec<User>> {
+    search_filter(search, active)
+        .order((created_at.desc(), id))
+        .limit(limit)
+        .offset(offset)
+        .select(User::as_select())
+        .load(conn)
+}
+
+pub fn count_users(search: Option<&str>, active: Option<bool>, conn: &mut PgConnection) -> QueryResult<i64> {
+    search_filter(search, active).count().get_result(conn)
+}
+
+pub fn set_user_active(user_id: Uuid, active: bool, conn: &mut PgConnection) -> QueryResult<User> {
+    diesel::update(users.filter(id.eq(user_id)))
+        .set(is_active.eq(Some(active)))
+        .returning(User::as_returning())
+        .get_result(conn)
+}
+
+pub fn set_active_organization(
+    user_id: Uuid,
+    organization_id: Option<Uuid>,
+    conn: &mut PgConnection,
+) -> QueryResult<User> {
+    diesel::update(users.filter(id.eq(user_id)))
+        .set(active_organization_id.eq(organization_id))
+        .returning(User::as_returning())
+        .get_result(conn)
+}
+
 pub fn find_user_by_email(user_email: &str, conn: &mut PgConnection) -> QueryResult<User> {
     users
         .filter(email.eq(user_email))
@@ -33,10 +106,105 @@ pub fn update_user(user_id: Uuid, user_data: &User, conn: &mut PgConnection) ->
         .get_result(conn)
 }
 
-pub fn find_user_by_reset_token(token: &str, conn: &mut PgConnection) -> QueryResult<User> {
+pub fn find_user_by_reset_token(token_hash: &str, conn: &mut PgConnection) -> QueryResult<User> {
+    users
+        .filter(password_reset_token_hash.eq(token_hash))
+        .filter(is_active.eq(true))
+        .select(User::as_select())
+        .first(conn)
+}
+
+pub fn update_password(
+    user_id: Uuid,
+    new_password_hash: &str,
+    conn: &mut PgConnection,
+) -> QueryResult<User> {
+    diesel::update(users.filter(id.eq(user_id)))
+        .set((
+            password_hash.eq(new_password_hash),
+            password_reset_token_hash.eq(None::<String>),
+            password_reset_expires.eq(None::<chrono::NaiveDateTime>),
+        ))
+        .returning(User::as_returning())
+        .get_result(conn)
+}
+
+pub fn replace_password_hash(
+    user_id: Uuid,
+    current_hash: &str,
+    new_password_hash: &str,
+    conn: &mut PgConnection,
+) -> QueryResult<usize> {
+    diesel::update(users.filter(id.eq(user_id)).filter(password_hash.eq(current_hash)))
+        .set(password_hash.eq(new_password_hash))
+        .execute(conn)
+}
+
+pub fn find_tokens_valid_after(user_id: Uuid, conn: &mut PgConnection) -> QueryResult<Option<chrono::NaiveDateTime>> {
     users
-        .filter(password_reset_token.eq(token))
+        .filter(id.eq(user_id))
+        .select(tokens_valid_after)
+        .first(conn)
+}
+
+pub fn set_tokens_valid_after(
+    user_id: Uuid,
+    valid_after: chrono::NaiveDateTime,
+    conn: &mut PgConnection,
+) -> QueryResult<usize> {
+    diesel::update(users.filter(id.eq(user_id)))
+        .set(tokens_valid_after.eq(Some(valid_after)))
+        .execute(conn)
+}
+
+pub fn find_user_by_verification_token(token_hash: &str, conn: &mut PgConnection) -> QueryResult<User> {
+    users
+        .filter(verification_token_hash.eq(token_hash))
         .filter(is_active.eq(true))
         .select(User::as_select())
         .first(conn)
 }
+
+pub fn mark_user_verified(user_id: Uuid, conn: &mut PgConnection) -> QueryResult<User> {
+    diesel::update(users.filter(id.eq(user_id)))
+        .set((
+            is_verified.eq(Some(true)),
+            verification_token_hash.eq(None::<String>),
+            verification_token_expires.eq(None::<chrono::NaiveDateTime>),
+        ))
+        .returning(User::as_returning())
+        .get_result(conn)
+}
+
+pub fn set_magic_link_token(
+    user_id: Uuid,
+    token_hash: &str,
+    expires: chrono::NaiveDateTime,
+    conn: &mut PgConnection,
+) -> QueryResult<usize> {
+    diesel::update(users.filter(id.eq(user_id)))
+        .set((
+            magic_link_token_hash.eq(Some(token_hash)),
+            magic_link_expires.eq(Some(expires)),
+        ))
+        .execute(conn)
+}
+
+// Clears the token in the same statement that finds it, so a link can only be
+// used once. Following the link proves ownership of the address.
+pub fn consume_magic_link_token(token_hash: &str, conn: &mut PgConnection) -> QueryResult<Option<User>> {
+    diesel::update(
+        users
+            .filter(magic_link_token_hash.eq(token_hash))
+            .filter(magic_link_expires.gt(chrono::Utc::now().naive_utc()))
+            .filter(is_active.eq(true)),
+    )
+    .set((
+        magic_link_token_hash.eq(None::<String>),
+        magic_link_expires.eq(None::<chrono::NaiveDateTime>),
+        is_verified.eq(Some(true)),
+    ))
+    .returning(User::as_returning())
+    .get_result(conn)
+    .optional()
+}
@@ -1,18 +1,21 @@
 use crate::{
-    config::database::DbPool,
+    audit::audit_service::{self, AuditEntry, AuditLog},
+    auth::{auth_middleware::ClientInfo, auth_service},
+    config::{database::DbPool, email_verification::EmailVerificationConfig},
     errors::{ApiError, ApiResult},
+    mail::{mailer::Mailer, mail_notifications},
     user::{
         user_dto::{UpdateUserProfileRequest, UserProfileResponse},
         user_repository,
     },
-    db::models::user::User,
+    db::{models::user::User, with_connection},
 };
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
 use uuid::Uuid;
 
 pub async fn get_user_profile(user_id: Uuid, pool: DbPool) -> ApiResult<UserProfileResponse> {
-    let mut conn = pool.get().map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-    let user = user_repository::find_user_by_id(user_id, &mut conn)?;
+    let user = with_connection(&pool, move |conn| Ok(user_repository::find_user_by_id(user_id, conn)?)).await?;
 
     Ok(user_to_profile_response(user))
 }
@@ -20,30 +23,97 @@ pub async fn get_user_profile(user_id: Uuid, pool: DbPool) -> ApiResult<UserProf
 pub async fn update_user_profile(
     user_id: Uuid,
     request: UpdateUserProfileRequest,
+    client: ClientInfo,
     pool: DbPool,
+    verification: &EmailVerificationConfig,
+    mailer: &Arc<dyn Mailer>,
 ) -> ApiResult<UserProfileResponse> {
-    let mut conn = pool.get().map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    let audit = AuditLog::new(&pool, &client);
+    let requested_fields: Vec<&str> = [
+        ("first_name", request.first_name.is_some()),
+        ("last_name", request.last_name.is_some()),
+        ("email", request.email.is_some()),
+        ("locale", request.locale.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(field, present)| present.then_some(field))
+    .collect();
 
-    let mut user = user_repository::find_user_by_id(user_id, &mut conn)?;
+    let verification = verification.clone();
+    let result = with_connection(&pool, move |conn| {
+        let original = user_repository::find_user_by_id(user_id, conn)?;
+        let mut user = original.clone();
 
-    if let Some(first_name) = request.first_name {
-        user.first_name = first_name;
-    }
-    if let Some(last_name) = request.last_name {
-        user.last_name = last_name;
-    }
-    if let Some(email) = request.email {
-        if let Ok(_existing_user) = user_repository::find_user_by_email(&email, &mut conn) {
-            return Err(ApiError::ResourceAlreadyExists("Email already in use".to_string()));
+        if let Some(first_name) = request.first_name {
+            user.first_name = first_name;
         }
-        user.email = email;
-    }
+        if let Some(last_name) = request.last_name {
+            user.last_name = last_name;
+        }
+        if let Some(email) = request.email {
+            if let Ok(_existing_user) = user_repository::find_user_by_email(&email, conn) {
+                return Err(ApiError::ResourceAlreadyExists("Email already in use".to_string()));
+            }
+            user.email = email;
+        }
+        if let Some(locale) = request.locale {
+            user.locale = Some(locale);
+        }
+
+        // A new address has to be verified again before it is trusted.
+        let email_changed = user.email != original.email;
+        if email_changed {
+            user.is_verified = Some(false);
+        }
+
+        let updated_user = user_repository::update_user(user_id, &user, conn)?;
+        let (updated_user, verification_token) = if email_changed {
+            let (updated_user, token) = auth_service::issue_verification_token(updated_user, &verification, conn)?;
+            (updated_user, Some(token))
+        } else {
+            (updated_user, None)
+        };
 
-    let updated_user = user_repository::update_user(user_id, &user, &mut conn)?;
+        Ok((original, updated_user, verification_token))
+    })
+    .await;
+
+    let entry = AuditEntry::from_result(audit_service::PROFILE_UPDATE, &result)
+        .actor(Some(user_id))
+        .target_user(Some(user_id));
+    let entry = match &result {
+        Ok((original, updated_user, _)) => entry.with("changes", profile_changes(original, updated_user)),
+        Err(_) => entry.with("fields", requested_fields),
+    };
+    audit.record(entry).await;
+
+    let (_, updated_user, verification_token) = result?;
+    if let Some(verification_token) = verification_token {
+        mail_notifications::send_verification_email(mailer, &updated_user, &verification_token);
+    }
 
     Ok(user_to_profile_response(updated_user))
 }
 
+// `{field: {from, to}}` for every profile field the update changed.
+fn profile_changes(before: &User, after: &User) -> Value {
+    let mut changes = Map::new();
+    let fields = [
+        ("first_name", json!(before.first_name), json!(after.first_name)),
+        ("last_name", json!(before.last_name), json!(after.last_name)),
+        ("email", json!(before.email), json!(after.email)),
+        ("locale", json!(before.locale), json!(after.locale)),
+    ];
+
+    for (field, from, to) in fields {
+        if from != to {
+            changes.insert(field.to_string(), json!({ "from": from, "to": to }));
+        }
+    }
+
+    Value::Object(changes)
+}
+
 fn user_to_profile_response(user: User) -> UserProfileResponse {
     UserProfileResponse {
         id: user.id,
@@ -52,6 +122,8 @@ fn user_to_profile_response(user: User) -> UserProfileResponse {
         email: user.email,
         is_active: user.is_active.unwrap_or(true),
         is_verified: user.is_verified.unwrap_or(false),
+        two_factor_enabled: user.totp_enabled_at.is_some(),
+        locale: user.locale,
         created_at: user.created_at,
         updated_at: user.updated_at,
     }
@@ -0,0 +1,5 @@
+pub mod webauthn_handler;
+pub mod webauthn_service;
+pub mod webauthn_repository;
+pub mod webauthn_dto;
+pub mod webauthn_ceremony;
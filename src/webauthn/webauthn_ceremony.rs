@@ -0,0 +1,211 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ciborium::Value;
+use ring::signature::{self, RsaPublicKeyComponents, UnparsedPublicKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+pub const COSE_ALG_ES256: i64 = -7;
+pub const COSE_ALG_EDDSA: i64 = -8;
+pub const COSE_ALG_RS256: i64 = -257;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+#[derive(Debug, Deserialize)]
+pub struct ClientData {
+    #[serde(rename = "type")]
+    pub ceremony_type: String,
+    pub challenge: String,
+    pub origin: String,
+}
+
+#[derive(Debug)]
+pub struct AuthenticatorData {
+    pub rp_id_hash: Vec<u8>,
+    pub flags: u8,
+    pub sign_count: u32,
+    pub attested_credential: Option<AttestedCredential>,
+}
+
+#[derive(Debug)]
+pub struct AttestedCredential {
+    pub credential_id: Vec<u8>,
+    pub public_key: Vec<u8>,
+}
+
+impl AuthenticatorData {
+    pub fn user_present(&self) -> bool {
+        self.flags & FLAG_USER_PRESENT != 0
+    }
+
+    pub fn user_verified(&self) -> bool {
+        self.flags & FLAG_USER_VERIFIED != 0
+    }
+
+    pub fn matches_rp_id(&self, rp_id: &str) -> bool {
+        self.rp_id_hash == Sha256::digest(rp_id.as_bytes()).as_slice()
+    }
+}
+
+enum PublicKey {
+    Es256 { point: Vec<u8> },
+    EdDsa { key: Vec<u8> },
+    Rs256 { n: Vec<u8>, e: Vec<u8> },
+}
+
+pub fn encode(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// Clients differ on whether they pad base64url values.
+pub fn decode(value: &str) -> Result<Vec<u8>, String> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|e| format!("Invalid base64url value: {}", e))
+}
+
+pub fn parse_client_data(client_data_json: &[u8]) -> Result<ClientData, String> {
+    serde_json::from_slice(client_data_json).map_err(|e| format!("Invalid client data: {}", e))
+}
+
+// Attestation statements are not verified; the relying party requests
+// `attestation: "none"` and only needs the authenticator data.
+pub fn parse_attestation_object(attestation_object: &[u8]) -> Result<AuthenticatorData, String> {
+    let value: Value = ciborium::de::from_reader(attestation_object)
+        .map_err(|e| format!("Invalid attestation object: {}", e))?;
+
+    let auth_data = value
+        .as_map()
+        .and_then(|entries| {
+            entries
+                .iter()
+                .find(|(key, _)| key.as_text() == Some("authData"))
+                .and_then(|(_, value)| value.as_bytes())
+        })
+        .ok_or_else(|| "Attestation object has no authenticator data".to_string())?;
+
+    parse_authenticator_data(auth_data)
+}
+
+pub fn parse_authenticator_data(bytes: &[u8]) -> Result<AuthenticatorData, String> {
+    if bytes.len() < 37 {
+        return Err("Authenticator data is too short".to_string());
+    }
+
+    let flags = bytes[32];
+    let sign_count = u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]);
+
+    let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+        Some(parse_attested_credential(&bytes[37..])?)
+    } else {
+        None
+    };
+
+    Ok(AuthenticatorData {
+        rp_id_hash: bytes[..32].to_vec(),
+        flags,
+        sign_count,
+        attested_credential,
+    })
+}
+
+// Checks that a COSE key uses an algorithm this server can verify.
+pub fn validate_public_key(cose_key: &[u8]) -> Result<(), String> {
+    parse_public_key(cose_key).map(|_| ())
+}
+
+// Assertion signatures cover the authenticator data followed by the SHA-256
+// of the client data JSON.
+pub fn verify_assertion(
+    cose_key: &[u8],
+    authenticator_data: &[u8],
+    client_data_json: &[u8],
+    signature: &[u8],
+) -> Result<bool, String> {
+    let mut message = authenticator_data.to_vec();
+    message.extend_from_slice(&Sha256::digest(client_data_json));
+
+    let verified = match parse_public_key(cose_key)? {
+        PublicKey::Es256 { point } => UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_ASN1, point)
+            .verify(&message, signature)
+            .is_ok(),
+        PublicKey::EdDsa { key } => UnparsedPublicKey::new(&signature::ED25519, key)
+            .verify(&message, signature)
+            .is_ok(),
+        PublicKey::Rs256 { n, e } => RsaPublicKeyComponents { n, e }
+            .verify(&signature::RSA_PKCS1_2048_8192_SHA256, &message, signature)
+            .is_ok(),
+    };
+
+    Ok(verified)
+}
+
+fn parse_attested_credential(bytes: &[u8]) -> Result<AttestedCredential, String> {
+    // 16-byte AAGUID, then a big-endian length-prefixed credential id.
+    if bytes.len() < 18 {
+        return Err("Attested credential data is too short".to_string());
+    }
+
+    let id_length = u16::from_be_bytes([bytes[16], bytes[17]]) as usize;
+    let key_start = 18 + id_length;
+    if bytes.len() <= key_start {
+        return Err("Attested credential data is too short".to_string());
+    }
+
+    // The COSE key is followed by optional extension data, so its length is
+    // only known after decoding it.
+    let mut remaining = &bytes[key_start..];
+    let _: Value = ciborium::de::from_reader(&mut remaining)
+        .map_err(|e| format!("Invalid credential public key: {}", e))?;
+    let key_end = bytes.len() - remaining.len();
+
+    Ok(AttestedCredential {
+        credential_id: bytes[18..key_start].to_vec(),
+        public_key: bytes[key_start..key_end].to_vec(),
+    })
+}
+
+fn parse_public_key(cose_key: &[u8]) -> Result<PublicKey, String> {
+    let value: Value =
+        ciborium::de::from_reader(cose_key).map_err(|e| format!("Invalid credential public key: {}", e))?;
+    let entries = value
+        .as_map()
+        .ok_or_else(|| "Credential public key is not a COSE key".to_string())?;
+
+    let field = |label: i64| {
+        entries
+            .iter()
+            .find(|(key, _)| key.as_integer().map(i128::from) == Some(label.into()))
+            .map(|(_, value)| value)
+    };
+    let integer = |label: i64| field(label).and_then(Value::as_integer).map(i128::from);
+    let bytes = |label: i64| field(label).and_then(Value::as_bytes).cloned();
+
+    // kty (1), alg (3), then key type specific parameters.
+    match (integer(1), integer(3).map(|alg| alg as i64)) {
+        (Some(2), Some(COSE_ALG_ES256)) if integer(-1) == Some(1) => {
+            let (Some(x), Some(y)) = (bytes(-2), bytes(-3)) else {
+                return Err("EC2 key is missing coordinates".to_string());
+            };
+            if x.len() != 32 || y.len() != 32 {
+                return Err("EC2 key has invalid coordinates".to_string());
+            }
+
+            let mut point = vec![0x04];
+            point.extend_from_slice(&x);
+            point.extend_from_slice(&y);
+            Ok(PublicKey::Es256 { point })
+        }
+        (Some(1), Some(COSE_ALG_EDDSA)) if integer(-1) == Some(6) => match bytes(-2) {
+            Some(key) if key.len() == 32 => Ok(PublicKey::EdDsa { key }),
+            _ => Err("OKP key has an invalid public key".to_string()),
+        },
+        (Some(3), Some(COSE_ALG_RS256)) => match (bytes(-1), bytes(-2)) {
+            (Some(n), Some(e)) => Ok(PublicKey::Rs256 { n, e }),
+            _ => Err("RSA key is missing its modulus or exponent".to_string()),
+        },
+        _ => Err("Unsupported credential public key algorithm".to_string()),
+    }
+}
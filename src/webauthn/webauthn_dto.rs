@@ -0,0 +1,124 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// Request and option payloads follow the WebAuthn JSON encoding, so browsers
+// can use `PublicKeyCredential.parseCreationOptionsFromJSON` and
+// `credential.toJSON()` directly.
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationCredential {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub response: AttestationResponse,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub attestation_object: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticationCredential {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub response: AssertionResponse,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    pub user_handle: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PasskeyRegistrationRequest {
+    #[validate(length(min = 1, max = 255, message = "Name must be between 1 and 255 characters"))]
+    pub name: Option<String>,
+
+    pub credential: RegistrationCredential,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PasskeyLoginRequest {
+    pub credential: AuthenticationCredential,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationOptionsResponse {
+    pub rp: RelyingParty,
+    pub user: UserEntity,
+    pub challenge: String,
+    pub pub_key_cred_params: Vec<CredentialParameter>,
+    pub timeout: i64,
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+    pub authenticator_selection: AuthenticatorSelection,
+    pub attestation: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestOptionsResponse {
+    pub challenge: String,
+    pub timeout: i64,
+    pub rp_id: String,
+    pub allow_credentials: Vec<CredentialDescriptor>,
+    pub user_verification: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RelyingParty {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntity {
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CredentialParameter {
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub alg: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    pub resident_key: String,
+    pub require_resident_key: bool,
+    pub user_verification: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PasskeyResponse {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<NaiveDateTime>,
+}
@@ -0,0 +1,73 @@
+use crate::{
+    app::AppState,
+    auth::{auth_dto::AuthResponse, auth_cookies, auth_middleware::{AuthUser, ClientInfo}},
+    errors::ApiResult,
+    webauthn::{
+        webauthn_dto::{
+            CreationOptionsResponse, PasskeyLoginRequest, PasskeyRegistrationRequest, PasskeyResponse,
+            RequestOptionsResponse,
+        },
+        webauthn_service,
+    },
+};
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use serde_json::{json, Value};
+use tower_cookies::Cookies;
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn registration_options_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+) -> ApiResult<Json<CreationOptionsResponse>> {
+    let options = webauthn_service::registration_options(authenticated_user.id, state.pool, &state.webauthn).await?;
+    Ok(Json(options))
+}
+
+pub async fn register_passkey_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Json(payload): Json<PasskeyRegistrationRequest>,
+) -> ApiResult<Json<PasskeyResponse>> {
+    payload.validate()?;
+    let passkey = webauthn_service::register_passkey(authenticated_user.id, payload, state.pool, &state.webauthn).await?;
+    Ok(Json(passkey))
+}
+
+pub async fn list_passkeys_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+) -> ApiResult<Json<Vec<PasskeyResponse>>> {
+    let passkeys = webauthn_service::list_passkeys(authenticated_user.id, state.pool).await?;
+    Ok(Json(passkeys))
+}
+
+pub async fn delete_passkey_handler(
+    State(state): State<AppState>,
+    authenticated_user: AuthUser,
+    Path(passkey_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    webauthn_service::delete_passkey(authenticated_user.id, passkey_id, state.pool).await?;
+    Ok(Json(json!({
+        "message": "Passkey removed"
+    })))
+}
+
+pub async fn login_options_handler(State(state): State<AppState>) -> ApiResult<Json<RequestOptionsResponse>> {
+    let options = webauthn_service::login_options(state.pool, &state.webauthn).await?;
+    Ok(Json(options))
+}
+
+pub async fn login_handler(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    cookies: Cookies,
+    Json(payload): Json<PasskeyLoginRequest>,
+) -> ApiResult<Json<AuthResponse>> {
+    let auth_response =
+        webauthn_service::login(payload, client, state.pool, &state.webauthn, &state.email_verification).await?;
+    Ok(Json(auth_cookies::store_session(&cookies, &state.auth_cookies, auth_response)))
+}
@@ -0,0 +1,107 @@
+use crate::db::models::webauthn_challenge::{NewWebauthnChallenge, WebauthnChallenge};
+use crate::db::models::webauthn_credential::{NewWebauthnCredential, WebauthnCredential};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub fn create_challenge(new_challenge: &NewWebauthnChallenge, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::webauthn_challenges::dsl::*;
+
+    diesel::insert_into(webauthn_challenges)
+        .values(new_challenge)
+        .execute(conn)
+}
+
+// Deletes the challenge while reading it, so each one backs a single ceremony.
+pub fn take_challenge(
+    challenge_value: &str,
+    ceremony_value: &str,
+    conn: &mut PgConnection,
+) -> QueryResult<Option<WebauthnChallenge>> {
+    use crate::schema::webauthn_challenges::dsl::*;
+
+    diesel::delete(
+        webauthn_challenges
+            .filter(challenge.eq(challenge_value))
+            .filter(ceremony.eq(ceremony_value))
+            .filter(expires_at.gt(chrono::Utc::now().naive_utc())),
+    )
+    .returning(WebauthnChallenge::as_returning())
+    .get_result(conn)
+    .optional()
+}
+
+pub fn clean_expired_challenges(conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::webauthn_challenges::dsl::*;
+
+    diesel::delete(webauthn_challenges.filter(expires_at.lt(chrono::Utc::now().naive_utc())))
+        .execute(conn)
+}
+
+pub fn create_credential(
+    new_credential: &NewWebauthnCredential,
+    conn: &mut PgConnection,
+) -> QueryResult<WebauthnCredential> {
+    use crate::schema::webauthn_credentials::dsl::*;
+
+    diesel::insert_into(webauthn_credentials)
+        .values(new_credential)
+        .returning(WebauthnCredential::as_returning())
+        .get_result(conn)
+}
+
+pub fn find_credential_by_credential_id(
+    credential_id_value: &str,
+    conn: &mut PgConnection,
+) -> QueryResult<WebauthnCredential> {
+    use crate::schema::webauthn_credentials::dsl::*;
+
+    webauthn_credentials
+        .filter(credential_id.eq(credential_id_value))
+        .select(WebauthnCredential::as_select())
+        .first(conn)
+}
+
+pub fn list_credentials(user_id_val: Uuid, conn: &mut PgConnection) -> QueryResult<Vec<WebauthnCredential>> {
+    use crate::schema::webauthn_credentials::dsl::*;
+
+    webauthn_credentials
+        .filter(user_id.eq(user_id_val))
+        .order(created_at.asc())
+        .select(WebauthnCredential::as_select())
+        .load(conn)
+}
+
+pub fn delete_credential(user_id_val: Uuid, credential_uuid: Uuid, conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::webauthn_credentials::dsl::*;
+
+    diesel::delete(
+        webauthn_credentials
+            .filter(id.eq(credential_uuid))
+            .filter(user_id.eq(user_id_val)),
+    )
+    .execute(conn)
+}
+
+// Conditional on the stored counter, so two concurrent assertions carrying the
+// same counter cannot both succeed.
+pub fn record_credential_use(
+    credential_uuid: Uuid,
+    previous_count: i64,
+    new_count: i64,
+    conn: &mut PgConnection,
+) -> QueryResult<bool> {
+    use crate::schema::webauthn_credentials::dsl::*;
+
+    let updated = diesel::update(
+        webauthn_credentials
+            .filter(id.eq(credential_uuid))
+            .filter(sign_count.eq(previous_count)),
+    )
+    .set((
+        sign_count.eq(new_count),
+        last_used_at.eq(Some(chrono::Utc::now().naive_utc())),
+    ))
+    .execute(conn)?;
+
+    Ok(updated > 0)
+}
@@ -0,0 +1,341 @@
+use crate::{
+    audit::audit_service::{self, AuditEntry, AuditLog},
+    auth::{auth_dto::AuthResponse, auth_hashing::random_value, auth_middleware::ClientInfo, auth_service},
+    config::{database::DbPool, email_verification::EmailVerificationConfig, webauthn::WebauthnConfig},
+    db::{
+        models::{
+            webauthn_challenge::NewWebauthnChallenge,
+            webauthn_credential::{NewWebauthnCredential, WebauthnCredential},
+        },
+        with_connection,
+    },
+    errors::{ApiError, ApiResult},
+    user::user_repository,
+    webauthn::{
+        webauthn_ceremony::{self, ClientData, COSE_ALG_EDDSA, COSE_ALG_ES256, COSE_ALG_RS256},
+        webauthn_dto::{
+            AuthenticatorSelection, CreationOptionsResponse, CredentialDescriptor, CredentialParameter,
+            PasskeyLoginRequest, PasskeyRegistrationRequest, PasskeyResponse, RelyingParty,
+            RequestOptionsResponse, UserEntity,
+        },
+        webauthn_repository,
+    },
+};
+use chrono::Utc;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::PgConnection;
+use uuid::Uuid;
+
+const REGISTRATION: &str = "registration";
+const AUTHENTICATION: &str = "authentication";
+const PUBLIC_KEY: &str = "public-key";
+
+pub async fn registration_options(
+    user_id: Uuid,
+    pool: DbPool,
+    config: &WebauthnConfig,
+) -> ApiResult<CreationOptionsResponse> {
+    let config = config.clone();
+
+    with_connection(&pool, move |conn| {
+        let user = user_repository::find_user_by_id(user_id, conn)?;
+        let existing = webauthn_repository::list_credentials(user.id, conn)?;
+        let challenge = issue_challenge(Some(user.id), REGISTRATION, &config, conn)?;
+
+        Ok(CreationOptionsResponse {
+            rp: RelyingParty {
+                id: config.rp_id.clone(),
+                name: config.rp_name.clone(),
+            },
+            user: UserEntity {
+                id: webauthn_ceremony::encode(user.id.as_bytes()),
+                name: user.email.clone(),
+                display_name: format!("{} {}", user.first_name, user.last_name),
+            },
+            challenge,
+            pub_key_cred_params: [COSE_ALG_ES256, COSE_ALG_EDDSA, COSE_ALG_RS256]
+                .into_iter()
+                .map(|alg| CredentialParameter {
+                    credential_type: PUBLIC_KEY.to_string(),
+                    alg,
+                })
+                .collect(),
+            timeout: config.challenge_ttl.num_milliseconds(),
+            exclude_credentials: existing
+                .into_iter()
+                .map(|credential| CredentialDescriptor {
+                    credential_type: PUBLIC_KEY.to_string(),
+                    id: credential.credential_id,
+                })
+                .collect(),
+            authenticator_selection: AuthenticatorSelection {
+                resident_key: "required".to_string(),
+                require_resident_key: true,
+                user_verification: "required".to_string(),
+            },
+            attestation: "none".to_string(),
+        })
+    })
+    .await
+}
+
+pub async fn register_passkey(
+    user_id: Uuid,
+    request: PasskeyRegistrationRequest,
+    pool: DbPool,
+    config: &WebauthnConfig,
+) -> ApiResult<PasskeyResponse> {
+    let config = config.clone();
+    let invalid = |reason: String| {
+        tracing::debug!("Rejected passkey registration: {}", reason);
+        ApiError::BadRequest("Invalid passkey registration".to_string())
+    };
+
+    let credential = request.credential;
+    if credential.credential_type != PUBLIC_KEY {
+        return Err(invalid("unexpected credential type".to_string()));
+    }
+
+    let client_data_json = webauthn_ceremony::decode(&credential.response.client_data_json).map_err(invalid)?;
+    let attestation_object = webauthn_ceremony::decode(&credential.response.attestation_object).map_err(invalid)?;
+    let client_data = webauthn_ceremony::parse_client_data(&client_data_json).map_err(invalid)?;
+    let authenticator_data = webauthn_ceremony::parse_attestation_object(&attestation_object).map_err(invalid)?;
+
+    check_client_data(&client_data, "webauthn.create", &config).map_err(invalid)?;
+
+    with_connection(&pool, move |conn| {
+        let challenge = webauthn_repository::take_challenge(&client_data.challenge, REGISTRATION, conn)?
+            .ok_or_else(|| invalid("unknown or expired challenge".to_string()))?;
+        if challenge.user_id != Some(user_id) {
+            return Err(invalid("challenge was issued to another user".to_string()));
+        }
+
+        if !authenticator_data.matches_rp_id(&config.rp_id) {
+            return Err(invalid("relying party id mismatch".to_string()));
+        }
+        if !authenticator_data.user_present() || !authenticator_data.user_verified() {
+            return Err(invalid("user was not verified".to_string()));
+        }
+
+        let attested = authenticator_data
+            .attested_credential
+            .ok_or_else(|| invalid("missing attested credential".to_string()))?;
+        let credential_id = webauthn_ceremony::encode(&attested.credential_id);
+        if credential_id != credential.id.trim_end_matches('=') {
+            return Err(invalid("credential id mismatch".to_string()));
+        }
+        webauthn_ceremony::validate_public_key(&attested.public_key).map_err(invalid)?;
+
+        let new_credential = NewWebauthnCredential {
+            user_id,
+            credential_id,
+            public_key: attested.public_key,
+            sign_count: authenticator_data.sign_count.into(),
+            name: request.name,
+        };
+
+        let stored = webauthn_repository::create_credential(&new_credential, conn).map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                ApiError::ResourceAlreadyExists("Passkey already registered".to_string())
+            }
+            e => e.into(),
+        })?;
+
+        tracing::info!("Passkey registered for user: {}", user_id);
+
+        Ok(credential_to_response(stored))
+    })
+    .await
+}
+
+pub async fn list_passkeys(user_id: Uuid, pool: DbPool) -> ApiResult<Vec<PasskeyResponse>> {
+    with_connection(&pool, move |conn| {
+        let credentials = webauthn_repository::list_credentials(user_id, conn)?;
+        Ok(credentials.into_iter().map(credential_to_response).collect())
+    })
+    .await
+}
+
+pub async fn delete_passkey(user_id: Uuid, passkey_id: Uuid, pool: DbPool) -> ApiResult<()> {
+    with_connection(&pool, move |conn| {
+        if webauthn_repository::delete_credential(user_id, passkey_id, conn)? == 0 {
+            return Err(ApiError::NotFound("Passkey not found".to_string()));
+        }
+        Ok(())
+    })
+    .await
+}
+
+// Discoverable credentials are used, so no account hint is needed and the
+// response does not reveal whether an email is registered.
+pub async fn login_options(pool: DbPool, config: &WebauthnConfig) -> ApiResult<RequestOptionsResponse> {
+    let config = config.clone();
+
+    with_connection(&pool, move |conn| {
+        let challenge = issue_challenge(None, AUTHENTICATION, &config, conn)?;
+
+        Ok(RequestOptionsResponse {
+            challenge,
+            timeout: config.challenge_ttl.num_milliseconds(),
+            rp_id: config.rp_id.clone(),
+            allow_credentials: Vec::new(),
+            user_verification: "required".to_string(),
+        })
+    })
+    .await
+}
+
+pub async fn login(
+    request: PasskeyLoginRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    config: &WebauthnConfig,
+    verification: &EmailVerificationConfig,
+) -> ApiResult<AuthResponse> {
+    let audit = AuditLog::new(&pool, &client);
+    let result = verify_assertion(request, client, pool, config, verification).await;
+
+    let user_id = result.as_ref().ok().map(|response| response.user.id);
+    audit
+        .record(
+            AuditEntry::from_result(audit_service::LOGIN, &result)
+                .actor(user_id)
+                .target_user(user_id)
+                .with("method", "passkey"),
+        )
+        .await;
+
+    result
+}
+
+async fn verify_assertion(
+    request: PasskeyLoginRequest,
+    client: ClientInfo,
+    pool: DbPool,
+    config: &WebauthnConfig,
+    verification: &EmailVerificationConfig,
+) -> ApiResult<AuthResponse> {
+    let config = config.clone();
+    let verification = verification.clone();
+    let invalid = |reason: String| {
+        tracing::debug!("Rejected passkey assertion: {}", reason);
+        ApiError::Unauthorized("Invalid passkey".to_string())
+    };
+
+    let credential = request.credential;
+    if credential.credential_type != PUBLIC_KEY {
+        return Err(invalid("unexpected credential type".to_string()));
+    }
+
+    let client_data_json = webauthn_ceremony::decode(&credential.response.client_data_json).map_err(invalid)?;
+    let authenticator_data_bytes =
+        webauthn_ceremony::decode(&credential.response.authenticator_data).map_err(invalid)?;
+    let signature = webauthn_ceremony::decode(&credential.response.signature).map_err(invalid)?;
+    let user_handle = credential
+        .response
+        .user_handle
+        .as_deref()
+        .filter(|handle| !handle.is_empty())
+        .map(webauthn_ceremony::decode)
+        .transpose()
+        .map_err(invalid)?;
+    let client_data = webauthn_ceremony::parse_client_data(&client_data_json).map_err(invalid)?;
+    let authenticator_data =
+        webauthn_ceremony::parse_authenticator_data(&authenticator_data_bytes).map_err(invalid)?;
+
+    check_client_data(&client_data, "webauthn.get", &config).map_err(invalid)?;
+
+    with_connection(&pool, move |conn| {
+        webauthn_repository::take_challenge(&client_data.challenge, AUTHENTICATION, conn)?
+            .ok_or_else(|| invalid("unknown or expired challenge".to_string()))?;
+
+        let stored = webauthn_repository::find_credential_by_credential_id(credential.id.trim_end_matches('='), conn)
+            .map_err(|_| invalid("unknown credential".to_string()))?;
+
+        if user_handle.is_some_and(|handle| handle != stored.user_id.as_bytes()) {
+            return Err(invalid("user handle mismatch".to_string()));
+        }
+        if !authenticator_data.matches_rp_id(&config.rp_id) {
+            return Err(invalid("relying party id mismatch".to_string()));
+        }
+        if !authenticator_data.user_present() || !authenticator_data.user_verified() {
+            return Err(invalid("user was not verified".to_string()));
+        }
+
+        if !webauthn_ceremony::verify_assertion(&stored.public_key, &authenticator_data_bytes, &client_data_json, &signature)
+            .map_err(invalid)?
+        {
+            return Err(invalid("signature verification failed".to_string()));
+        }
+
+        record_sign_count(&stored, authenticator_data.sign_count.into(), conn)?;
+
+        let user = user_repository::find_user_by_id(stored.user_id, conn)
+            .map_err(|_| invalid("credential owner not found".to_string()))?;
+
+        auth_service::check_login_allowed(&user, &verification)?;
+        auth_service::start_session(user, &client, conn)
+    })
+    .await
+}
+
+fn issue_challenge(
+    user_id: Option<Uuid>,
+    ceremony: &str,
+    config: &WebauthnConfig,
+    conn: &mut PgConnection,
+) -> ApiResult<String> {
+    let challenge = random_value(32);
+
+    webauthn_repository::create_challenge(
+        &NewWebauthnChallenge {
+            user_id,
+            challenge: challenge.clone(),
+            ceremony: ceremony.to_string(),
+            expires_at: (Utc::now() + config.challenge_ttl).naive_utc(),
+        },
+        conn,
+    )?;
+
+    Ok(challenge)
+}
+
+fn check_client_data(client_data: &ClientData, expected_type: &str, config: &WebauthnConfig) -> Result<(), String> {
+    if client_data.ceremony_type != expected_type {
+        return Err(format!("unexpected client data type {}", client_data.ceremony_type));
+    }
+    if !config.is_allowed_origin(&client_data.origin) {
+        return Err(format!("origin {} is not allowed", client_data.origin));
+    }
+    Ok(())
+}
+
+// Authenticators that keep a signature counter must report a larger value on
+// every use; anything else suggests a cloned authenticator. Passkeys synced
+// between devices always report zero.
+fn record_sign_count(stored: &WebauthnCredential, new_count: i64, conn: &mut PgConnection) -> ApiResult<()> {
+    if (new_count != 0 || stored.sign_count != 0) && new_count <= stored.sign_count {
+        tracing::warn!(
+            target: "security",
+            user_id = %stored.user_id,
+            credential_id = %stored.id,
+            "Passkey signature counter did not increase, possible cloned authenticator"
+        );
+        return Err(ApiError::Unauthorized("Invalid passkey".to_string()));
+    }
+
+    if !webauthn_repository::record_credential_use(stored.id, stored.sign_count, new_count, conn)? {
+        return Err(ApiError::Unauthorized("Invalid passkey".to_string()));
+    }
+
+    Ok(())
+}
+
+fn credential_to_response(credential: WebauthnCredential) -> PasskeyResponse {
+    PasskeyResponse {
+        id: credential.id,
+        name: credential.name,
+        created_at: credential.created_at,
+        last_used_at: credential.last_used_at,
+    }
+}
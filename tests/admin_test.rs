@@ -0,0 +1,208 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_api_template::db::models::{user::User, user_role::NewUserRole};
+use axum_api_template::rbac::rbac_repository;
+use diesel::PgConnection;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+async fn send(app: &Router, method: &str, uri: &str, token: Option<&str>, body: Value) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = request
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body_bytes).unwrap_or(Value::Null))
+}
+
+async fn login(app: &Router, email: &str, password: &str) -> (StatusCode, Value) {
+    send(app, "POST", "/auth/login", None, json!({ "email": email, "password": password })).await
+}
+
+fn create_admin(conn: &mut PgConnection) -> (User, String) {
+    let admin = common::create_test_user(conn);
+    let role = rbac_repository::find_role_by_name("admin", conn).unwrap().unwrap();
+    rbac_repository::assign_role(&NewUserRole { user_id: admin.id, role_id: role.id }, conn).unwrap();
+    let token = common::generate_test_token(admin.id);
+    (admin, token)
+}
+
+#[tokio::test]
+async fn test_user_administration_requires_permission() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let (status, _) = send(&app, "GET", "/api/admin/users", Some(&token), Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let uri = format!("/api/admin/users/{}/deactivate", user.id);
+    let (status, _) = send(&app, "POST", &uri, Some(&token), Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_list_and_search_users() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let (_, admin_token) = create_admin(&mut conn);
+    let marker = uuid::Uuid::new_v4().simple().to_string();
+    let alice = common::create_test_user_with_email(&mut conn, &format!("alice_{}@example.com", marker));
+    common::create_test_user_with_email(&mut conn, &format!("bob_{}@example.com", marker));
+
+    let uri = format!("/api/admin/users?search={}&per_page=1", marker);
+    let (status, body) = send(&app, "GET", &uri, Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], json!(2));
+    assert_eq!(body["users"].as_array().unwrap().len(), 1);
+
+    // `_` is matched literally, not as a wildcard.
+    let uri = format!("/api/admin/users?search=alice_{}", marker);
+    let (_, body) = send(&app, "GET", &uri, Some(&admin_token), Value::Null).await;
+    assert_eq!(body["total"], json!(1));
+    assert_eq!(body["users"][0]["id"], json!(alice.id));
+
+    let uri = format!("/api/admin/users?search={}&is_active=false", marker);
+    let (_, body) = send(&app, "GET", &uri, Some(&admin_token), Value::Null).await;
+    assert_eq!(body["total"], json!(0));
+
+    let (status, _) = send(&app, "GET", "/api/admin/users?per_page=500", Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_deactivate_and_reactivate_user() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let (admin, admin_token) = create_admin(&mut conn);
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+    let (status, body) = send(&app, "POST", &format!("/api/admin/users/{}/deactivate", user.id), Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["is_active"], json!(false));
+
+    let (status, body) = send(&app, "GET", &format!("/api/admin/users/{}", user.id), Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["is_active"], json!(false));
+
+    let (status, _) = send(&app, "GET", "/api/user/profile", Some(&user_token), Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = login(&app, &user.email, "password123").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = send(&app, "POST", &format!("/api/admin/users/{}/reactivate", user.id), Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["is_active"], json!(true));
+    let (status, _) = login(&app, &user.email, "password123").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(&app, "POST", &format!("/api/admin/users/{}/deactivate", admin.id), Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_create_user_and_force_password_reset() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let (_, admin_token) = create_admin(&mut conn);
+
+    let email = format!("created-{}@example.com", uuid::Uuid::new_v4());
+    let (status, body) = send(
+        &app,
+        "POST",
+        "/api/admin/users",
+        Some(&admin_token),
+        json!({ "first_name": "New", "last_name": "User", "email": email, "is_verified": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["is_verified"], json!(true));
+    assert_eq!(common::wait_for_mail_to(&email, 1).await.len(), 1);
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/admin/users",
+        Some(&admin_token),
+        json!({ "first_name": "New", "last_name": "User", "email": email, "password": "password123" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let user = common::create_test_user(&mut conn);
+    let uri = format!("/api/admin/users/{}/password-reset", user.id);
+    let (status, _) = send(&app, "POST", &uri, Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = login(&app, &user.email, "password123").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let messages = common::wait_for_mail_to(&user.email, 1).await;
+    let token = common::extract_mail_token(messages.last().unwrap(), "Reset token:").unwrap();
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/auth/reset-password",
+        None,
+        json!({ "token": token, "new_password": "newpassword123" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = login(&app, &user.email, "newpassword123").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_revoke_user_sessions() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let (_, admin_token) = create_admin(&mut conn);
+    let user = common::create_test_user(&mut conn);
+
+    let (_, first) = login(&app, &user.email, "password123").await;
+    let (_, second) = login(&app, &user.email, "password123").await;
+
+    let (status, body) = send(&app, "GET", &format!("/api/admin/users/{}", user.id), Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let sessions = body["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+
+    let session_id = sessions[0]["id"].as_str().unwrap();
+    let uri = format!("/api/admin/users/{}/sessions/{}", user.id, session_id);
+    let (status, _) = send(&app, "DELETE", &uri, Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = send(&app, "GET", &format!("/api/admin/users/{}", user.id), Some(&admin_token), Value::Null).await;
+    assert_eq!(body["sessions"].as_array().unwrap().len(), 1);
+
+    let (status, _) = send(&app, "DELETE", &format!("/api/admin/users/{}/sessions", user.id), Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    for session in [first, second] {
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/auth/refresh",
+            None,
+            json!({ "refresh_token": session["refresh_token"] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
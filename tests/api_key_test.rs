@@ -0,0 +1,154 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+async fn send(app: &Router, method: &str, uri: &str, auth: (&str, String), body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(auth.0, auth.1)
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body_bytes).unwrap_or(Value::Null))
+}
+
+fn bearer(token: &str) -> (&'static str, String) {
+    ("Authorization", format!("Bearer {}", token))
+}
+
+fn api_key_header(key: &str) -> (&'static str, String) {
+    ("X-API-Key", key.to_string())
+}
+
+async fn create_api_key(app: &Router, user_token: &str, body: Value) -> Value {
+    let (status, api_key) = send(app, "POST", "/api/user/api-keys", bearer(user_token), body).await;
+    assert_eq!(status, StatusCode::OK);
+    api_key
+}
+
+#[tokio::test]
+async fn test_api_key_authenticates_with_its_scopes() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    let api_key = create_api_key(&app, &user_token, json!({ "name": "CI", "scopes": ["profile:read"] })).await;
+    let key = api_key["key"].as_str().unwrap();
+    assert!(key.starts_with(api_key["prefix"].as_str().unwrap()));
+
+    let (status, profile) = send(&app, "GET", "/api/user/profile", bearer(key), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(profile["id"], json!(user.id));
+
+    let (status, _) = send(&app, "GET", "/api/user/profile", api_key_header(key), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Keys are limited to their scopes and kept out of account management.
+    let (status, _) = send(&app, "PUT", "/api/user/profile", api_key_header(key), json!({ "first_name": "X" })).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(&app, "GET", "/api/user/api-keys", api_key_header(key), Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The secret is only shown at creation.
+    let (status, listed) = send(&app, "GET", "/api/user/api-keys", bearer(&user_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let listed = listed.as_array().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["prefix"], api_key["prefix"]);
+    assert!(listed[0].get("key").is_none());
+    assert!(!listed[0]["last_used_at"].is_null());
+}
+
+#[tokio::test]
+async fn test_revoked_expired_and_unknown_keys_are_rejected() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    let api_key = create_api_key(&app, &user_token, json!({ "name": "Script", "scopes": ["profile:read"] })).await;
+    let key = api_key["key"].as_str().unwrap();
+
+    let (status, _) = send(&app, "GET", "/api/user/profile", api_key_header(&format!("{}x", key)), Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let uri = format!("/api/user/api-keys/{}", api_key["id"].as_str().unwrap());
+    let (status, _) = send(&app, "DELETE", &uri, bearer(&user_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, "DELETE", &uri, bearer(&user_token), Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, "GET", "/api/user/profile", bearer(key), Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let expiring = create_api_key(
+        &app,
+        &user_token,
+        json!({ "name": "Temp", "scopes": ["profile:read"], "expires_at": Utc::now() + Duration::hours(1) }),
+    )
+    .await;
+    {
+        use axum_api_template::schema::api_keys::dsl::*;
+        diesel::update(api_keys)
+            .set(expires_at.eq(Some(Utc::now().naive_utc() - Duration::minutes(1))))
+            .execute(&mut *conn)
+            .unwrap();
+    }
+    let (status, _) = send(&app, "GET", "/api/user/profile", bearer(expiring["key"].as_str().unwrap()), Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_deactivated_user_keys_are_rejected() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    let api_key = create_api_key(&app, &user_token, json!({ "name": "CI", "scopes": ["profile:read"] })).await;
+
+    {
+        use axum_api_template::schema::users::dsl::*;
+        diesel::update(users.filter(id.eq(user.id)))
+            .set(is_active.eq(Some(false)))
+            .execute(&mut *conn)
+            .unwrap();
+    }
+
+    let (status, _) = send(&app, "GET", "/api/user/profile", api_key_header(api_key["key"].as_str().unwrap()), Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_create_api_key_validates_scopes_and_expiry() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    for body in [
+        json!({ "name": "CI", "scopes": ["admin"] }),
+        json!({ "name": "CI", "scopes": [] }),
+        json!({ "name": "", "scopes": ["profile:read"] }),
+        json!({ "name": "CI", "scopes": ["profile:read"], "expires_at": Utc::now() - Duration::hours(1) }),
+    ] {
+        let (status, _) = send(&app, "POST", "/api/user/api-keys", bearer(&user_token), body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let scopes = json!(["profile:write", "profile:read", "profile:write"]);
+    let api_key = create_api_key(&app, &user_token, json!({ "name": "CI", "scopes": scopes })).await;
+    assert_eq!(api_key["scopes"], json!(["profile:read", "profile:write"]));
+}
@@ -0,0 +1,174 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_api_template::auth::auth_hashing::hash_token;
+use axum_api_template::db::models::user_role::NewUserRole;
+use axum_api_template::mfa::mfa_repository;
+use axum_api_template::rbac::rbac_repository;
+use diesel::prelude::*;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+async fn send(app: &Router, method: &str, uri: &str, token: Option<&str>, body: Value) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::USER_AGENT, "audit-test");
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = request
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body_bytes).unwrap_or(Value::Null))
+}
+
+fn create_admin_token(conn: &mut PgConnection) -> String {
+    let admin = common::create_test_user(conn);
+    let role = rbac_repository::find_role_by_name("admin", conn).unwrap().unwrap();
+    rbac_repository::assign_role(&NewUserRole { user_id: admin.id, role_id: role.id }, conn).unwrap();
+    common::generate_test_token(admin.id)
+}
+
+#[tokio::test]
+async fn test_login_attempts_are_recorded() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let admin_token = create_admin_token(&mut conn);
+    let user = common::create_test_user(&mut conn);
+
+    let (status, _) = send(&app, "POST", "/auth/login", None, json!({ "email": user.email, "password": "wrong-password" })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send(&app, "POST", "/auth/login", None, json!({ "email": user.email, "password": "password123" })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let uri = format!("/api/admin/audit-events?action=auth.login&target_type=user&target_id={}", user.id);
+    let (status, body) = send(&app, "GET", &uri, Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+
+    assert_eq!(events[0]["outcome"], json!("success"));
+    assert_eq!(events[0]["actor_id"], json!(user.id));
+    assert_eq!(events[0]["user_agent"], json!("audit-test"));
+    assert_eq!(events[0]["payload"]["method"], json!("password"));
+
+    // A failed attempt names the account but has no actor.
+    assert_eq!(events[1]["outcome"], json!("failure"));
+    assert_eq!(events[1]["actor_id"], Value::Null);
+    assert_eq!(events[1]["payload"]["reason"], json!("Unauthorized: Invalid credentials"));
+
+    let uri = format!("/api/admin/audit-events?outcome=failure&target_id={}", user.id);
+    let (_, body) = send(&app, "GET", &uri, Some(&admin_token), Value::Null).await;
+    assert_eq!(body["events"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_second_factor_attempts_are_recorded() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let admin_token = create_admin_token(&mut conn);
+    let user = common::create_test_user(&mut conn);
+    mfa_repository::set_pending_totp_secret(user.id, "JBSWY3DPEHPK3PXP", &mut conn).unwrap();
+    mfa_repository::enable_totp(user.id, &mut conn).unwrap();
+    mfa_repository::replace_recovery_codes(user.id, &[hash_token("abcde-fghij")], &mut conn).unwrap();
+
+    let (_, body) = send(&app, "POST", "/auth/login", None, json!({ "email": user.email, "password": "password123" })).await;
+    let mfa_token = body["mfa_token"].as_str().unwrap();
+
+    let (status, _) = send(&app, "POST", "/auth/2fa/verify", None, json!({ "mfa_token": mfa_token, "code": "zzzzz-zzzzz" })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send(&app, "POST", "/auth/2fa/verify", None, json!({ "mfa_token": mfa_token, "code": "abcde-fghij" })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let uri = format!("/api/admin/audit-events?action=auth.login&target_id={}", user.id);
+    let (_, body) = send(&app, "GET", &uri, Some(&admin_token), Value::Null).await;
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 3);
+
+    assert_eq!(events[0]["outcome"], json!("success"));
+    assert_eq!(events[0]["actor_id"], json!(user.id));
+    assert_eq!(events[0]["payload"]["method"], json!("recovery_code"));
+
+    assert_eq!(events[1]["outcome"], json!("failure"));
+    assert_eq!(events[1]["actor_id"], Value::Null);
+    assert_eq!(events[1]["payload"]["method"], json!("recovery_code"));
+
+    // The password step only names the account until the second factor passes.
+    assert_eq!(events[2]["payload"]["mfa_required"], json!(true));
+    assert_eq!(events[2]["actor_id"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_profile_update_records_changes() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let admin_token = create_admin_token(&mut conn);
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let body = json!({ "first_name": "Renamed", "last_name": "User" });
+    let (status, _) = send(&app, "PUT", "/api/user/profile", Some(&token), body).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let uri = format!("/api/admin/audit-events?action=user.profile_update&actor_id={}", user.id);
+    let (_, body) = send(&app, "GET", &uri, Some(&admin_token), Value::Null).await;
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+
+    // Unchanged fields are left out of the diff.
+    assert_eq!(
+        events[0]["payload"]["changes"],
+        json!({ "first_name": { "from": "Test", "to": "Renamed" } })
+    );
+}
+
+#[tokio::test]
+async fn test_audit_events_are_paginated_with_a_cursor() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let admin_token = create_admin_token(&mut conn);
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    for name in ["One", "Two", "Three"] {
+        let (status, _) = send(&app, "PUT", "/api/user/profile", Some(&token), json!({ "first_name": name })).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let uri = format!("/api/admin/audit-events?actor_id={}&limit=2", user.id);
+    let (_, first_page) = send(&app, "GET", &uri, Some(&admin_token), Value::Null).await;
+    assert_eq!(first_page["events"].as_array().unwrap().len(), 2);
+    assert_eq!(first_page["events"][0]["payload"]["changes"]["first_name"]["to"], json!("Three"));
+
+    let cursor = first_page["next_cursor"].as_str().unwrap();
+    let (_, second_page) = send(&app, "GET", &format!("{}&cursor={}", uri, cursor), Some(&admin_token), Value::Null).await;
+    assert_eq!(second_page["events"].as_array().unwrap().len(), 1);
+    assert_eq!(second_page["events"][0]["payload"]["changes"]["first_name"]["to"], json!("One"));
+    assert_eq!(second_page["next_cursor"], Value::Null);
+
+    let (status, _) = send(&app, "GET", &format!("{}&cursor=not-a-cursor", uri), Some(&admin_token), Value::Null).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_audit_log_requires_permission_and_is_append_only() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let (status, _) = send(&app, "GET", "/api/admin/audit-events", Some(&token), Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    assert!(diesel::sql_query("UPDATE audit_events SET action = 'tampered'").execute(&mut *conn).is_err());
+    assert!(diesel::sql_query("DELETE FROM audit_events").execute(&mut *conn).is_err());
+}
@@ -0,0 +1,187 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::env;
+use tower::ServiceExt;
+use tower_cookies::cookie::{Cookie, SameSite};
+
+mod common;
+
+struct Response {
+    status: StatusCode,
+    cookies: HashMap<String, Cookie<'static>>,
+    body: Value,
+}
+
+fn setup_cookie_app() -> Router {
+    env::set_var("AUTH_COOKIES_ENABLED", "true");
+    env::set_var("AUTH_COOKIES_ACCESS_TOKEN", "true");
+    common::setup_test_app()
+}
+
+async fn send(app: &Router, method: &str, uri: &str, cookies: &HashMap<String, Cookie<'static>>, csrf: Option<&str>, body: Option<Value>) -> Response {
+    let mut request = Request::builder().uri(uri).method(method);
+
+    let cookie_header = cookies
+        .values()
+        .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+        .collect::<Vec<_>>()
+        .join("; ");
+    if !cookie_header.is_empty() {
+        request = request.header(header::COOKIE, cookie_header);
+    }
+    if let Some(csrf) = csrf {
+        request = request.header("X-CSRF-Token", csrf);
+    }
+
+    let body = match body {
+        Some(body) => {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+            Body::from(serde_json::to_vec(&body).unwrap())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app.clone().oneshot(request.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let cookies = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .map(|value| Cookie::parse(value.to_str().unwrap().to_string()).unwrap())
+        .map(|cookie| (cookie.name().to_string(), cookie))
+        .collect();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+
+    Response {
+        status,
+        cookies,
+        body: serde_json::from_slice(&body_bytes).unwrap_or(Value::Null),
+    }
+}
+
+async fn login(app: &Router, email: &str) -> HashMap<String, Cookie<'static>> {
+    let response = send(
+        app,
+        "POST",
+        "/auth/login",
+        &HashMap::new(),
+        None,
+        Some(json!({ "email": email, "password": "password123" })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+    response.cookies
+}
+
+#[tokio::test]
+async fn test_login_sets_session_cookies() {
+    let mut conn = common::setup_test_db();
+    let app = setup_cookie_app();
+    let user = common::create_test_user(&mut conn);
+
+    let response = send(
+        &app,
+        "POST",
+        "/auth/login",
+        &HashMap::new(),
+        None,
+        Some(json!({ "email": user.email, "password": "password123" })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.body.get("access_token").is_none());
+    assert!(response.body.get("refresh_token").is_none());
+    assert_eq!(response.body["user"]["id"], json!(user.id));
+
+    let refresh_cookie = &response.cookies["refresh_token"];
+    assert_eq!(refresh_cookie.http_only(), Some(true));
+    assert_eq!(refresh_cookie.secure(), Some(true));
+    assert_eq!(refresh_cookie.same_site(), Some(SameSite::Strict));
+    assert_eq!(refresh_cookie.path(), Some("/auth"));
+
+    assert_eq!(response.cookies["access_token"].http_only(), Some(true));
+    assert_ne!(response.cookies["csrf_token"].http_only(), Some(true));
+}
+
+#[tokio::test]
+async fn test_cookie_requests_require_csrf_token() {
+    let mut conn = common::setup_test_db();
+    let app = setup_cookie_app();
+    let user = common::create_test_user(&mut conn);
+
+    let cookies = login(&app, &user.email).await;
+    let csrf = cookies["csrf_token"].value().to_string();
+
+    let response = send(&app, "GET", "/api/user/profile", &cookies, None, None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["id"], json!(user.id));
+
+    let update = json!({ "first_name": "Cookie" });
+    let response = send(&app, "PUT", "/api/user/profile", &cookies, None, Some(update.clone())).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    let response = send(&app, "PUT", "/api/user/profile", &cookies, Some("wrong"), Some(update.clone())).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    let response = send(&app, "PUT", "/api/user/profile", &cookies, Some(&csrf), Some(update)).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    // Header-authenticated clients aren't exposed to CSRF.
+    let token = common::generate_test_token(user.id);
+    let request = Request::builder()
+        .uri("/api/user/profile")
+        .method("PUT")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&json!({ "first_name": "Header" })).unwrap()))
+        .unwrap();
+    assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::OK);
+
+    // Any other scheme doesn't exempt a cookie-authenticated request.
+    let cookie_header = cookies
+        .values()
+        .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let request = Request::builder()
+        .uri("/api/user/profile")
+        .method("PUT")
+        .header(header::AUTHORIZATION, "Basic eDp4")
+        .header(header::COOKIE, cookie_header)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&json!({ "first_name": "Forged" })).unwrap()))
+        .unwrap();
+    assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_refresh_and_logout_with_cookies() {
+    let mut conn = common::setup_test_db();
+    let app = setup_cookie_app();
+    let user = common::create_test_user(&mut conn);
+
+    let mut cookies = login(&app, &user.email).await;
+    let csrf = cookies["csrf_token"].value().to_string();
+
+    let response = send(&app, "POST", "/auth/refresh", &cookies, None, None).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = send(&app, "POST", "/auth/refresh", &cookies, Some(&csrf), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.body.get("refresh_token").is_none());
+    assert_ne!(response.cookies["refresh_token"].value(), cookies["refresh_token"].value());
+    cookies.extend(response.cookies);
+    let csrf = cookies["csrf_token"].value().to_string();
+
+    let response = send(&app, "POST", "/api/logout", &cookies, Some(&csrf), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    for name in ["access_token", "refresh_token", "csrf_token"] {
+        assert_eq!(response.cookies[name].value(), "");
+        assert_eq!(response.cookies[name].max_age(), Some(tower_cookies::cookie::time::Duration::ZERO));
+    }
+
+    let response = send(&app, "POST", "/auth/refresh", &cookies, Some(&csrf), None).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
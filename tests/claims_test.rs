@@ -0,0 +1,128 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum_api_template::auth::auth_middleware::AuthUser;
+use axum_api_template::auth::auth_tokens::{
+    generate_access_token, generate_access_token_with, validate_access_token, AccessTokenOptions,
+};
+use chrono::Utc;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use serde_json::{json, Map, Value};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+fn token_with(claims: Value) -> String {
+    let secret = std::env::var("JWT_ACCESS_SECRET").unwrap();
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_ref())).unwrap()
+}
+
+fn base_claims() -> Value {
+    json!({
+        "sub": Uuid::new_v4(),
+        "jti": Uuid::new_v4().to_string(),
+        "iss": "axum-api-template",
+        "aud": ["axum-api-template"],
+        "exp": Utc::now().timestamp() + 60,
+        "iat": Utc::now().timestamp(),
+    })
+}
+
+#[test]
+fn test_access_token_carries_issuer_and_audience() {
+    let claims = validate_access_token(&generate_access_token(Uuid::new_v4())).unwrap();
+
+    assert_eq!(claims.iss, "axum-api-template");
+    assert_eq!(claims.aud, ["axum-api-template"]);
+    assert!(claims.scope.is_none());
+}
+
+#[test]
+fn test_wrong_issuer_or_audience_is_rejected() {
+    assert!(validate_access_token(&token_with(base_claims())).is_ok());
+
+    let mut claims = base_claims();
+    claims["iss"] = json!("someone-else");
+    assert!(validate_access_token(&token_with(claims)).is_err());
+
+    let mut claims = base_claims();
+    claims["aud"] = json!(["another-api"]);
+    assert!(validate_access_token(&token_with(claims)).is_err());
+
+    let mut claims = base_claims();
+    claims.as_object_mut().unwrap().remove("aud");
+    assert!(validate_access_token(&token_with(claims)).is_err());
+}
+
+#[test]
+fn test_scope_and_custom_claims_round_trip() {
+    let user_id = Uuid::new_v4();
+    let mut custom_claims = Map::new();
+    custom_claims.insert("tenant_id".to_string(), json!("acme"));
+    custom_claims.insert("roles".to_string(), json!(["admin"]));
+    custom_claims.insert("sub".to_string(), json!("spoofed"));
+
+    let token = generate_access_token_with(
+        user_id,
+        AccessTokenOptions {
+            scope: Some("profile:read profile:write".to_string()),
+            custom_claims,
+        },
+    );
+    let claims = validate_access_token(&token).unwrap();
+
+    assert_eq!(claims.sub, user_id);
+    assert_eq!(claims.scope.as_deref(), Some("profile:read profile:write"));
+    assert_eq!(claims.custom["tenant_id"], json!("acme"));
+    assert!(!claims.custom.contains_key("sub"));
+}
+
+#[test]
+fn test_auth_user_scopes_and_claims() {
+    let mut claims = Map::new();
+    claims.insert("roles".to_string(), json!(["admin", "editor"]));
+
+    let scoped = AuthUser {
+        id: Uuid::new_v4(),
+        scopes: Some(vec!["profile:read".to_string()]),
+        claims,
+    };
+
+    assert!(scoped.has_scope("profile:read"));
+    assert!(!scoped.has_scope("profile:write"));
+    assert_eq!(scoped.claim::<Vec<String>>("roles").unwrap(), ["admin", "editor"]);
+    assert!(scoped.claim::<String>("tenant_id").is_none());
+
+    let unrestricted = AuthUser {
+        id: Uuid::new_v4(),
+        scopes: None,
+        claims: Map::new(),
+    };
+
+    assert!(unrestricted.has_scope("profile:write"));
+}
+
+#[tokio::test]
+async fn test_protected_route_accepts_scoped_token() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+
+    let user = common::create_test_user(&mut conn);
+    let token = generate_access_token_with(
+        user.id,
+        AccessTokenOptions {
+            scope: Some("profile:read".to_string()),
+            custom_claims: Map::new(),
+        },
+    );
+
+    let request = Request::builder()
+        .uri("/api/user/profile")
+        .method("GET")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
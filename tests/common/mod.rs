@@ -1,4 +1,6 @@
-use diesel::{Connection, PgConnection, RunQueryDsl};
+#![allow(dead_code)]
+
+use diesel::{PgConnection, RunQueryDsl};
 use diesel::r2d2::{ConnectionManager, Pool};
 use axum_api_template::app;
 use axum_api_template::db::models::user::{User, NewUser};
@@ -8,8 +10,32 @@ use axum_api_template::auth::auth_tokens::generate_access_token;
 use axum::Router;
 use once_cell::sync::Lazy;
 use std::env;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
 use uuid::Uuid;
 
+static DB_LOCK: Mutex<()> = Mutex::new(());
+
+pub struct TestDb {
+    conn: diesel::r2d2::PooledConnection<ConnectionManager<PgConnection>>,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Deref for TestDb {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl DerefMut for TestDb {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.conn
+    }
+}
+
 pub static TEST_POOL: Lazy<Pool<ConnectionManager<PgConnection>>> = Lazy::new(|| {
     dotenv::dotenv().ok();
     let database_url = env::var("TEST_DATABASE_URL")
@@ -25,20 +51,69 @@ pub static TEST_POOL: Lazy<Pool<ConnectionManager<PgConnection>>> = Lazy::new(||
 });
 
 pub fn setup_test_app() -> Router {
+    env::set_var("MAIL_TRANSPORT", "file");
+    env::set_var("MAIL_FILE_PATH", test_mail_dir());
     app()
 }
 
-pub fn setup_test_db() -> diesel::r2d2::PooledConnection<ConnectionManager<PgConnection>> {
+pub fn test_mail_dir() -> PathBuf {
+    env::temp_dir().join("axum-api-template-test-mail")
+}
+
+// Mail is delivered in the background, so this waits until at least
+// `expected` messages for the recipient have been written.
+pub async fn wait_for_mail_to(recipient: &str, expected: usize) -> Vec<String> {
+    for _ in 0..100 {
+        let messages = delivered_mail_to(recipient);
+        if messages.len() >= expected {
+            return messages;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    delivered_mail_to(recipient)
+}
+
+pub fn delivered_mail_to(recipient: &str) -> Vec<String> {
+    let to_header = format!("To: {}", recipient);
+
+    let mut files: Vec<PathBuf> = match std::fs::read_dir(test_mail_dir()) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "eml"))
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+    files.sort();
+
+    files
+        .into_iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .filter(|message| message.lines().any(|line| line == to_header))
+        .collect()
+}
+
+pub fn extract_mail_token(message: &str, label: &str) -> Option<String> {
+    message
+        .lines()
+        .find_map(|line| line.strip_prefix(label))
+        .map(|token| token.trim().to_string())
+}
+
+pub fn setup_test_db() -> TestDb {
+    let guard = DB_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
     let mut conn = TEST_POOL.get().expect("Failed to get DB connection from pool");
     cleanup_test_data(&mut conn);
-    conn
+    TestDb { conn, _guard: guard }
 }
 
 fn cleanup_test_data(conn: &mut diesel::r2d2::PooledConnection<ConnectionManager<PgConnection>>) {
     use diesel::sql_query;
     let _ = sql_query("DELETE FROM refresh_tokens").execute(conn);
     let _ = sql_query("DELETE FROM revoked_tokens").execute(conn);
+    let _ = sql_query("DELETE FROM webauthn_challenges").execute(conn);
+    let _ = sql_query("DELETE FROM oauth_clients").execute(conn);
     let _ = sql_query("DELETE FROM users").execute(conn);
+    let _ = sql_query("DELETE FROM organizations").execute(conn);
 }
 
 pub fn create_test_user_with_email(conn: &mut PgConnection, email: &str) -> User {
@@ -49,6 +124,7 @@ pub fn create_test_user_with_email(conn: &mut PgConnection, email: &str) -> User
         password_hash: hash_password("password123").expect("Failed to hash password"),
         is_active: Some(true),
         is_verified: Some(true),
+        locale: None,
     };
 
     user_repository::create_user(&new_user, conn)
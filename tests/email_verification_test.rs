@@ -0,0 +1,186 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum_api_template::auth::auth_hashing::hash_token;
+use axum_api_template::schema::users;
+use axum_api_template::user::user_repository;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+fn json_request(uri: &str, data: Value) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&data).unwrap()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_register_issues_verification_token() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+
+    let request = json_request("/auth/register", json!({
+        "first_name": "Jane",
+        "last_name": "Doe",
+        "email": "jane.verify@example.com",
+        "password": "password123"
+    }));
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let user = user_repository::find_user_by_email("jane.verify@example.com", &mut conn).unwrap();
+
+    assert_eq!(user.is_verified, Some(false));
+    assert!(user.verification_token_hash.is_some());
+    assert!(user.verification_token_expires.is_some());
+}
+
+#[tokio::test]
+async fn test_verify_email_marks_user_verified() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+
+    let sent = common::delivered_mail_to("jane.confirm@example.com").len();
+    let request = json_request("/auth/register", json!({
+        "first_name": "Jane",
+        "last_name": "Doe",
+        "email": "jane.confirm@example.com",
+        "password": "password123"
+    }));
+    app.clone().oneshot(request).await.unwrap();
+
+    let messages = common::wait_for_mail_to("jane.confirm@example.com", sent + 1).await;
+    let token = common::extract_mail_token(messages.last().unwrap(), "Verification token:").unwrap();
+
+    // Only the hash is stored.
+    let user = user_repository::find_user_by_email("jane.confirm@example.com", &mut conn).unwrap();
+    assert_eq!(user.verification_token_hash, Some(hash_token(&token)));
+
+    let request = json_request("/auth/verify-email", json!({ "token": token }));
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let user = user_repository::find_user_by_email("jane.confirm@example.com", &mut conn).unwrap();
+
+    assert_eq!(user.is_verified, Some(true));
+    assert!(user.verification_token_hash.is_none());
+
+    let request = json_request("/auth/verify-email", json!({ "token": token }));
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_verify_email_with_invalid_token() {
+    let _conn = common::setup_test_db();
+    let app = common::setup_test_app();
+
+    let request = json_request("/auth/verify-email", json!({ "token": "invalid_token" }));
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_resend_verification_does_not_reveal_accounts() {
+    let _conn = common::setup_test_db();
+    let app = common::setup_test_app();
+
+    let request = json_request("/auth/resend-verification", json!({ "email": "nobody@example.com" }));
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body_bytes).unwrap();
+
+    assert!(body["message"].as_str().unwrap().contains("verification email"));
+}
+
+#[tokio::test]
+async fn test_resend_verification_is_throttled() {
+    let _conn = common::setup_test_db();
+    let app = common::setup_test_app();
+
+    let mut statuses = Vec::new();
+    for _ in 0..4 {
+        let request = json_request("/auth/resend-verification", json!({ "email": "nobody@example.com" }));
+        statuses.push(app.clone().oneshot(request).await.unwrap().status());
+    }
+
+    assert_eq!(statuses[..3], [StatusCode::OK; 3]);
+    assert_eq!(statuses[3], StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_resend_cooldown_counts_from_when_the_email_was_sent() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    // A token issued two minutes ago under a longer TTL.
+    let now = Utc::now().naive_utc();
+    diesel::update(users::table.find(user.id))
+        .set((
+            users::is_verified.eq(Some(false)),
+            users::verification_token_hash.eq(Some(hash_token("issued-earlier"))),
+            users::verification_token_expires.eq(Some(now + Duration::days(30))),
+            users::verification_sent_at.eq(Some(now - Duration::minutes(2))),
+        ))
+        .execute(&mut *conn)
+        .unwrap();
+
+    let request = json_request("/auth/resend-verification", json!({ "email": user.email }));
+    assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::OK);
+    assert_eq!(common::wait_for_mail_to(&user.email, 1).await.len(), 1);
+
+    let request = json_request("/auth/resend-verification", json!({ "email": user.email }));
+    assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::OK);
+    let user = user_repository::find_user_by_email(&user.email, &mut conn).unwrap();
+    assert!(user.verification_sent_at.unwrap() > now - Duration::seconds(5));
+    assert_ne!(user.verification_token_hash, Some(hash_token("issued-earlier")));
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert_eq!(common::delivered_mail_to(&user.email).len(), 1);
+}
+
+#[tokio::test]
+async fn test_changing_email_requires_verification_again() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let new_email = format!("changed-{}@example.com", uuid::Uuid::new_v4());
+
+    let request = Request::builder()
+        .uri("/api/user/profile")
+        .method("PUT")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::AUTHORIZATION, format!("Bearer {}", common::generate_test_token(user.id)))
+        .body(Body::from(serde_json::to_vec(&json!({ "email": new_email })).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body["is_verified"], json!(false));
+
+    let messages = common::wait_for_mail_to(&new_email, 1).await;
+    let token = common::extract_mail_token(messages.last().unwrap(), "Verification token:").unwrap();
+
+    let request = json_request("/auth/verify-email", json!({ "token": token }));
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let user = user_repository::find_user_by_email(&new_email, &mut conn).unwrap();
+    assert_eq!(user.is_verified, Some(true));
+}
@@ -0,0 +1,99 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum_api_template::auth::auth_keys::SigningKey;
+use http_body_util::BodyExt;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, Validation};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+mod common;
+
+fn key_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/keys").join(name)
+}
+
+fn read_key(name: &str) -> Vec<u8> {
+    std::fs::read(key_path(name)).unwrap()
+}
+
+fn use_rs256_keys() {
+    std::env::set_var("JWT_ALGORITHM", "RS256");
+    std::env::set_var("JWT_PRIVATE_KEY_PATH", key_path("rsa_private.pem"));
+    std::env::set_var("JWT_PUBLIC_KEY_PATH", key_path("rsa_public.pem"));
+}
+
+#[tokio::test]
+async fn test_jwks_verifies_issued_access_tokens() {
+    use_rs256_keys();
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let request = Request::builder()
+        .uri("/.well-known/jwks.json")
+        .method("GET")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let jwks: JwkSet = serde_json::from_slice(&body_bytes).unwrap();
+
+    let header = decode_header(&token).unwrap();
+    assert_eq!(header.alg, Algorithm::RS256);
+
+    let jwk = jwks.find(header.kid.as_deref().unwrap()).expect("kid published in JWKS");
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.set_issuer(&["axum-api-template"]);
+    validation.set_audience(&["axum-api-template"]);
+    let claims = decode::<Value>(&token, &DecodingKey::from_jwk(jwk).unwrap(), &validation)
+        .unwrap()
+        .claims;
+
+    assert_eq!(claims["sub"], json!(user.id));
+
+    let request = Request::builder()
+        .uri("/api/user/profile")
+        .method("GET")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn test_signing_keys_round_trip_through_jwk() {
+    for (algorithm, private_key, public_key) in [
+        (Algorithm::RS256, "rsa_private.pem", "rsa_public.pem"),
+        (Algorithm::ES256, "ec_private.pem", "ec_public.pem"),
+        (Algorithm::EdDSA, "ed25519_private.pem", "ed25519_public.pem"),
+    ] {
+        let key = SigningKey::from_pem(algorithm, &read_key(private_key), &read_key(public_key)).unwrap();
+        let token = encode(&key.header(), &json!({ "sub": "someone", "exp": 4102444800i64 }), key.encoding_key()).unwrap();
+
+        let jwk = key.public_jwk().unwrap();
+        assert_eq!(jwk.common.key_id.as_deref(), Some(key.kid.as_str()));
+
+        let decoded = decode::<Value>(&token, &DecodingKey::from_jwk(jwk).unwrap(), &Validation::new(algorithm));
+        assert!(decoded.is_ok(), "{:?} token should verify against its JWK", algorithm);
+    }
+}
+
+#[test]
+fn test_mismatched_key_pair_is_rejected() {
+    let result = SigningKey::from_pem(
+        Algorithm::ES256,
+        &read_key("ec_private.pem"),
+        &read_key("rsa_public.pem"),
+    );
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,118 @@
+use axum_api_template::auth::auth_keys::{Keyring, KeyringEntry, SigningKey};
+use chrono::{DateTime, TimeZone, Utc};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header, Validation};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+fn key_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/keys").join(name)
+}
+
+fn at(month: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, month, 15, 0, 0, 0).unwrap()
+}
+
+fn claims() -> Value {
+    json!({ "sub": "someone", "exp": 4102444800i64 })
+}
+
+fn rotating_keyring() -> Keyring {
+    let ec_key = SigningKey::from_pem(
+        Algorithm::ES256,
+        &std::fs::read(key_path("ec_private.pem")).unwrap(),
+        &std::fs::read(key_path("ec_public.pem")).unwrap(),
+    )
+    .unwrap();
+
+    Keyring::new(vec![
+        KeyringEntry {
+            key: SigningKey::from_secret(b"old-secret").with_kid("old"),
+            active_from: Some(at(1)),
+            retire_after: Some(at(6)),
+        },
+        KeyringEntry {
+            key: ec_key.with_kid("new"),
+            active_from: Some(at(3)),
+            retire_after: None,
+        },
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_signs_with_current_key_and_validates_until_retired() {
+    let keyring = rotating_keyring();
+
+    assert_eq!(keyring.current_key(at(2)).unwrap().kid, "old");
+    assert_eq!(keyring.current_key(at(4)).unwrap().kid, "new");
+
+    let old_token = keyring.encode(&claims(), at(2)).unwrap();
+    let new_token = keyring.encode(&claims(), at(4)).unwrap();
+
+    assert!(keyring.decode::<Value>(&old_token, at(4), &Validation::default()).is_some());
+    assert!(keyring.decode::<Value>(&new_token, at(4), &Validation::default()).is_some());
+
+    assert!(keyring.decode::<Value>(&old_token, at(7), &Validation::default()).is_none());
+    assert!(keyring.decode::<Value>(&new_token, at(7), &Validation::default()).is_some());
+}
+
+#[test]
+fn test_unknown_kid_is_rejected() {
+    let keyring = rotating_keyring();
+
+    let mut header = Header::new(Algorithm::HS256);
+    header.kid = Some("unknown".to_string());
+    let token = encode(&header, &claims(), &EncodingKey::from_secret(b"old-secret")).unwrap();
+
+    assert!(keyring.decode::<Value>(&token, at(4), &Validation::default()).is_none());
+}
+
+#[test]
+fn test_tokens_without_kid_try_every_key() {
+    let keyring = rotating_keyring();
+
+    let token = encode(&Header::default(), &claims(), &EncodingKey::from_secret(b"old-secret")).unwrap();
+
+    assert!(keyring.decode::<Value>(&token, at(4), &Validation::default()).is_some());
+    assert!(keyring.decode::<Value>(&token, at(7), &Validation::default()).is_none());
+}
+
+#[test]
+fn test_keyring_file_publishes_non_retired_public_keys() {
+    let keyring = Keyring::from_file(&key_path("keyring.json")).unwrap();
+
+    let kids = |now| -> Vec<String> {
+        keyring
+            .jwks(now)
+            .keys
+            .into_iter()
+            .filter_map(|jwk| jwk.common.key_id)
+            .collect()
+    };
+
+    assert_eq!(kids(at(5)), ["2026-04", "2026-10"]);
+    assert_eq!(kids(Utc.with_ymd_and_hms(2026, 12, 2, 0, 0, 0).unwrap()), ["2026-10"]);
+    assert_eq!(keyring.current_key(at(5)).unwrap().kid, "2026-04");
+    assert_eq!(keyring.current_key(at(10)).unwrap().kid, "2026-10");
+}
+
+#[test]
+fn test_duplicate_kids_are_rejected() {
+    let result = Keyring::new(vec![
+        KeyringEntry::new(SigningKey::from_secret(b"first").with_kid("same")),
+        KeyringEntry::new(SigningKey::from_secret(b"second").with_kid("same")),
+    ]);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_empty_key_paths_count_as_unset() {
+    std::env::set_var("JWT_KEYRING_PATH", "");
+    std::env::set_var("JWT_PRIVATE_KEY_PATH", "");
+    std::env::set_var("JWT_ALGORITHM", "HS256");
+    std::env::set_var("JWT_ACCESS_SECRET", "env-secret");
+
+    let keyring = Keyring::from_env().unwrap();
+    assert!(keyring.current_key(Utc::now()).is_some());
+}
@@ -0,0 +1,61 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use tower::ServiceExt;
+
+mod common;
+
+async fn send(app: &Router, method: &str, uri: &str, token: &str) -> StatusCode {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_logout_revokes_current_access_token() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    assert_eq!(send(&app, "GET", "/api/user/profile", &token).await, StatusCode::OK);
+    assert_eq!(send(&app, "POST", "/api/logout", &token).await, StatusCode::OK);
+    assert_eq!(send(&app, "GET", "/api/user/profile", &token).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_logout_keeps_other_access_tokens() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let first_token = common::generate_test_token(user.id);
+    let second_token = common::generate_test_token(user.id);
+
+    assert_eq!(send(&app, "POST", "/api/logout", &first_token).await, StatusCode::OK);
+    assert_eq!(send(&app, "GET", "/api/user/profile", &second_token).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_logout_everywhere_revokes_all_access_tokens() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let other_device_token = common::generate_test_token(user.id);
+    let current_token = common::generate_test_token(user.id);
+
+    // Tokens issued in the same second as the logout are revoked as well.
+    assert_eq!(send(&app, "POST", "/api/logout-all", &current_token).await, StatusCode::OK);
+    assert_eq!(send(&app, "GET", "/api/user/profile", &current_token).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(send(&app, "GET", "/api/user/profile", &other_device_token).await, StatusCode::UNAUTHORIZED);
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+    let new_token = common::generate_test_token(user.id);
+
+    assert_eq!(send(&app, "GET", "/api/user/profile", &new_token).await, StatusCode::OK);
+}
@@ -0,0 +1,119 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_api_template::auth::auth_hashing::hash_token;
+use axum_api_template::db::models::user::NewUser;
+use axum_api_template::mfa::mfa_repository;
+use axum_api_template::user::user_repository;
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+async fn post_json(app: &Router, uri: &str, data: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&data).unwrap()))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body_bytes).unwrap_or(Value::Null))
+}
+
+async fn request_link(app: &Router, email: &str) -> String {
+    let sent = common::delivered_mail_to(email).len();
+    let (status, body) = post_json(app, "/auth/magic-link", json!({ "email": email })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["message"].as_str().unwrap().contains("login link"));
+
+    let messages = common::wait_for_mail_to(email, sent + 1).await;
+    common::extract_mail_token(messages.last().unwrap(), "Login token:").unwrap()
+}
+
+#[tokio::test]
+async fn test_magic_link_logs_in_once() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    let token = request_link(&app, &user.email).await;
+    let stored = user_repository::find_user_by_email(&user.email, &mut conn).unwrap();
+    assert_eq!(stored.magic_link_token_hash, Some(hash_token(&token)));
+
+    let (status, body) = post_json(&app, "/auth/magic-link/consume", json!({ "token": token })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["access_token"].is_string());
+    assert!(body["refresh_token"].is_string());
+    assert_eq!(body["user"]["email"], json!(user.email));
+
+    let (status, _) = post_json(&app, "/auth/magic-link/consume", json!({ "token": token })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_magic_link_for_unknown_email_looks_the_same() {
+    let _conn = common::setup_test_db();
+    let app = common::setup_test_app();
+
+    let (status, body) = post_json(&app, "/auth/magic-link", json!({ "email": "nobody@example.com" })).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["message"].as_str().unwrap().contains("login link"));
+    assert!(common::delivered_mail_to("nobody@example.com").is_empty());
+}
+
+#[tokio::test]
+async fn test_expired_magic_link_is_rejected() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    user_repository::set_magic_link_token(
+        user.id,
+        &hash_token("expired-token"),
+        (Utc::now() - Duration::minutes(1)).naive_utc(),
+        &mut conn,
+    )
+    .unwrap();
+
+    let (status, _) = post_json(&app, "/auth/magic-link/consume", json!({ "token": "expired-token" })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_magic_link_verifies_email_and_respects_totp() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+
+    let user = user_repository::create_user(
+        &NewUser {
+            first_name: "Link".to_string(),
+            last_name: "User".to_string(),
+            email: format!("link-{}@example.com", uuid::Uuid::new_v4()),
+            password_hash: "unused".to_string(),
+            is_active: Some(true),
+            is_verified: Some(false),
+            locale: None,
+        },
+        &mut conn,
+    )
+    .unwrap();
+    mfa_repository::set_pending_totp_secret(user.id, "JBSWY3DPEHPK3PXP", &mut conn).unwrap();
+    mfa_repository::enable_totp(user.id, &mut conn).unwrap();
+
+    let token = request_link(&app, &user.email).await;
+    let (status, body) = post_json(&app, "/auth/magic-link/consume", json!({ "token": token })).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["mfa_required"], json!(true));
+    assert!(body.get("access_token").is_none());
+
+    let stored = user_repository::find_user_by_email(&user.email, &mut conn).unwrap();
+    assert_eq!(stored.is_verified, Some(true));
+}
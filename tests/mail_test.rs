@@ -0,0 +1,167 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum_api_template::mail::file_mailer::{FileMailer, FileTarget};
+use axum_api_template::mail::mail_templates::render_template;
+use axum_api_template::mail::mailer::{Mailer, OutgoingEmail};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+fn json_request(uri: &str, data: Value) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&data).unwrap()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_register_delivers_verification_email() {
+    let _conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let email = format!("verify-{}@example.com", Uuid::new_v4());
+
+    let request = json_request("/auth/register", json!({
+        "first_name": "Jane",
+        "last_name": "Doe",
+        "email": email,
+        "password": "password123"
+    }));
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let messages = common::wait_for_mail_to(&email, 1).await;
+
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("Subject: Verify your email address"));
+
+    let token = common::extract_mail_token(&messages[0], "Verification token:").unwrap();
+
+    let request = json_request("/auth/verify-email", json!({ "token": token }));
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_register_uses_locale_template_variant() {
+    let _conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let email = format!("locale-{}@example.com", Uuid::new_v4());
+
+    let request = json_request("/auth/register", json!({
+        "first_name": "Joana",
+        "last_name": "Silva",
+        "email": email,
+        "password": "password123",
+        "locale": "pt-BR"
+    }));
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let messages = common::wait_for_mail_to(&email, 1).await;
+
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("Subject: Confirme seu e-mail"));
+    assert!(messages[0].contains("multipart/alternative"));
+}
+
+#[tokio::test]
+async fn test_password_reset_flow_delivers_emails() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    let request = json_request("/auth/forgot-password", json!({ "email": user.email }));
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let messages = common::wait_for_mail_to(&user.email, 1).await;
+
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("Subject: Reset your password"));
+
+    let token = common::extract_mail_token(&messages[0], "Reset token:").unwrap();
+
+    let request = json_request("/auth/reset-password", json!({
+        "token": token,
+        "new_password": "newpassword123"
+    }));
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let messages = common::wait_for_mail_to(&user.email, 2).await;
+
+    assert_eq!(messages.len(), 2);
+    assert!(messages[1].contains("Subject: Your password was changed"));
+}
+
+#[tokio::test]
+async fn test_mbox_transport_appends_messages() {
+    let path = std::env::temp_dir().join(format!("outbox-{}.mbox", Uuid::new_v4()));
+    let mailer = FileMailer::new(
+        FileTarget::Mbox(path.clone()),
+        "Test <no-reply@example.com>".parse().unwrap(),
+    );
+
+    for subject in ["First", "Second"] {
+        mailer
+            .send(OutgoingEmail {
+                to: "jane@example.com".to_string(),
+                subject: subject.to_string(),
+                text_body: "Hello\nFrom the test suite\n".to_string(),
+                html_body: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    let mbox = std::fs::read_to_string(&path).unwrap();
+    let separators = mbox
+        .lines()
+        .filter(|line| line.starts_with("From no-reply@example.com "))
+        .count();
+
+    assert_eq!(separators, 2);
+    assert!(mbox.contains("Subject: First"));
+    assert!(mbox.contains("Subject: Second"));
+    assert!(mbox.contains("\n>From the test suite\n"));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn test_render_template_falls_back_by_locale() {
+    let dir = std::env::temp_dir().join(format!("templates-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(dir.join("en")).unwrap();
+    std::fs::create_dir_all(dir.join("pt")).unwrap();
+    std::fs::write(dir.join("en/welcome.subject.txt"), "Welcome {{ first_name }}\n").unwrap();
+    std::fs::write(dir.join("en/welcome.txt"), "Hello {{first_name}}").unwrap();
+    std::fs::write(dir.join("pt/welcome.subject.txt"), "Bem-vindo {{ first_name }}").unwrap();
+    std::fs::write(dir.join("pt/welcome.txt"), "Ola {{ first_name }}").unwrap();
+    std::fs::write(dir.join("pt/welcome.html"), "<p>Ola {{ first_name }}</p>").unwrap();
+
+    let variables = HashMap::from([("first_name", "<Jo>".to_string())]);
+
+    let rendered = render_template(&dir, "welcome", Some("pt-BR"), "en", &variables).unwrap();
+    assert_eq!(rendered.subject, "Bem-vindo <Jo>");
+    assert_eq!(rendered.text_body, "Ola <Jo>");
+    assert_eq!(rendered.html_body.as_deref(), Some("<p>Ola &lt;Jo&gt;</p>"));
+
+    let rendered = render_template(&dir, "welcome", Some("fr"), "en", &variables).unwrap();
+    assert_eq!(rendered.subject, "Welcome <Jo>");
+    assert!(rendered.html_body.is_none());
+
+    assert!(render_template(&dir, "missing", None, "en", &variables).is_err());
+    assert!(render_template(&dir, "welcome", Some("../en"), "pt", &variables).is_ok());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,606 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_api_template::auth::auth_tokens::validate_access_token;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use diesel::RunQueryDsl;
+use http_body_util::BodyExt;
+use reqwest::Url;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tower::ServiceExt;
+
+mod common;
+
+const REDIRECT_URI: &str = "http://localhost:4000/callback";
+const CODE_VERIFIER: &str = "a-code-verifier-that-is-at-least-forty-three-characters-long";
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body_bytes).unwrap_or(Value::Null))
+}
+
+async fn send_json(app: &Router, method: &str, uri: &str, token: &str, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    send(app, request).await
+}
+
+async fn token_request(app: &Router, basic: Option<(&str, &str)>, params: &[(&str, &str)]) -> (StatusCode, Value) {
+    let mut form = Url::parse("http://localhost/").unwrap();
+    form.query_pairs_mut().extend_pairs(params);
+
+    let mut request = Request::builder()
+        .uri("/oauth/token")
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+    if let Some((client_id, client_secret)) = basic {
+        request = request.header(
+            header::AUTHORIZATION,
+            format!("Basic {}", STANDARD.encode(format!("{}:{}", client_id, client_secret))),
+        );
+    }
+
+    send(app, request.body(Body::from(form.query().unwrap().to_string())).unwrap()).await
+}
+
+async fn register_client(app: &Router, token: &str, body: Value) -> Value {
+    let (status, client) = send_json(app, "POST", "/api/user/oauth-clients", token, body).await;
+    assert_eq!(status, StatusCode::OK);
+    client
+}
+
+fn authorize_query(client_id: &str, scope: &str, redirect_uri: &str) -> String {
+    let mut url = Url::parse("http://localhost/oauth/authorize").unwrap();
+    url.query_pairs_mut().extend_pairs([
+        ("response_type", "code"),
+        ("client_id", client_id),
+        ("redirect_uri", redirect_uri),
+        ("scope", scope),
+        ("state", "xyz"),
+        ("code_challenge", URL_SAFE_NO_PAD.encode(Sha256::digest(CODE_VERIFIER)).as_str()),
+        ("code_challenge_method", "S256"),
+    ]);
+    format!("{}?{}", url.path(), url.query().unwrap())
+}
+
+fn redirect_params(response: &Value) -> HashMap<String, String> {
+    let url = Url::parse(response["redirect_to"].as_str().unwrap()).unwrap();
+    assert!(url.as_str().starts_with(REDIRECT_URI));
+    url.query_pairs().into_owned().collect()
+}
+
+// Walks the consent screen and returns the authorization code.
+async fn authorize_with_consent(app: &Router, user_token: &str, client_id: &str, scope: &str) -> String {
+    let query = authorize_query(client_id, scope, REDIRECT_URI);
+    let (status, body) = send_json(app, "GET", &query, user_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["consent_required"], json!(true));
+
+    let params: HashMap<String, String> = Url::parse(&format!("http://localhost{}", query))
+        .unwrap()
+        .query_pairs()
+        .into_owned()
+        .collect();
+    let mut decision = serde_json::to_value(params).unwrap();
+    decision["approve"] = json!(true);
+
+    let (status, body) = send_json(app, "POST", "/oauth/authorize", user_token, decision).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let params = redirect_params(&body);
+    assert_eq!(params["state"], "xyz");
+    params["code"].clone()
+}
+
+#[tokio::test]
+async fn test_authorization_code_flow_with_pkce_and_refresh() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    let client = register_client(
+        &app,
+        &user_token,
+        json!({ "name": "Dashboard", "redirect_uris": [REDIRECT_URI], "scopes": ["profile:read", "profile:write"] }),
+    )
+    .await;
+    let client_id = client["client_id"].as_str().unwrap();
+    let client_secret = client["client_secret"].as_str().unwrap();
+
+    let code = authorize_with_consent(&app, &user_token, client_id, "profile:read").await;
+
+    let (status, tokens) = token_request(
+        &app,
+        Some((client_id, client_secret)),
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", REDIRECT_URI),
+            ("code_verifier", CODE_VERIFIER),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tokens["token_type"], json!("Bearer"));
+    assert_eq!(tokens["scope"], json!("profile:read"));
+
+    let access_token = tokens["access_token"].as_str().unwrap();
+    let claims = validate_access_token(access_token).unwrap();
+    assert_eq!(claims.sub, user.id);
+    assert_eq!(claims.custom["client_id"], json!(client_id));
+
+    // The token reaches what its scope covers and nothing else.
+    let (status, _) = send_json(&app, "GET", "/api/user/profile", access_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(&app, "PUT", "/api/user/profile", access_token, json!({ "first_name": "X" })).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_json(&app, "GET", "/api/user/passkeys", access_token, Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Codes are single-use.
+    let (status, body) = token_request(
+        &app,
+        Some((client_id, client_secret)),
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", REDIRECT_URI),
+            ("code_verifier", CODE_VERIFIER),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], json!("invalid_grant"));
+
+    let refresh_token = tokens["refresh_token"].as_str().unwrap();
+    let (status, refreshed) = token_request(
+        &app,
+        Some((client_id, client_secret)),
+        &[("grant_type", "refresh_token"), ("refresh_token", refresh_token)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(refreshed["scope"], json!("profile:read"));
+    assert_ne!(refreshed["refresh_token"], tokens["refresh_token"]);
+
+    // Client refresh tokens can't be turned into first-party sessions.
+    let request = Request::builder()
+        .uri("/auth/refresh")
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "refresh_token": refreshed["refresh_token"] })).unwrap(),
+        ))
+        .unwrap();
+    let (status, _) = send(&app, request).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_code_exchange_checks_pkce_and_redirect_uri() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    // Public clients authenticate with PKCE alone.
+    let client = register_client(
+        &app,
+        &user_token,
+        json!({ "name": "Mobile", "redirect_uris": [REDIRECT_URI], "scopes": ["profile:read"], "confidential": false }),
+    )
+    .await;
+    assert!(client.get("client_secret").is_none());
+    let client_id = client["client_id"].as_str().unwrap();
+
+    let code = authorize_with_consent(&app, &user_token, client_id, "profile:read").await;
+    let (status, body) = token_request(
+        &app,
+        None,
+        &[
+            ("grant_type", "authorization_code"),
+            ("client_id", client_id),
+            ("code", &code),
+            ("redirect_uri", REDIRECT_URI),
+            ("code_verifier", "a-different-verifier-that-is-also-forty-three-characters"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], json!("invalid_grant"));
+
+    // Consent is remembered, so the next request redirects straight away.
+    let (status, body) = send_json(
+        &app,
+        "GET",
+        &authorize_query(client_id, "profile:read", REDIRECT_URI),
+        &user_token,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let code = redirect_params(&body)["code"].clone();
+
+    let (status, body) = token_request(
+        &app,
+        None,
+        &[
+            ("grant_type", "authorization_code"),
+            ("client_id", client_id),
+            ("code", &code),
+            ("redirect_uri", "http://localhost:4000/other"),
+            ("code_verifier", CODE_VERIFIER),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], json!("invalid_grant"));
+}
+
+#[tokio::test]
+async fn test_authorize_rejections() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    let client = register_client(
+        &app,
+        &user_token,
+        json!({ "name": "Reports", "redirect_uris": [REDIRECT_URI], "scopes": ["profile:read"] }),
+    )
+    .await;
+    let client_id = client["client_id"].as_str().unwrap();
+
+    // An unregistered redirect URI is never redirected to.
+    let (status, _) = send_json(
+        &app,
+        "GET",
+        &authorize_query(client_id, "profile:read", "http://evil.example.com/callback"),
+        &user_token,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = send_json(
+        &app,
+        "GET",
+        &authorize_query(client_id, "profile:write", REDIRECT_URI),
+        &user_token,
+        Value::Null,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(redirect_params(&body)["error"], "invalid_scope");
+
+    let query = authorize_query(client_id, "profile:read", REDIRECT_URI);
+    let mut decision: Value = serde_json::to_value(
+        Url::parse(&format!("http://localhost{}", query))
+            .unwrap()
+            .query_pairs()
+            .into_owned()
+            .collect::<HashMap<String, String>>(),
+    )
+    .unwrap();
+    decision["approve"] = json!(false);
+    let (status, body) = send_json(&app, "POST", "/oauth/authorize", &user_token, decision).await;
+    assert_eq!(status, StatusCode::OK);
+    let params = redirect_params(&body);
+    assert_eq!(params["error"], "access_denied");
+    assert_eq!(params["state"], "xyz");
+
+    // First-party clients skip the consent screen.
+    diesel::sql_query(format!(
+        "UPDATE oauth_clients SET is_first_party = TRUE WHERE client_id = '{}'",
+        client_id
+    ))
+    .execute(&mut *conn)
+    .unwrap();
+    let (_, body) = send_json(&app, "GET", &query, &user_token, Value::Null).await;
+    assert!(redirect_params(&body).contains_key("code"));
+}
+
+#[tokio::test]
+async fn test_client_credentials_grant() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    let (status, _) = send_json(
+        &app,
+        "POST",
+        "/api/user/oauth-clients",
+        &user_token,
+        json!({ "name": "Public", "scopes": ["profile:read"], "grant_types": ["client_credentials"], "confidential": false }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let client = register_client(
+        &app,
+        &user_token,
+        json!({ "name": "Worker", "scopes": ["profile:read"], "grant_types": ["client_credentials"] }),
+    )
+    .await;
+    let client_id = client["client_id"].as_str().unwrap();
+    let client_secret = client["client_secret"].as_str().unwrap();
+
+    let (status, body) = token_request(
+        &app,
+        None,
+        &[
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id),
+            ("client_secret", "wrong-secret"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], json!("invalid_client"));
+
+    let (status, tokens) = token_request(
+        &app,
+        None,
+        &[
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tokens["scope"], json!("profile:read"));
+    assert!(tokens.get("refresh_token").is_none());
+
+    let claims = validate_access_token(tokens["access_token"].as_str().unwrap()).unwrap();
+    assert_eq!(claims.sub.to_string(), client["id"].as_str().unwrap());
+
+    let (status, body) = token_request(
+        &app,
+        Some((client_id, client_secret)),
+        &[("grant_type", "authorization_code"), ("code", "x")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], json!("unauthorized_client"));
+}
+
+#[tokio::test]
+async fn test_revoking_consent_ends_client_refresh_tokens() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    let client = register_client(
+        &app,
+        &user_token,
+        json!({ "name": "Calendar", "redirect_uris": [REDIRECT_URI], "scopes": ["profile:read"] }),
+    )
+    .await;
+    let client_id = client["client_id"].as_str().unwrap();
+    let client_secret = client["client_secret"].as_str().unwrap();
+
+    let code = authorize_with_consent(&app, &user_token, client_id, "profile:read").await;
+    let (_, tokens) = token_request(
+        &app,
+        Some((client_id, client_secret)),
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", REDIRECT_URI),
+            ("code_verifier", CODE_VERIFIER),
+        ],
+    )
+    .await;
+
+    let (status, consents) = send_json(&app, "GET", "/api/user/oauth-consents", &user_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(consents[0]["client_id"], json!(client_id));
+    assert_eq!(consents[0]["scopes"], json!(["profile:read"]));
+
+    let consent_uri = format!("/api/user/oauth-consents/{}", consents[0]["id"].as_str().unwrap());
+    let (status, _) = send_json(&app, "DELETE", &consent_uri, &user_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = token_request(
+        &app,
+        Some((client_id, client_secret)),
+        &[("grant_type", "refresh_token"), ("refresh_token", tokens["refresh_token"].as_str().unwrap())],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], json!("invalid_grant"));
+}
+
+async fn issue_user_tokens(app: &Router, user_token: &str, client: &Value) -> Value {
+    let client_id = client["client_id"].as_str().unwrap();
+    let code = authorize_with_consent(app, user_token, client_id, "profile:read").await;
+
+    let (status, tokens) = token_request(
+        app,
+        Some((client_id, client["client_secret"].as_str().unwrap())),
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", REDIRECT_URI),
+            ("code_verifier", CODE_VERIFIER),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    tokens
+}
+
+async fn form_request(app: &Router, uri: &str, basic: (&str, &str), params: &[(&str, &str)]) -> (StatusCode, Value) {
+    let mut form = Url::parse("http://localhost/").unwrap();
+    form.query_pairs_mut().extend_pairs(params);
+
+    let request = Request::builder()
+        .uri(uri)
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(
+            header::AUTHORIZATION,
+            format!("Basic {}", STANDARD.encode(format!("{}:{}", basic.0, basic.1))),
+        )
+        .body(Body::from(form.query().unwrap().to_string()))
+        .unwrap();
+    send(app, request).await
+}
+
+#[tokio::test]
+async fn test_introspection_and_revocation() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    let client = register_client(
+        &app,
+        &user_token,
+        json!({ "name": "Gateway", "redirect_uris": [REDIRECT_URI], "scopes": ["profile:read"] }),
+    )
+    .await;
+    let credentials = (client["client_id"].as_str().unwrap(), client["client_secret"].as_str().unwrap());
+    let tokens = issue_user_tokens(&app, &user_token, &client).await;
+    let access_token = tokens["access_token"].as_str().unwrap();
+    let refresh_token = tokens["refresh_token"].as_str().unwrap();
+
+    let (status, body) = form_request(&app, "/oauth/introspect", credentials, &[("token", access_token)]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["active"], json!(true));
+    assert_eq!(body["token_type"], json!("access_token"));
+    assert_eq!(body["scope"], json!("profile:read"));
+    assert_eq!(body["client_id"], json!(credentials.0));
+    assert_eq!(body["sub"], json!(user.id));
+
+    let (_, body) = form_request(
+        &app,
+        "/oauth/introspect",
+        credentials,
+        &[("token", refresh_token), ("token_type_hint", "refresh_token")],
+    )
+    .await;
+    assert_eq!(body["active"], json!(true));
+    assert_eq!(body["token_type"], json!("refresh_token"));
+
+    let (_, body) = form_request(&app, "/oauth/introspect", credentials, &[("token", "not-a-token")]).await;
+    assert_eq!(body, json!({ "active": false }));
+
+    let (status, _) = form_request(&app, "/oauth/revoke", credentials, &[("token", access_token)]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = form_request(&app, "/oauth/introspect", credentials, &[("token", access_token)]).await;
+    assert_eq!(body["active"], json!(false));
+    let (status, _) = send_json(&app, "GET", "/api/user/profile", access_token, Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = form_request(
+        &app,
+        "/oauth/revoke",
+        credentials,
+        &[("token", refresh_token), ("token_type_hint", "refresh_token")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = form_request(&app, "/oauth/introspect", credentials, &[("token", refresh_token)]).await;
+    assert_eq!(body["active"], json!(false));
+    let (_, body) = token_request(
+        &app,
+        Some(credentials),
+        &[("grant_type", "refresh_token"), ("refresh_token", refresh_token)],
+    )
+    .await;
+    assert_eq!(body["error"], json!("invalid_grant"));
+}
+
+#[tokio::test]
+async fn test_introspection_and_revocation_are_limited_to_the_client() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let user_token = common::generate_test_token(user.id);
+
+    let owner = register_client(
+        &app,
+        &user_token,
+        json!({ "name": "Owner", "redirect_uris": [REDIRECT_URI], "scopes": ["profile:read"] }),
+    )
+    .await;
+    let other = register_client(
+        &app,
+        &user_token,
+        json!({ "name": "Other", "scopes": ["profile:read"], "grant_types": ["client_credentials"] }),
+    )
+    .await;
+    let public = register_client(
+        &app,
+        &user_token,
+        json!({ "name": "Public", "redirect_uris": [REDIRECT_URI], "scopes": ["profile:read"], "confidential": false }),
+    )
+    .await;
+    let other_credentials = (other["client_id"].as_str().unwrap(), other["client_secret"].as_str().unwrap());
+    let tokens = issue_user_tokens(&app, &user_token, &owner).await;
+    let access_token = tokens["access_token"].as_str().unwrap();
+    let refresh_token = tokens["refresh_token"].as_str().unwrap();
+
+    let (status, body) = form_request(&app, "/oauth/introspect", (other_credentials.0, "wrong"), &[("token", access_token)]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], json!("invalid_client"));
+
+    // Public clients can't introspect.
+    let request = Request::builder()
+        .uri("/oauth/introspect")
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(format!(
+            "token={}&client_id={}",
+            access_token,
+            public["client_id"].as_str().unwrap()
+        )))
+        .unwrap();
+    let (status, body) = send(&app, request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], json!("unauthorized_client"));
+
+    // Any confidential client can check access tokens, but refresh tokens are
+    // private to the client they were issued to.
+    let (_, body) = form_request(&app, "/oauth/introspect", other_credentials, &[("token", access_token)]).await;
+    assert_eq!(body["active"], json!(true));
+    let (_, body) = form_request(&app, "/oauth/introspect", other_credentials, &[("token", refresh_token)]).await;
+    assert_eq!(body["active"], json!(false));
+
+    // Revoking another client's tokens succeeds without revoking anything.
+    let (status, _) = form_request(&app, "/oauth/revoke", other_credentials, &[("token", access_token)]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = form_request(&app, "/oauth/revoke", other_credentials, &[("token", refresh_token)]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_json(&app, "GET", "/api/user/profile", access_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Client credentials tokens can be revoked by their own client.
+    let (_, client_tokens) = token_request(
+        &app,
+        Some(other_credentials),
+        &[("grant_type", "client_credentials")],
+    )
+    .await;
+    let client_token = client_tokens["access_token"].as_str().unwrap();
+    let (status, _) = form_request(&app, "/oauth/revoke", other_credentials, &[("token", client_token)]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = form_request(&app, "/oauth/introspect", other_credentials, &[("token", client_token)]).await;
+    assert_eq!(body["active"], json!(false));
+}
@@ -0,0 +1,325 @@
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use axum::routing::{get, post};
+use axum::{Form, Json, Router};
+use axum_api_template::auth::auth_keys::{Keyring, KeyringEntry, SigningKey};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::Utc;
+use jsonwebtoken::Algorithm;
+use once_cell::sync::Lazy;
+use reqwest::Url;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+const CLIENT_ID: &str = "stub-client";
+const CLIENT_SECRET: &str = "stub-secret";
+
+// Minimal OIDC provider: the test "signs in" by registering an authorization
+// code for the identity it wants, and the app redeems it at the token endpoint.
+struct StubProvider {
+    issuer: String,
+    keyring: Keyring,
+    codes: Mutex<HashMap<String, PendingCode>>,
+}
+
+struct PendingCode {
+    nonce: String,
+    code_challenge: String,
+    identity: Value,
+}
+
+static PROVIDER: Lazy<Arc<StubProvider>> = Lazy::new(|| {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let issuer = format!("http://{}", listener.local_addr().unwrap());
+
+    let key_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/keys");
+    let key = SigningKey::from_pem(
+        Algorithm::RS256,
+        &std::fs::read(key_dir.join("rsa_private.pem")).unwrap(),
+        &std::fs::read(key_dir.join("rsa_public.pem")).unwrap(),
+    )
+    .unwrap()
+    .with_kid("stub-key");
+
+    let provider = Arc::new(StubProvider {
+        issuer: issuer.clone(),
+        keyring: Keyring::new(vec![KeyringEntry::new(key)]).unwrap(),
+        codes: Mutex::new(HashMap::new()),
+    });
+
+    let router = Router::new()
+        .route("/.well-known/openid-configuration", get(discovery))
+        .route("/jwks", get(jwks))
+        .route("/token", post(token))
+        .with_state(provider.clone());
+
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async move {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            axum::serve(listener, router).await.unwrap();
+        });
+    });
+
+    std::env::set_var("OIDC_PROVIDERS", "stub");
+    std::env::set_var("OIDC_STUB_ISSUER", &issuer);
+    std::env::set_var("OIDC_STUB_CLIENT_ID", CLIENT_ID);
+    std::env::set_var("OIDC_STUB_CLIENT_SECRET", CLIENT_SECRET);
+
+    provider
+});
+
+async fn discovery(State(provider): State<Arc<StubProvider>>) -> Json<Value> {
+    Json(json!({
+        "issuer": provider.issuer,
+        "authorization_endpoint": format!("{}/authorize", provider.issuer),
+        "token_endpoint": format!("{}/token", provider.issuer),
+        "jwks_uri": format!("{}/jwks", provider.issuer),
+    }))
+}
+
+async fn jwks(State(provider): State<Arc<StubProvider>>) -> Json<Value> {
+    Json(serde_json::to_value(provider.keyring.jwks(Utc::now())).unwrap())
+}
+
+async fn token(
+    State(provider): State<Arc<StubProvider>>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Json<Value>, StatusCode> {
+    let expected_auth = format!("Basic {}", STANDARD.encode(format!("{}:{}", CLIENT_ID, CLIENT_SECRET)));
+    if headers.get(header::AUTHORIZATION).and_then(|value| value.to_str().ok()) != Some(expected_auth.as_str()) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let pending = provider
+        .codes
+        .lock()
+        .unwrap()
+        .remove(&form["code"])
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    if URL_SAFE_NO_PAD.encode(Sha256::digest(form["code_verifier"].as_bytes())) != pending.code_challenge {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut claims = json!({
+        "iss": provider.issuer,
+        "aud": CLIENT_ID,
+        "iat": Utc::now().timestamp(),
+        "exp": Utc::now().timestamp() + 300,
+        "nonce": pending.nonce,
+    });
+    claims.as_object_mut().unwrap().extend(pending.identity.as_object().unwrap().clone());
+
+    Ok(Json(json!({
+        "access_token": "stub-access-token",
+        "token_type": "Bearer",
+        "id_token": provider.keyring.encode(&claims, Utc::now()).unwrap(),
+    })))
+}
+
+fn oidc_app() -> Router {
+    Lazy::force(&PROVIDER);
+    common::setup_test_app()
+}
+
+// Plays the browser and the provider's login page for an authorization URL.
+fn sign_in_at_provider(authorization_url: &str, identity: Value) -> (String, String) {
+    let url = Url::parse(authorization_url).unwrap();
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    assert_eq!(params["client_id"], CLIENT_ID);
+    assert_eq!(params["code_challenge_method"], "S256");
+    assert!(params["scope"].split(' ').any(|scope| scope == "openid"));
+
+    let code = Uuid::new_v4().to_string();
+    PROVIDER.codes.lock().unwrap().insert(
+        code.clone(),
+        PendingCode {
+            nonce: params["nonce"].clone(),
+            code_challenge: params["code_challenge"].clone(),
+            identity,
+        },
+    );
+
+    (code, params["state"].clone())
+}
+
+async fn send(app: &Router, method: &str, uri: &str, token: Option<&str>, body: Value) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = request.body(Body::from(serde_json::to_vec(&body).unwrap())).unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn provider_login(app: &Router, identity: Value) -> (StatusCode, Value) {
+    let (status, body) = send(app, "POST", "/auth/oidc/stub/authorize", None, json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (code, state) = sign_in_at_provider(body["authorization_url"].as_str().unwrap(), identity);
+    send(app, "POST", "/auth/oidc/stub/callback", None, json!({ "code": code, "state": state })).await
+}
+
+#[tokio::test]
+async fn test_provider_login_creates_and_reuses_account() {
+    let _conn = common::setup_test_db();
+    let app = oidc_app();
+    let identity = json!({
+        "sub": "subject-1",
+        "email": "social@example.com",
+        "email_verified": true,
+        "given_name": "Social",
+        "family_name": "User",
+    });
+
+    let (status, first) = provider_login(&app, identity.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(first["access_token"].is_string());
+    assert_eq!(first["user"]["email"], json!("social@example.com"));
+    assert_eq!(first["user"]["first_name"], json!("Social"));
+
+    let (status, second) = provider_login(&app, identity).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(second["user"]["id"], first["user"]["id"]);
+}
+
+#[tokio::test]
+async fn test_verified_email_links_existing_account() {
+    let mut conn = common::setup_test_db();
+    let app = oidc_app();
+    let user = common::create_test_user(&mut conn);
+
+    let (status, _) = provider_login(
+        &app,
+        json!({ "sub": "unverified", "email": user.email, "email_verified": false }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = provider_login(
+        &app,
+        json!({ "sub": "verified", "email": user.email, "email_verified": "true" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["user"]["id"], json!(user.id));
+
+    let token = common::generate_test_token(user.id);
+    let (_, identities) = send(&app, "GET", "/api/user/identities", Some(&token), Value::Null).await;
+    assert_eq!(identities.as_array().unwrap().len(), 1);
+    assert_eq!(identities[0]["provider"], json!("stub"));
+}
+
+#[tokio::test]
+async fn test_unverified_local_account_is_not_linked() {
+    let _conn = common::setup_test_db();
+    let app = oidc_app();
+    let email = format!("claimed-{}@example.com", Uuid::new_v4());
+
+    // Someone registers the address with a password before its owner signs in.
+    let (status, registered) = send(
+        &app,
+        "POST",
+        "/auth/register",
+        None,
+        json!({ "first_name": "Claimed", "last_name": "First", "email": email, "password": "password123" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = provider_login(
+        &app,
+        json!({ "sub": "owner", "email": email, "email_verified": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let token = registered["access_token"].as_str().unwrap();
+    let (_, identities) = send(&app, "GET", "/api/user/identities", Some(token), Value::Null).await;
+    assert_eq!(identities.as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_link_and_unlink_identity() {
+    let mut conn = common::setup_test_db();
+    let app = oidc_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let (status, body) = send(&app, "POST", "/api/user/identities/stub/authorize", Some(&token), json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // A different address at the provider; linking doesn't depend on email.
+    let (code, state) = sign_in_at_provider(
+        body["authorization_url"].as_str().unwrap(),
+        json!({ "sub": "linked-subject", "email": "other@example.com", "email_verified": true }),
+    );
+    let (status, identity) = send(
+        &app,
+        "POST",
+        "/api/user/identities/stub",
+        Some(&token),
+        json!({ "code": code, "state": state }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(identity["provider"], json!("stub"));
+
+    let (status, body) = provider_login(&app, json!({ "sub": "linked-subject" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["user"]["id"], json!(user.id));
+
+    let (status, _) = send(&app, "DELETE", "/api/user/identities/stub", Some(&token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, identities) = send(&app, "GET", "/api/user/identities", Some(&token), Value::Null).await;
+    assert!(identities.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_login_state_is_single_use_and_bound_to_its_flow() {
+    let mut conn = common::setup_test_db();
+    let app = oidc_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let (_, body) = send(&app, "POST", "/auth/oidc/stub/authorize", None, json!({})).await;
+    let (code, state) = sign_in_at_provider(
+        body["authorization_url"].as_str().unwrap(),
+        json!({ "sub": "single-use", "email": "single@example.com", "email_verified": true }),
+    );
+    let callback = json!({ "code": code, "state": state });
+
+    let (status, _) = send(&app, "POST", "/auth/oidc/stub/callback", None, callback.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, "POST", "/auth/oidc/stub/callback", None, callback).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A state issued for linking can't complete a login.
+    let (_, body) = send(&app, "POST", "/api/user/identities/stub/authorize", Some(&token), json!({})).await;
+    let (code, state) = sign_in_at_provider(body["authorization_url"].as_str().unwrap(), json!({ "sub": "x" }));
+    let (status, _) = send(&app, "POST", "/auth/oidc/stub/callback", None, json!({ "code": code, "state": state })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(&app, "POST", "/auth/oidc/unknown/authorize", None, json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
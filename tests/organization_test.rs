@@ -0,0 +1,204 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_api_template::auth::auth_tokens::validate_access_token;
+use axum_api_template::schema::invitations;
+use diesel::prelude::*;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+async fn send(app: &Router, method: &str, uri: &str, token: &str, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body_bytes).unwrap_or(Value::Null))
+}
+
+// Creates an organization and returns its id with an access token scoped to it.
+async fn create_organization(app: &Router, token: &str) -> (String, String) {
+    let slug = format!("org-{}", Uuid::new_v4().simple());
+    let (status, organization) = send(app, "POST", "/api/organizations", token, json!({ "name": "Acme", "slug": slug })).await;
+    assert_eq!(status, StatusCode::OK);
+    let id = organization["id"].as_str().unwrap().to_string();
+    (id.clone(), activate(app, token, &id).await)
+}
+
+async fn activate(app: &Router, token: &str, organization_id: &str) -> String {
+    let uri = format!("/api/organizations/{}/activate", organization_id);
+    let (status, body) = send(app, "POST", &uri, token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    body["access_token"].as_str().unwrap().to_string()
+}
+
+async fn invite(app: &Router, token: &str, email: &str, role: &str) -> String {
+    let sent = common::delivered_mail_to(&email.to_lowercase()).len();
+    let (status, _) = send(
+        app,
+        "POST",
+        "/api/organizations/current/invitations",
+        token,
+        json!({ "email": email, "role": role }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let messages = common::wait_for_mail_to(&email.to_lowercase(), sent + 1).await;
+    common::extract_mail_token(messages.last().unwrap(), "Invitation token:").unwrap()
+}
+
+#[tokio::test]
+async fn test_active_organization_claim() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let (status, _) = send(&app, "POST", "/api/organizations", &token, json!({ "name": "Acme", "slug": "Not A Slug" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (organization_id, scoped_token) = create_organization(&app, &token).await;
+    let claims = validate_access_token(&scoped_token).unwrap();
+    assert_eq!(claims.custom["org_id"], json!(organization_id));
+
+    let (status, _) = send(&app, "GET", "/api/organizations/current", &token, Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = send(&app, "GET", "/api/organizations/current", &scoped_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["role"], json!("owner"));
+
+    let (second_id, _) = create_organization(&app, &token).await;
+    let (_, organizations) = send(&app, "GET", "/api/organizations", &token, Value::Null).await;
+    let organizations = organizations.as_array().unwrap();
+    assert_eq!(organizations.len(), 2);
+    let active: Vec<&Value> = organizations.iter().filter(|organization| organization["active"] == json!(true)).collect();
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0]["id"], json!(second_id));
+
+    let stranger = common::create_test_user(&mut conn);
+    let uri = format!("/api/organizations/{}/activate", organization_id);
+    let (status, _) = send(&app, "POST", &uri, &common::generate_test_token(stranger.id), Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_invitation_flow() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let owner = common::create_test_user(&mut conn);
+    let invitee = common::create_test_user(&mut conn);
+    let other = common::create_test_user(&mut conn);
+    let (organization_id, owner_token) = create_organization(&app, &common::generate_test_token(owner.id)).await;
+
+    let invitation_token = invite(&app, &owner_token, &invitee.email.to_uppercase(), "admin").await;
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/organizations/current/invitations",
+        &owner_token,
+        json!({ "email": invitee.email }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, invitations) = send(&app, "GET", "/api/organizations/current/invitations", &owner_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(invitations.as_array().unwrap().len(), 1);
+
+    let body = json!({ "token": invitation_token });
+    let (status, _) = send(&app, "POST", "/api/invitations/accept", &common::generate_test_token(other.id), body.clone()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let invitee_token = common::generate_test_token(invitee.id);
+    let (status, organization) = send(&app, "POST", "/api/invitations/accept", &invitee_token, body.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(organization["id"], json!(organization_id));
+    assert_eq!(organization["role"], json!("admin"));
+    assert_eq!(organization["active"], json!(true));
+
+    let (status, _) = send(&app, "POST", "/api/invitations/accept", &invitee_token, body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, members) = send(&app, "GET", "/api/organizations/current/members", &owner_token, Value::Null).await;
+    assert_eq!(members.as_array().unwrap().len(), 2);
+    let (_, invitations) = send(&app, "GET", "/api/organizations/current/invitations", &owner_token, Value::Null).await;
+    assert_eq!(invitations.as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_declined_and_expired_invitations() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let owner = common::create_test_user(&mut conn);
+    let invitee = common::create_test_user(&mut conn);
+    let invitee_token = common::generate_test_token(invitee.id);
+    let (_, owner_token) = create_organization(&app, &common::generate_test_token(owner.id)).await;
+
+    let declined = invite(&app, &owner_token, &invitee.email, "member").await;
+    let (status, _) = send(&app, "POST", "/api/invitations/decline", &invitee_token, json!({ "token": declined })).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, "POST", "/api/invitations/accept", &invitee_token, json!({ "token": declined })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let expired = invite(&app, &owner_token, &invitee.email, "member").await;
+    diesel::update(invitations::table.filter(invitations::accepted_at.is_null()))
+        .set(invitations::expires_at.eq(chrono::Utc::now().naive_utc() - chrono::Duration::minutes(1)))
+        .execute(&mut *conn)
+        .unwrap();
+    let (status, _) = send(&app, "POST", "/api/invitations/accept", &invitee_token, json!({ "token": expired })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_member_roles() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let owner = common::create_test_user(&mut conn);
+    let admin = common::create_test_user(&mut conn);
+    let member = common::create_test_user(&mut conn);
+    let (organization_id, owner_token) = create_organization(&app, &common::generate_test_token(owner.id)).await;
+
+    for (user, role) in [(&admin, "admin"), (&member, "member")] {
+        let token = invite(&app, &owner_token, &user.email, role).await;
+        let (status, _) = send(&app, "POST", "/api/invitations/accept", &common::generate_test_token(user.id), json!({ "token": token })).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let admin_token = activate(&app, &common::generate_test_token(admin.id), &organization_id).await;
+    let member_token = activate(&app, &common::generate_test_token(member.id), &organization_id).await;
+
+    let (status, _) = send(&app, "GET", "/api/organizations/current/invitations", &member_token, Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let member_uri = format!("/api/organizations/current/members/{}", member.id);
+    let (status, _) = send(&app, "PUT", &member_uri, &admin_token, json!({ "role": "owner" })).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let owner_uri = format!("/api/organizations/current/members/{}", owner.id);
+    let (status, _) = send(&app, "DELETE", &owner_uri, &admin_token, Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) = send(&app, "PUT", &member_uri, &admin_token, json!({ "role": "admin" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["role"], json!("admin"));
+
+    // The last owner can neither leave nor step down.
+    let (status, _) = send(&app, "DELETE", &owner_uri, &owner_token, Value::Null).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, "PUT", &owner_uri, &owner_token, json!({ "role": "member" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Leaving takes effect right away, even with the claim still in the token.
+    let (status, _) = send(&app, "DELETE", &member_uri, &member_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, "GET", "/api/organizations/current", &member_token, Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,259 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ciborium::Value as Cbor;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tower::ServiceExt;
+
+mod common;
+
+const ORIGIN: &str = "http://localhost:3000";
+const RP_ID: &str = "localhost";
+
+// Software authenticator holding a single ES256 passkey.
+struct Authenticator {
+    key_pair: EcdsaKeyPair,
+    credential_id: Vec<u8>,
+    sign_count: u32,
+}
+
+impl Authenticator {
+    fn new() -> Self {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+
+        Self {
+            key_pair,
+            credential_id: uuid::Uuid::new_v4().as_bytes().to_vec(),
+            sign_count: 0,
+        }
+    }
+
+    fn cose_key(&self) -> Vec<u8> {
+        let point = self.key_pair.public_key().as_ref();
+        let key = Cbor::Map(vec![
+            (Cbor::from(1), Cbor::from(2)),
+            (Cbor::from(3), Cbor::from(-7)),
+            (Cbor::from(-1), Cbor::from(1)),
+            (Cbor::from(-2), Cbor::Bytes(point[1..33].to_vec())),
+            (Cbor::from(-3), Cbor::Bytes(point[33..].to_vec())),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&key, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn authenticator_data(&self, attested: bool) -> Vec<u8> {
+        let mut data = Sha256::digest(RP_ID.as_bytes()).to_vec();
+        data.push(if attested { 0x45 } else { 0x05 });
+        data.extend_from_slice(&self.sign_count.to_be_bytes());
+
+        if attested {
+            data.extend_from_slice(&[0; 16]);
+            data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+            data.extend_from_slice(&self.credential_id);
+            data.extend_from_slice(&self.cose_key());
+        }
+        data
+    }
+
+    fn create(&self, challenge: &str, origin: &str) -> Value {
+        let client_data = json!({ "type": "webauthn.create", "challenge": challenge, "origin": origin });
+        let attestation_object = Cbor::Map(vec![
+            (Cbor::from("fmt"), Cbor::from("none")),
+            (Cbor::from("attStmt"), Cbor::Map(Vec::new())),
+            (Cbor::from("authData"), Cbor::Bytes(self.authenticator_data(true))),
+        ]);
+        let mut attestation_bytes = Vec::new();
+        ciborium::ser::into_writer(&attestation_object, &mut attestation_bytes).unwrap();
+
+        json!({
+            "id": b64(&self.credential_id),
+            "rawId": b64(&self.credential_id),
+            "type": "public-key",
+            "response": {
+                "clientDataJSON": b64(client_data.to_string().as_bytes()),
+                "attestationObject": b64(&attestation_bytes),
+            }
+        })
+    }
+
+    fn get(&mut self, challenge: &str, user_handle: &[u8]) -> Value {
+        self.sign_count += 1;
+        let client_data = json!({ "type": "webauthn.get", "challenge": challenge, "origin": ORIGIN }).to_string();
+        let authenticator_data = self.authenticator_data(false);
+
+        let mut message = authenticator_data.clone();
+        message.extend_from_slice(&Sha256::digest(client_data.as_bytes()));
+        let signature = self.key_pair.sign(&SystemRandom::new(), &message).unwrap();
+
+        json!({
+            "id": b64(&self.credential_id),
+            "rawId": b64(&self.credential_id),
+            "type": "public-key",
+            "response": {
+                "clientDataJSON": b64(client_data.as_bytes()),
+                "authenticatorData": b64(&authenticator_data),
+                "signature": b64(signature.as_ref()),
+                "userHandle": b64(user_handle),
+            }
+        })
+    }
+}
+
+fn b64(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+async fn send(app: &Router, method: &str, uri: &str, token: Option<&str>, body: Value) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = request.body(Body::from(serde_json::to_vec(&body).unwrap())).unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn register(app: &Router, token: &str, authenticator: &Authenticator) -> Value {
+    let (status, options) = send(app, "POST", "/api/user/passkeys/options", Some(token), json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(options["rp"]["id"], json!(RP_ID));
+    assert_eq!(options["authenticatorSelection"]["userVerification"], json!("required"));
+
+    let challenge = options["challenge"].as_str().unwrap();
+    let (status, passkey) = send(
+        app,
+        "POST",
+        "/api/user/passkeys",
+        Some(token),
+        json!({ "name": "Laptop", "credential": authenticator.create(challenge, ORIGIN) }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    passkey
+}
+
+async fn login_challenge(app: &Router) -> String {
+    let (status, options) = send(app, "POST", "/auth/passkey/options", None, json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    options["challenge"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_register_and_login_with_passkey() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+    let mut authenticator = Authenticator::new();
+
+    let passkey = register(&app, &token, &authenticator).await;
+    assert_eq!(passkey["name"], json!("Laptop"));
+
+    let challenge = login_challenge(&app).await;
+    let credential = authenticator.get(&challenge, user.id.as_bytes());
+    let (status, body) = send(&app, "POST", "/auth/passkey/login", None, json!({ "credential": credential })).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["access_token"].is_string());
+    assert!(body["refresh_token"].is_string());
+    assert_eq!(body["user"]["id"], json!(user.id));
+
+    let (status, passkeys) = send(&app, "GET", "/api/user/passkeys", Some(&token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(passkeys.as_array().unwrap().len(), 1);
+    assert!(passkeys[0]["last_used_at"].is_string());
+}
+
+#[tokio::test]
+async fn test_assertions_cannot_be_replayed() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+    let mut authenticator = Authenticator::new();
+
+    register(&app, &token, &authenticator).await;
+
+    let challenge = login_challenge(&app).await;
+    let credential = authenticator.get(&challenge, user.id.as_bytes());
+    let (status, _) = send(&app, "POST", "/auth/passkey/login", None, json!({ "credential": credential })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(&app, "POST", "/auth/passkey/login", None, json!({ "credential": credential })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // A fresh challenge signed with a counter that did not advance.
+    authenticator.sign_count -= 1;
+    let challenge = login_challenge(&app).await;
+    let credential = authenticator.get(&challenge, user.id.as_bytes());
+    let (status, _) = send(&app, "POST", "/auth/passkey/login", None, json!({ "credential": credential })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_registration_rejects_foreign_origin_and_reused_challenge() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+    let authenticator = Authenticator::new();
+
+    let (_, options) = send(&app, "POST", "/api/user/passkeys/options", Some(&token), json!({})).await;
+    let challenge = options["challenge"].as_str().unwrap();
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/user/passkeys",
+        Some(&token),
+        json!({ "credential": authenticator.create(challenge, "https://evil.example.com") }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/user/passkeys",
+        Some(&token),
+        json!({ "credential": authenticator.create("not-an-issued-challenge", ORIGIN) }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_deleted_passkey_can_no_longer_log_in() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+    let mut authenticator = Authenticator::new();
+
+    let passkey = register(&app, &token, &authenticator).await;
+    let uri = format!("/api/user/passkeys/{}", passkey["id"].as_str().unwrap());
+
+    let (status, _) = send(&app, "DELETE", &uri, Some(&token), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, "DELETE", &uri, Some(&token), Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let challenge = login_challenge(&app).await;
+    let credential = authenticator.get(&challenge, user.id.as_bytes());
+    let (status, _) = send(&app, "POST", "/auth/passkey/login", None, json!({ "credential": credential })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
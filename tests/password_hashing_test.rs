@@ -0,0 +1,86 @@
+use argon2::password_hash::{PasswordHasher, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum_api_template::auth::auth_hashing::{hash_password, password_needs_rehash, verify_password};
+use axum_api_template::db::models::user::NewUser;
+use axum_api_template::user::user_repository;
+use serde_json::json;
+use tower::ServiceExt;
+
+mod common;
+
+fn argon2_hash_with(algorithm: Algorithm, params: Params) -> String {
+    Argon2::new(algorithm, Version::V0x13, params)
+        .hash_password(b"password123", &SaltString::from_b64("c29tZXNhbHRzb21lc2FsdA").unwrap())
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_hash_password_uses_argon2id() {
+    let hash = hash_password("password123").unwrap();
+
+    assert!(hash.starts_with("$argon2id$v=19$"));
+    assert!(verify_password("password123", &hash).unwrap());
+    assert!(!verify_password("wrong-password", &hash).unwrap());
+    assert!(!password_needs_rehash(&hash));
+}
+
+#[test]
+fn test_bcrypt_hashes_still_verify_and_need_rehash() {
+    let hash = bcrypt::hash("password123", 4).unwrap();
+
+    assert!(verify_password("password123", &hash).unwrap());
+    assert!(!verify_password("wrong-password", &hash).unwrap());
+    assert!(password_needs_rehash(&hash));
+}
+
+#[test]
+fn test_outdated_argon2_parameters_need_rehash() {
+    let weaker = argon2_hash_with(Algorithm::Argon2id, Params::new(8, 1, 1, None).unwrap());
+    let argon2i = argon2_hash_with(Algorithm::Argon2i, Params::default());
+
+    assert!(verify_password("password123", &weaker).unwrap());
+    assert!(password_needs_rehash(&weaker));
+    assert!(verify_password("password123", &argon2i).unwrap());
+    assert!(password_needs_rehash(&argon2i));
+}
+
+#[tokio::test]
+async fn test_login_rehashes_bcrypt_password() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+
+    let new_user = NewUser {
+        first_name: "Legacy".to_string(),
+        last_name: "User".to_string(),
+        email: "legacy@example.com".to_string(),
+        password_hash: bcrypt::hash("password123", 4).unwrap(),
+        is_active: Some(true),
+        is_verified: Some(true),
+        locale: None,
+    };
+    user_repository::create_user(&new_user, &mut conn).unwrap();
+
+    for _ in 0..2 {
+        let request = Request::builder()
+            .uri("/auth/login")
+            .method("POST")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::to_vec(&json!({
+                    "email": "legacy@example.com",
+                    "password": "password123"
+                }))
+                .unwrap(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = user_repository::find_user_by_email("legacy@example.com", &mut conn).unwrap();
+        assert!(user.password_hash.starts_with("$argon2id$"));
+    }
+}
@@ -1,12 +1,60 @@
 use axum::body::Body;
 use axum::http::{header, Request, StatusCode};
+use axum_api_template::auth::auth_hashing::hash_token;
+use axum_api_template::user::user_repository;
 use http_body_util::BodyExt;
 use serde_json::{json, Value};
 use tower::ServiceExt;
 
 mod common;
 
+fn json_request(uri: &str, data: Value) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&data).unwrap()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_reset_token_is_stored_hashed_and_single_use() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    let request = json_request("/auth/forgot-password", json!({ "email": user.email }));
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let messages = common::wait_for_mail_to(&user.email, 1).await;
+    let token = common::extract_mail_token(&messages[0], "Reset token:").unwrap();
+    let stored = user_repository::find_user_by_email(&user.email, &mut conn).unwrap();
+
+    assert_ne!(stored.password_reset_token_hash.as_deref(), Some(token.as_str()));
+    assert_eq!(stored.password_reset_token_hash, Some(hash_token(&token)));
 
+    let request = json_request("/auth/reset-password", json!({
+        "token": token,
+        "new_password": "newpassword123"
+    }));
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let stored = user_repository::find_user_by_email(&user.email, &mut conn).unwrap();
+
+    assert!(stored.password_reset_token_hash.is_none());
+
+    let request = json_request("/auth/reset-password", json!({
+        "token": token,
+        "new_password": "anotherpassword123"
+    }));
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
 
 #[tokio::test]
 async fn test_forgot_password_with_nonexistent_email() {
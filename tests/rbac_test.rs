@@ -0,0 +1,129 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_api_template::auth::auth_tokens::validate_access_token;
+use axum_api_template::db::models::user_role::NewUserRole;
+use axum_api_template::rbac::rbac_repository;
+use diesel::PgConnection;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+async fn send(app: &Router, method: &str, uri: &str, token: &str, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body_bytes).unwrap_or(Value::Null))
+}
+
+fn grant_role(conn: &mut PgConnection, user_id: Uuid, role: &str) {
+    let role = rbac_repository::find_role_by_name(role, conn).unwrap().unwrap();
+    rbac_repository::assign_role(&NewUserRole { user_id, role_id: role.id }, conn).unwrap();
+}
+
+#[tokio::test]
+async fn test_admin_routes_require_permission() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let admin = common::create_test_user(&mut conn);
+    grant_role(&mut conn, admin.id, "admin");
+
+    let (status, _) = send(&app, "GET", "/api/admin/roles", &common::generate_test_token(user.id), Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, roles) = send(&app, "GET", "/api/admin/roles", &common::generate_test_token(admin.id), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    let admin_role = roles.as_array().unwrap().iter().find(|role| role["name"] == json!("admin")).unwrap();
+    assert_eq!(admin_role["permissions"], json!(["audit:read", "roles:manage", "users:read", "users:write"]));
+}
+
+#[tokio::test]
+async fn test_session_tokens_carry_roles() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let admin = common::create_test_user(&mut conn);
+    grant_role(&mut conn, admin.id, "admin");
+
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "email": admin.email, "password": "password123" })).unwrap(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes()).unwrap();
+
+    let claims = validate_access_token(body["access_token"].as_str().unwrap()).unwrap();
+    assert_eq!(claims.custom["roles"], json!(["admin"]));
+}
+
+#[tokio::test]
+async fn test_assign_and_remove_roles() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let admin = common::create_test_user(&mut conn);
+    let user = common::create_test_user(&mut conn);
+    grant_role(&mut conn, admin.id, "admin");
+    let admin_token = common::generate_test_token(admin.id);
+    let user_token = common::generate_test_token(user.id);
+
+    let uri = format!("/api/admin/users/{}/roles/admin", user.id);
+    let (status, body) = send(&app, "PUT", &uri, &admin_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["roles"], json!(["admin"]));
+
+    // Permissions follow the database, not the token.
+    let (status, _) = send(&app, "GET", "/api/admin/roles", &user_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send(&app, "DELETE", &uri, &admin_token, Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["roles"], json!([]));
+    let (status, _) = send(&app, "GET", "/api/admin/roles", &user_token, Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send(&app, "DELETE", &uri, &admin_token, Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, "PUT", &format!("/api/admin/users/{}/roles/owner", user.id), &admin_token, Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let self_uri = format!("/api/admin/users/{}/roles/admin", admin.id);
+    let (status, _) = send(&app, "DELETE", &self_uri, &admin_token, Value::Null).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_scoped_tokens_have_no_permissions() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let admin = common::create_test_user(&mut conn);
+    grant_role(&mut conn, admin.id, "admin");
+    let admin_token = common::generate_test_token(admin.id);
+
+    let (status, api_key) = send(
+        &app,
+        "POST",
+        "/api/user/api-keys",
+        &admin_token,
+        json!({ "name": "CI", "scopes": ["profile:read"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(&app, "GET", "/api/admin/roles", api_key["key"].as_str().unwrap(), Value::Null).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,185 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_api_template::auth::auth_hashing::hash_token;
+use axum_api_template::auth::auth_repository;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+// Tests that change REFRESH_TOKEN_REUSE_GRACE_SECONDS must not overlap.
+static GRACE_PERIOD_ENV: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+async fn post_json(app: &Router, uri: &str, data: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&data).unwrap()))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+
+    (status, serde_json::from_slice(&body_bytes).unwrap_or(Value::Null))
+}
+
+async fn login(app: &Router, email: &str) -> String {
+    let (status, body) = post_json(app, "/auth/login", json!({
+        "email": email,
+        "password": "password123"
+    })).await;
+
+    assert_eq!(status, StatusCode::OK);
+    body["refresh_token"].as_str().unwrap().to_string()
+}
+
+async fn refresh(app: &Router, refresh_token: &str) -> (StatusCode, Value) {
+    post_json(app, "/auth/refresh", json!({ "refresh_token": refresh_token })).await
+}
+
+#[tokio::test]
+async fn test_refresh_rotates_token() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    let first_token = login(&app, &user.email).await;
+    let (status, body) = refresh(&app, &first_token).await;
+
+    assert_eq!(status, StatusCode::OK);
+
+    let second_token = body["refresh_token"].as_str().unwrap().to_string();
+
+    assert_ne!(first_token, second_token);
+
+    let (status, _) = refresh(&app, &second_token).await;
+
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_refresh_token_is_stored_hashed() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    let refresh_token = login(&app, &user.email).await;
+
+    assert!(auth_repository::find_refresh_token(&refresh_token, &mut conn).is_err());
+
+    let stored = auth_repository::find_refresh_token(&hash_token(&refresh_token), &mut conn).unwrap();
+
+    assert_eq!(stored.user_id, user.id);
+    assert_ne!(stored.token_hash, refresh_token);
+}
+
+#[tokio::test]
+async fn test_reused_refresh_token_revokes_family() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let _env = GRACE_PERIOD_ENV.lock().await;
+    std::env::set_var("REFRESH_TOKEN_REUSE_GRACE_SECONDS", "0");
+
+    let first_token = login(&app, &user.email).await;
+    let (_, body) = refresh(&app, &first_token).await;
+    let second_token = body["refresh_token"].as_str().unwrap().to_string();
+
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let (status, body) = refresh(&app, &first_token).await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(body["message"].as_str().unwrap().contains("reuse detected"));
+
+    let (status, _) = refresh(&app, &second_token).await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let messages = common::wait_for_mail_to(&user.email, 1).await;
+
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("Subject: Suspicious sign-in activity on your account"));
+
+    std::env::remove_var("REFRESH_TOKEN_REUSE_GRACE_SECONDS");
+}
+
+#[tokio::test]
+async fn test_concurrent_refresh_within_grace_window() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let _env = GRACE_PERIOD_ENV.lock().await;
+    std::env::set_var("REFRESH_TOKEN_REUSE_GRACE_SECONDS", "30");
+
+    let first_token = login(&app, &user.email).await;
+    let (status, body) = refresh(&app, &first_token).await;
+    let second_token = body["refresh_token"].as_str().unwrap().to_string();
+
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = refresh(&app, &first_token).await;
+
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = refresh(&app, &second_token).await;
+
+    assert_eq!(status, StatusCode::OK);
+
+    std::env::remove_var("REFRESH_TOKEN_REUSE_GRACE_SECONDS");
+}
+
+#[tokio::test]
+async fn test_grace_window_allows_a_single_replay() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let _env = GRACE_PERIOD_ENV.lock().await;
+    std::env::set_var("REFRESH_TOKEN_REUSE_GRACE_SECONDS", "30");
+
+    let first_token = login(&app, &user.email).await;
+    let (status, _) = refresh(&app, &first_token).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = refresh(&app, &first_token).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = refresh(&app, &first_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(body["message"].as_str().unwrap().contains("reuse detected"));
+
+    std::env::remove_var("REFRESH_TOKEN_REUSE_GRACE_SECONDS");
+}
+
+#[tokio::test]
+async fn test_grace_window_does_not_cover_other_clients() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let _env = GRACE_PERIOD_ENV.lock().await;
+    std::env::set_var("REFRESH_TOKEN_REUSE_GRACE_SECONDS", "30");
+
+    let first_token = login(&app, &user.email).await;
+    let (status, body) = refresh(&app, &first_token).await;
+    assert_eq!(status, StatusCode::OK);
+    let second_token = body["refresh_token"].as_str().unwrap().to_string();
+
+    let request = Request::builder()
+        .uri("/auth/refresh")
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::USER_AGENT, "another-device")
+        .body(Body::from(serde_json::to_vec(&json!({ "refresh_token": first_token })).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let (status, _) = refresh(&app, &second_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    std::env::remove_var("REFRESH_TOKEN_REUSE_GRACE_SECONDS");
+}
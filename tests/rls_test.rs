@@ -0,0 +1,189 @@
+use axum_api_template::auth::auth_hashing::hash_token;
+use axum_api_template::auth::auth_repository;
+use axum_api_template::db::{with_connection, DbContext};
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sql_types::{Nullable, Text};
+use uuid::Uuid;
+
+mod common;
+
+#[derive(QueryableByName, Debug, PartialEq)]
+struct Settings {
+    #[diesel(sql_type = Nullable<diesel::sql_types::Uuid>)]
+    user_id: Option<Uuid>,
+    #[diesel(sql_type = Nullable<diesel::sql_types::Uuid>)]
+    organization_id: Option<Uuid>,
+}
+
+#[derive(QueryableByName)]
+struct Count {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct IdRow {
+    #[diesel(sql_type = diesel::sql_types::Uuid)]
+    id: Uuid,
+}
+
+fn read_settings(conn: &mut PgConnection) -> QueryResult<Settings> {
+    diesel::sql_query("SELECT app_current_user_id() AS user_id, app_current_organization_id() AS organization_id")
+        .get_result(conn)
+}
+
+fn count(conn: &mut PgConnection, table: &str) -> i64 {
+    diesel::sql_query(format!("SELECT COUNT(*) AS count FROM {}", table))
+        .get_result::<Count>(conn)
+        .unwrap()
+        .count
+}
+
+// Superusers bypass row-level security, so policies are checked as a
+// throwaway role inside a transaction that is rolled back.
+fn as_restricted_role<F>(conn: &mut PgConnection, f: F)
+where
+    F: FnOnce(&mut PgConnection),
+{
+    let _ = conn.transaction::<(), Error, _>(|conn| {
+        diesel::sql_query("CREATE ROLE rls_probe NOLOGIN").execute(conn)?;
+        diesel::sql_query("GRANT SELECT ON api_keys, memberships, invitations, refresh_tokens, users TO rls_probe").execute(conn)?;
+        diesel::sql_query("SET LOCAL ROLE rls_probe").execute(conn)?;
+        f(conn);
+        Err(Error::RollbackTransaction)
+    });
+}
+
+fn create_organization(conn: &mut PgConnection, members: &[Uuid]) -> Uuid {
+    let organization_id = Uuid::new_v4();
+    diesel::sql_query("INSERT INTO organizations (id, name, slug) VALUES ($1, 'Acme', $2)")
+        .bind::<diesel::sql_types::Uuid, _>(organization_id)
+        .bind::<Text, _>(format!("org-{}", organization_id.simple()))
+        .execute(conn)
+        .unwrap();
+    for member in members {
+        diesel::sql_query("INSERT INTO memberships (organization_id, user_id, role) VALUES ($1, $2, 'member')")
+            .bind::<diesel::sql_types::Uuid, _>(organization_id)
+            .bind::<diesel::sql_types::Uuid, _>(*member)
+            .execute(conn)
+            .unwrap();
+    }
+    organization_id
+}
+
+#[tokio::test]
+async fn test_connections_carry_the_request_context() {
+    let _conn = common::setup_test_db();
+    let pool = common::TEST_POOL.clone();
+    let context = DbContext {
+        user_id: Some(Uuid::new_v4()),
+        organization_id: Some(Uuid::new_v4()),
+    };
+
+    let settings = context
+        .scope(with_connection(&pool, |conn| Ok(read_settings(conn)?)))
+        .await
+        .unwrap();
+    assert_eq!(settings, Settings { user_id: context.user_id, organization_id: context.organization_id });
+
+    // `SET LOCAL` ends with the transaction, so nothing leaks to the next checkout.
+    for _ in 0..3 {
+        let settings = with_connection(&pool, |conn| Ok(read_settings(conn)?)).await.unwrap();
+        assert_eq!(settings, Settings { user_id: None, organization_id: None });
+    }
+}
+
+#[tokio::test]
+async fn test_policies_isolate_user_rows() {
+    let mut conn = common::setup_test_db();
+    let alice = common::create_test_user(&mut conn);
+    let bob = common::create_test_user(&mut conn);
+    for user in [&alice, &bob] {
+        diesel::sql_query("INSERT INTO api_keys (user_id, name, prefix, key_hash) VALUES ($1, 'CI', 'pat_0', $2)")
+            .bind::<diesel::sql_types::Uuid, _>(user.id)
+            .bind::<Text, _>(Uuid::new_v4().to_string())
+            .execute(&mut *conn)
+            .unwrap();
+    }
+
+    as_restricted_role(&mut conn, |conn| {
+        assert_eq!(count(conn, "api_keys"), 2);
+
+        DbContext { user_id: Some(alice.id), organization_id: None }.apply(conn).unwrap();
+        assert_eq!(count(conn, "api_keys"), 1);
+        let owner: Uuid = diesel::sql_query("SELECT user_id AS id FROM api_keys")
+            .get_result::<IdRow>(conn)
+            .unwrap()
+            .id;
+        assert_eq!(owner, alice.id);
+    });
+}
+
+#[tokio::test]
+async fn test_policies_isolate_tenants() {
+    let mut conn = common::setup_test_db();
+    let alice = common::create_test_user(&mut conn);
+    let bob = common::create_test_user(&mut conn);
+    let carol = common::create_test_user(&mut conn);
+    let shared = create_organization(&mut conn, &[alice.id, bob.id]);
+    let other = create_organization(&mut conn, &[carol.id]);
+
+    for (organization_id, email) in [(shared, "new@example.com".to_string()), (other, alice.email.to_lowercase())] {
+        diesel::sql_query(
+            "INSERT INTO invitations (organization_id, email, role, token_hash, expires_at) \
+             VALUES ($1, $2, 'member', $3, NOW() + INTERVAL '1 day')",
+        )
+        .bind::<diesel::sql_types::Uuid, _>(organization_id)
+        .bind::<Text, _>(email)
+        .bind::<Text, _>(Uuid::new_v4().to_string())
+        .execute(&mut *conn)
+        .unwrap();
+    }
+
+    as_restricted_role(&mut conn, |conn| {
+        assert_eq!(count(conn, "memberships"), 3);
+        assert_eq!(count(conn, "invitations"), 2);
+
+        // Bob, with the shared organization active, sees its members and invitations only.
+        DbContext { user_id: Some(bob.id), organization_id: Some(shared) }.apply(conn).unwrap();
+        assert_eq!(count(conn, "memberships"), 2);
+        assert_eq!(count(conn, "invitations"), 1);
+
+        // Without an active organization, Alice sees her own membership and
+        // the invitation addressed to her.
+        DbContext { user_id: Some(alice.id), organization_id: None }.apply(conn).unwrap();
+        assert_eq!(count(conn, "memberships"), 1);
+        assert_eq!(count(conn, "invitations"), 1);
+
+        DbContext { user_id: Some(carol.id), organization_id: Some(other) }.apply(conn).unwrap();
+        assert_eq!(count(conn, "memberships"), 1);
+        assert_eq!(count(conn, "invitations"), 1);
+    });
+}
+
+#[tokio::test]
+async fn test_refresh_tokens_are_hidden_from_other_users() {
+    let mut conn = common::setup_test_db();
+    let alice = common::create_test_user(&mut conn);
+    let bob = common::create_test_user(&mut conn);
+    for user in [&alice, &bob] {
+        diesel::sql_query(
+            "INSERT INTO refresh_tokens (user_id, token_hash, expires_at, family_id) \
+             VALUES ($1, $2, NOW() + INTERVAL '1 day', gen_random_uuid())",
+        )
+        .bind::<diesel::sql_types::Uuid, _>(user.id)
+        .bind::<Text, _>(hash_token(&user.id.to_string()))
+        .execute(&mut *conn)
+        .unwrap();
+    }
+
+    as_restricted_role(&mut conn, |conn| {
+        DbContext { user_id: Some(alice.id), organization_id: None }.apply(conn).unwrap();
+        assert_eq!(count(conn, "refresh_tokens"), 1);
+
+        // A lookup that forgets to filter by user still can't reach Bob's token.
+        assert!(auth_repository::find_refresh_token(&hash_token(&alice.id.to_string()), conn).is_ok());
+        assert!(auth_repository::find_refresh_token(&hash_token(&bob.id.to_string()), conn).is_err());
+    });
+}
@@ -0,0 +1,229 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_api_template::schema::refresh_tokens;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+const FIREFOX_ON_WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:128.0) Gecko/20100101 Firefox/128.0";
+const CHROME_ON_MACOS: &str =
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36";
+
+async fn send(app: &Router, method: &str, uri: &str, token: Option<&str>, user_agent: &str, body: Value) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::USER_AGENT, user_agent);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request.body(Body::from(serde_json::to_vec(&body).unwrap())).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body_bytes).unwrap_or(Value::Null))
+}
+
+async fn login(app: &Router, email: &str, user_agent: &str) -> Value {
+    let (status, body) = send(
+        app,
+        "POST",
+        "/auth/login",
+        None,
+        user_agent,
+        json!({ "email": email, "password": "password123" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    body
+}
+
+async fn list_sessions(app: &Router, access_token: &str) -> Vec<Value> {
+    let (status, sessions) = send(app, "GET", "/api/user/sessions", Some(access_token), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    sessions.as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn test_sessions_list_devices_and_follow_refresh() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    let laptop = login(&app, &user.email, FIREFOX_ON_WINDOWS).await;
+    let desktop = login(&app, &user.email, CHROME_ON_MACOS).await;
+
+    let sessions = list_sessions(&app, laptop["access_token"].as_str().unwrap()).await;
+    assert_eq!(sessions.len(), 2);
+
+    let current: Vec<&Value> = sessions.iter().filter(|session| session["current"] == json!(true)).collect();
+    assert_eq!(current.len(), 1);
+    assert_eq!(current[0]["device_label"], json!("Firefox on Windows"));
+    assert_eq!(current[0]["user_agent"], json!(FIREFOX_ON_WINDOWS));
+    assert!(sessions.iter().any(|session| session["device_label"] == json!("Chrome on macOS")));
+
+    // Refreshing keeps the session and its start time.
+    let desktop_session = sessions.iter().find(|session| session["current"] == json!(false)).unwrap();
+    let (status, refreshed) = send(
+        &app,
+        "POST",
+        "/auth/refresh",
+        None,
+        CHROME_ON_MACOS,
+        json!({ "refresh_token": desktop["refresh_token"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let sessions = list_sessions(&app, refreshed["access_token"].as_str().unwrap()).await;
+    assert_eq!(sessions.len(), 2);
+    let refreshed_session = sessions.iter().find(|session| session["current"] == json!(true)).unwrap();
+    assert_eq!(refreshed_session["id"], desktop_session["id"]);
+    assert_eq!(refreshed_session["created_at"], desktop_session["created_at"]);
+}
+
+#[tokio::test]
+async fn test_revoking_a_session_ends_its_tokens() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let other_user = common::create_test_user(&mut conn);
+
+    let laptop = login(&app, &user.email, FIREFOX_ON_WINDOWS).await;
+    let desktop = login(&app, &user.email, CHROME_ON_MACOS).await;
+    let laptop_token = laptop["access_token"].as_str().unwrap();
+    let desktop_token = desktop["access_token"].as_str().unwrap();
+
+    let sessions = list_sessions(&app, laptop_token).await;
+    let desktop_session = sessions.iter().find(|session| session["current"] == json!(false)).unwrap();
+    let uri = format!("/api/user/sessions/{}", desktop_session["id"].as_str().unwrap());
+
+    let other_token = common::generate_test_token(other_user.id);
+    let (status, _) = send(&app, "DELETE", &uri, Some(&other_token), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, "DELETE", &uri, Some(laptop_token), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(&app, "GET", "/api/user/profile", Some(desktop_token), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/auth/refresh",
+        None,
+        CHROME_ON_MACOS,
+        json!({ "refresh_token": desktop["refresh_token"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send(&app, "GET", "/api/user/profile", Some(laptop_token), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list_sessions(&app, laptop_token).await.len(), 1);
+
+    let (status, _) = send(&app, "DELETE", &uri, Some(laptop_token), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_revoke_other_sessions_keeps_the_current_one() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    let current = login(&app, &user.email, FIREFOX_ON_WINDOWS).await;
+    let other = login(&app, &user.email, CHROME_ON_MACOS).await;
+    login(&app, &user.email, "curl/8.5.0").await;
+    let current_token = current["access_token"].as_str().unwrap();
+
+    let (status, body) =
+        send(&app, "POST", "/api/user/sessions/revoke-others", Some(current_token), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["revoked"], json!(2));
+
+    let sessions = list_sessions(&app, current_token).await;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["current"], json!(true));
+
+    let (status, _) = send(&app, "GET", "/api/user/profile", Some(other["access_token"].as_str().unwrap()), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Tokens that don't belong to a session can't tell which one to keep.
+    let token = common::generate_test_token(user.id);
+    let (status, _) = send(&app, "POST", "/api/user/sessions/revoke-others", Some(&token), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_logout_only_ends_the_current_session() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    let laptop = login(&app, &user.email, FIREFOX_ON_WINDOWS).await;
+    let desktop = login(&app, &user.email, CHROME_ON_MACOS).await;
+    let desktop_token = desktop["access_token"].as_str().unwrap();
+
+    let (status, _) = send(&app, "POST", "/api/logout", laptop["access_token"].as_str(), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/auth/refresh",
+        None,
+        FIREFOX_ON_WINDOWS,
+        json!({ "refresh_token": laptop["refresh_token"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let sessions = list_sessions(&app, desktop_token).await;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["current"], json!(true));
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/auth/refresh",
+        None,
+        CHROME_ON_MACOS,
+        json!({ "refresh_token": desktop["refresh_token"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_logout_everywhere_revokes_sessions_instead_of_deleting_them() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+
+    let laptop = login(&app, &user.email, FIREFOX_ON_WINDOWS).await;
+    login(&app, &user.email, CHROME_ON_MACOS).await;
+
+    let (status, _) =
+        send(&app, "POST", "/api/logout-all", laptop["access_token"].as_str(), "test", Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The rows stay behind as revoked sessions, so tokens naming them are refused.
+    let revoked_at: Vec<Option<NaiveDateTime>> = refresh_tokens::table
+        .filter(refresh_tokens::user_id.eq(user.id))
+        .select(refresh_tokens::revoked_at)
+        .load(&mut *conn)
+        .unwrap();
+    assert_eq!(revoked_at.len(), 2);
+    assert!(revoked_at.iter().all(Option::is_some));
+}
@@ -0,0 +1,257 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use chrono::Utc;
+use serde_json::{json, Value};
+use totp_rs::{Algorithm, Secret, TOTP};
+use tower::ServiceExt;
+
+mod common;
+
+async fn post_json(app: &Router, uri: &str, token: Option<&str>, body: Value) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .uri(uri)
+        .method("POST")
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = request.body(Body::from(serde_json::to_vec(&body).unwrap())).unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+// Code for the time step `offset` steps away from now.
+fn code_at(secret: &str, offset: i64) -> String {
+    let totp = TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        30,
+        Secret::Encoded(secret.to_string()).to_bytes().unwrap(),
+        None,
+        "test".to_string(),
+    )
+    .unwrap();
+    totp.generate((Utc::now().timestamp() + offset * 30) as u64)
+}
+
+async fn enable_totp(app: &Router, token: &str) -> (String, Vec<String>) {
+    let (status, enrollment) = post_json(app, "/api/user/2fa/totp", Some(token), json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(enrollment["provisioning_uri"].as_str().unwrap().starts_with("otpauth://totp/"));
+    let secret = enrollment["secret"].as_str().unwrap().to_string();
+
+    let (status, body) = post_json(
+        app,
+        "/api/user/2fa/totp/confirm",
+        Some(token),
+        json!({ "code": code_at(&secret, 0) }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let recovery_codes = body["recovery_codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|code| code.as_str().unwrap().to_string())
+        .collect();
+
+    (secret, recovery_codes)
+}
+
+async fn login_challenge(app: &Router, email: &str) -> String {
+    let (status, body) = post_json(
+        app,
+        "/auth/login",
+        None,
+        json!({ "email": email, "password": "password123" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["mfa_required"], json!(true));
+    assert!(body.get("access_token").is_none());
+    body["mfa_token"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_login_requires_totp_once_enabled() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let (secret, recovery_codes) = enable_totp(&app, &token).await;
+    assert_eq!(recovery_codes.len(), 10);
+
+    let mfa_token = login_challenge(&app, &user.email).await;
+
+    let (status, _) = post_json(
+        &app,
+        "/auth/2fa/verify",
+        None,
+        json!({ "mfa_token": mfa_token, "code": "000000" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = post_json(
+        &app,
+        "/auth/2fa/verify",
+        None,
+        json!({ "mfa_token": mfa_token, "code": code_at(&secret, 1) }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["access_token"].is_string());
+    assert!(body["refresh_token"].is_string());
+
+    let (status, _) = post_json(
+        &app,
+        "/auth/2fa/verify",
+        None,
+        json!({ "mfa_token": mfa_token, "code": code_at(&secret, 1) }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_totp_codes_cannot_be_replayed() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let (secret, _) = enable_totp(&app, &token).await;
+
+    let first_token = login_challenge(&app, &user.email).await;
+    let second_token = login_challenge(&app, &user.email).await;
+
+    // The confirmation already used the current step.
+    let (status, _) = post_json(
+        &app,
+        "/auth/2fa/verify",
+        None,
+        json!({ "mfa_token": first_token, "code": code_at(&secret, 0) }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = post_json(
+        &app,
+        "/auth/2fa/verify",
+        None,
+        json!({ "mfa_token": first_token, "code": code_at(&secret, 1) }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = post_json(
+        &app,
+        "/auth/2fa/verify",
+        None,
+        json!({ "mfa_token": second_token, "code": code_at(&secret, 1) }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_recovery_codes_are_single_use() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let (_, recovery_codes) = enable_totp(&app, &token).await;
+
+    let mfa_token = login_challenge(&app, &user.email).await;
+    let (status, _) = post_json(
+        &app,
+        "/auth/2fa/verify",
+        None,
+        json!({ "mfa_token": mfa_token, "code": recovery_codes[0].to_uppercase() }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let mfa_token = login_challenge(&app, &user.email).await;
+    let (status, _) = post_json(
+        &app,
+        "/auth/2fa/verify",
+        None,
+        json!({ "mfa_token": mfa_token, "code": recovery_codes[0] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_disable_totp_restores_password_login() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let (_, recovery_codes) = enable_totp(&app, &token).await;
+
+    let (status, _) = post_json(&app, "/api/user/2fa/disable", Some(&token), json!({ "code": "abcde-fghjk" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = post_json(
+        &app,
+        "/api/user/2fa/disable",
+        Some(&token),
+        json!({ "code": recovery_codes[1] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = post_json(
+        &app,
+        "/auth/login",
+        None,
+        json!({ "email": user.email, "password": "password123" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["access_token"].is_string());
+    assert!(body.get("mfa_required").is_none());
+}
+
+#[tokio::test]
+async fn test_mfa_challenge_is_revoked_after_repeated_failures() {
+    let mut conn = common::setup_test_db();
+    let app = common::setup_test_app();
+    let user = common::create_test_user(&mut conn);
+    let token = common::generate_test_token(user.id);
+
+    let (secret, _) = enable_totp(&app, &token).await;
+    let mfa_token = login_challenge(&app, &user.email).await;
+
+    // A fresh User-Agent per attempt gets past the rate limiter, so only the
+    // challenge itself stops the guessing.
+    let verify = |code: String, attempt: usize| {
+        let body = json!({ "mfa_token": mfa_token, "code": code });
+        let request = Request::builder()
+            .uri("/auth/2fa/verify")
+            .method("POST")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::USER_AGENT, format!("guesser-{}", attempt))
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap();
+        app.clone().oneshot(request)
+    };
+
+    for attempt in 0..5 {
+        let response = verify("000000".to_string(), attempt).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    let response = verify(code_at(&secret, 1), 5).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -1,7 +1,5 @@
 use axum::body::Body;
 use axum::http::{header, Request, StatusCode};
-use http_body_util::BodyExt;
-use serde_json::{json, Value};
 use tower::ServiceExt;
 
 mod common;